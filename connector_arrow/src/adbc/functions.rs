@@ -0,0 +1,689 @@
+//! Functions of the ADBC C API.
+//!
+//! Safety requirements of all functions are those of the ADBC C API: pointers must be
+//! null or valid, and objects must be initialized and released in the documented order.
+#![allow(non_snake_case, clippy::missing_safety_doc)]
+
+use std::convert::TryFrom;
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use arrow::array::{RecordBatch, RecordBatchIterator, RecordBatchReader, StringArray, StructArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::ffi::{from_ffi, FFI_ArrowArray, FFI_ArrowSchema};
+use arrow::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
+
+use super::*;
+use crate::dynamic::{DynConnector, QueryStream};
+
+#[derive(Default)]
+struct DatabaseState {
+    uri: Option<String>,
+}
+
+#[derive(Default)]
+struct ConnectionState {
+    conn: Option<Box<dyn DynConnector>>,
+
+    /// Set while a result stream borrows the connection.
+    streaming: Arc<AtomicBool>,
+}
+
+impl ConnectionState {
+    fn connector(&mut self) -> Result<&mut Box<dyn DynConnector>, Failure> {
+        if self.streaming.load(Ordering::Acquire) {
+            return Err(Failure::invalid_state(
+                "a result stream of the connection is not released",
+            ));
+        }
+        self.conn
+            .as_mut()
+            .ok_or_else(|| Failure::invalid_state("connection is not initialized"))
+    }
+}
+
+struct StatementState {
+    conn: *mut ConnectionState,
+    query: Option<String>,
+    bound: Option<(SchemaRef, Vec<RecordBatch>)>,
+    ingest_table: Option<String>,
+    ingest_create: bool,
+}
+
+unsafe fn state<T, S>(private_data: Option<&mut T>) -> Result<&mut S, Failure>
+where
+    T: PrivateData,
+{
+    let ptr = private_data.map_or(std::ptr::null_mut(), |t| t.private_data());
+    (ptr as *mut S)
+        .as_mut()
+        .ok_or_else(|| Failure::invalid_state("not initialized"))
+}
+
+trait PrivateData {
+    fn private_data(&mut self) -> *mut c_void;
+}
+
+impl PrivateData for AdbcDatabase {
+    fn private_data(&mut self) -> *mut c_void {
+        self.private_data
+    }
+}
+
+impl PrivateData for AdbcConnection {
+    fn private_data(&mut self) -> *mut c_void {
+        self.private_data
+    }
+}
+
+impl PrivateData for AdbcStatement {
+    fn private_data(&mut self) -> *mut c_void {
+        self.private_data
+    }
+}
+
+unsafe fn string_arg(ptr: *const c_char, name: &str) -> Result<Option<String>, Failure> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    let s = CStr::from_ptr(ptr).to_str().map_err(|_| {
+        Failure::new(
+            ADBC_STATUS_INVALID_ARGUMENT,
+            format!("{name} is not valid UTF-8"),
+        )
+    })?;
+    Ok(Some(s.to_string()))
+}
+
+unsafe fn required_arg(ptr: *const c_char, name: &str) -> Result<String, Failure> {
+    string_arg(ptr, name)?
+        .ok_or_else(|| Failure::new(ADBC_STATUS_INVALID_ARGUMENT, format!("{name} is null")))
+}
+
+unsafe fn export_batches(
+    out: *mut FFI_ArrowArrayStream,
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+) -> Result<(), Failure> {
+    if out.is_null() {
+        return Err(Failure::new(ADBC_STATUS_INVALID_ARGUMENT, "out is null"));
+    }
+    let reader = RecordBatchIterator::new(batches.into_iter().map(Ok), schema);
+    std::ptr::write(out, FFI_ArrowArrayStream::new(Box::new(reader)));
+    Ok(())
+}
+
+// --- database ---
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcDatabaseNew(
+    database: *mut AdbcDatabase,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let database = database
+            .as_mut()
+            .ok_or_else(|| Failure::new(ADBC_STATUS_INVALID_ARGUMENT, "database is null"))?;
+        let state = Box::<DatabaseState>::default();
+        database.private_data = Box::into_raw(state) as *mut c_void;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcDatabaseSetOption(
+    database: *mut AdbcDatabase,
+    key: *const c_char,
+    value: *const c_char,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let state: &mut DatabaseState = state(database.as_mut())?;
+
+        let key = required_arg(key, "key")?;
+        match key.as_str() {
+            ADBC_OPTION_URI => {
+                state.uri = Some(required_arg(value, "value")?);
+                Ok(())
+            }
+            _ => Err(Failure::not_implemented(&format!("option {key}"))),
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcDatabaseInit(
+    database: *mut AdbcDatabase,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let state: &mut DatabaseState = state(database.as_mut())?;
+        if state.uri.is_none() {
+            return Err(Failure::invalid_state("option uri is not set"));
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcDatabaseRelease(
+    database: *mut AdbcDatabase,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let _: &mut DatabaseState = state(database.as_mut())?;
+        let database = &mut *database;
+        drop(Box::from_raw(database.private_data as *mut DatabaseState));
+        database.private_data = std::ptr::null_mut();
+        Ok(())
+    })
+}
+
+// --- connection ---
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionNew(
+    connection: *mut AdbcConnection,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let connection = connection
+            .as_mut()
+            .ok_or_else(|| Failure::new(ADBC_STATUS_INVALID_ARGUMENT, "connection is null"))?;
+        let state = Box::<ConnectionState>::default();
+        connection.private_data = Box::into_raw(state) as *mut c_void;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionSetOption(
+    connection: *mut AdbcConnection,
+    key: *const c_char,
+    value: *const c_char,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let _: &mut ConnectionState = state(connection.as_mut())?;
+
+        let key = required_arg(key, "key")?;
+        let value = required_arg(value, "value")?;
+        match (key.as_str(), value.as_str()) {
+            (ADBC_CONNECTION_OPTION_AUTOCOMMIT, "true") => Ok(()),
+            (ADBC_CONNECTION_OPTION_AUTOCOMMIT, _) => Err(Failure::not_implemented("transactions")),
+            _ => Err(Failure::not_implemented(&format!("option {key}"))),
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionInit(
+    connection: *mut AdbcConnection,
+    database: *mut AdbcDatabase,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let conn_state: &mut ConnectionState = state(connection.as_mut())?;
+        let db_state: &mut DatabaseState = state(database.as_mut())?;
+
+        if conn_state.conn.is_some() {
+            return Err(Failure::invalid_state("connection is already initialized"));
+        }
+        let uri = db_state
+            .uri
+            .as_deref()
+            .ok_or_else(|| Failure::invalid_state("option uri is not set"))?;
+
+        conn_state.conn = Some(crate::connect(uri)?);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionRelease(
+    connection: *mut AdbcConnection,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let state: &mut ConnectionState = state(connection.as_mut())?;
+        if state.streaming.load(Ordering::Acquire) {
+            return Err(Failure::invalid_state(
+                "a result stream of the connection is not released",
+            ));
+        }
+        let connection = &mut *connection;
+        drop(Box::from_raw(
+            connection.private_data as *mut ConnectionState,
+        ));
+        connection.private_data = std::ptr::null_mut();
+        Ok(())
+    })
+}
+
+unsafe fn connector<'a>(
+    connection: *mut AdbcConnection,
+) -> Result<&'a mut Box<dyn DynConnector>, Failure> {
+    let state: &mut ConnectionState = state(connection.as_mut())?;
+    state.connector()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionCommit(
+    connection: *mut AdbcConnection,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        connector(connection)?;
+        Err(Failure::invalid_state("connection is in autocommit mode"))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionRollback(
+    connection: *mut AdbcConnection,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        connector(connection)?;
+        Err(Failure::invalid_state("connection is in autocommit mode"))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionGetInfo(
+    _connection: *mut AdbcConnection,
+    _info_codes: *const u32,
+    _info_codes_length: usize,
+    _out: *mut FFI_ArrowArrayStream,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        Err(Failure::not_implemented("AdbcConnectionGetInfo"))
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn AdbcConnectionGetObjects(
+    connection: *mut AdbcConnection,
+    depth: c_int,
+    catalog: *const c_char,
+    db_schema: *const c_char,
+    table_name: *const c_char,
+    table_type: *const *const c_char,
+    column_name: *const c_char,
+    out: *mut FFI_ArrowArrayStream,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let conn = connector(connection)?;
+
+        let mut table_types = None;
+        if !table_type.is_null() {
+            let mut types = Vec::new();
+            let mut ptr = table_type;
+            while !(*ptr).is_null() {
+                types.push(required_arg(*ptr, "table_type")?);
+                ptr = ptr.add(1);
+            }
+            table_types = Some(types);
+        }
+
+        let filter = super::objects::Filter {
+            catalog: string_arg(catalog, "catalog")?,
+            db_schema: string_arg(db_schema, "db_schema")?,
+            table_name: string_arg(table_name, "table_name")?,
+            table_types,
+            column_name: string_arg(column_name, "column_name")?,
+        };
+
+        let batch = super::objects::get_objects(conn.as_mut(), depth, &filter)?;
+        export_batches(out, batch.schema(), vec![batch])
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionGetTableSchema(
+    connection: *mut AdbcConnection,
+    _catalog: *const c_char,
+    _db_schema: *const c_char,
+    table_name: *const c_char,
+    schema: *mut FFI_ArrowSchema,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let conn = connector(connection)?;
+        let table_name = required_arg(table_name, "table_name")?;
+        if schema.is_null() {
+            return Err(Failure::new(ADBC_STATUS_INVALID_ARGUMENT, "schema is null"));
+        }
+
+        let table_schema = conn.table_get(&table_name)?;
+        let ffi_schema = FFI_ArrowSchema::try_from(table_schema.as_ref())?;
+        std::ptr::write(schema, ffi_schema);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionGetTableTypes(
+    connection: *mut AdbcConnection,
+    out: *mut FFI_ArrowArrayStream,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        connector(connection)?;
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "table_type",
+            DataType::Utf8,
+            false,
+        )]));
+        let table_types = StringArray::from(vec![super::objects::TABLE_TYPE]);
+        let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(table_types)])?;
+        export_batches(out, schema, vec![batch])
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcConnectionReadPartition(
+    _connection: *mut AdbcConnection,
+    _serialized_partition: *const u8,
+    _serialized_length: usize,
+    _out: *mut FFI_ArrowArrayStream,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        Err(Failure::not_implemented("partitioned results"))
+    })
+}
+
+// --- statement ---
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementNew(
+    connection: *mut AdbcConnection,
+    statement: *mut AdbcStatement,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        connector(connection)?;
+        let statement = statement
+            .as_mut()
+            .ok_or_else(|| Failure::new(ADBC_STATUS_INVALID_ARGUMENT, "statement is null"))?;
+
+        let state = Box::new(StatementState {
+            conn: (*connection).private_data as *mut ConnectionState,
+            query: None,
+            bound: None,
+            ingest_table: None,
+            ingest_create: true,
+        });
+        statement.private_data = Box::into_raw(state) as *mut c_void;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementRelease(
+    statement: *mut AdbcStatement,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let _: &mut StatementState = state(statement.as_mut())?;
+        let statement = &mut *statement;
+        drop(Box::from_raw(statement.private_data as *mut StatementState));
+        statement.private_data = std::ptr::null_mut();
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementSetSqlQuery(
+    statement: *mut AdbcStatement,
+    query: *const c_char,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let state: &mut StatementState = state(statement.as_mut())?;
+        state.query = Some(required_arg(query, "query")?);
+        state.ingest_table = None;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementSetSubstraitPlan(
+    _statement: *mut AdbcStatement,
+    _plan: *const u8,
+    _length: usize,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || Err(Failure::not_implemented("Substrait")))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementSetOption(
+    statement: *mut AdbcStatement,
+    key: *const c_char,
+    value: *const c_char,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let state: &mut StatementState = state(statement.as_mut())?;
+
+        let key = required_arg(key, "key")?;
+        let value = required_arg(value, "value")?;
+        match (key.as_str(), value.as_str()) {
+            (ADBC_INGEST_OPTION_TARGET_TABLE, _) => {
+                state.ingest_table = Some(value);
+                state.query = None;
+            }
+            (ADBC_INGEST_OPTION_MODE, ADBC_INGEST_OPTION_MODE_CREATE) => {
+                state.ingest_create = true;
+            }
+            (ADBC_INGEST_OPTION_MODE, ADBC_INGEST_OPTION_MODE_APPEND) => {
+                state.ingest_create = false;
+            }
+            (ADBC_INGEST_OPTION_MODE, _) => {
+                return Err(Failure::not_implemented(&format!("ingest mode {value}")));
+            }
+            _ => return Err(Failure::not_implemented(&format!("option {key}"))),
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementPrepare(
+    statement: *mut AdbcStatement,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let state: &mut StatementState = state(statement.as_mut())?;
+        if state.query.is_none() && state.ingest_table.is_none() {
+            return Err(Failure::invalid_state("query is not set"));
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementGetParameterSchema(
+    _statement: *mut AdbcStatement,
+    _schema: *mut FFI_ArrowSchema,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || Err(Failure::not_implemented("parameter schema")))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementBind(
+    statement: *mut AdbcStatement,
+    values: *mut FFI_ArrowArray,
+    schema: *mut FFI_ArrowSchema,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let state: &mut StatementState = state(statement.as_mut())?;
+        if values.is_null() || schema.is_null() {
+            return Err(Failure::new(
+                ADBC_STATUS_INVALID_ARGUMENT,
+                "values or schema is null",
+            ));
+        }
+
+        // take ownership, so the data is released even if import fails
+        let values = FFI_ArrowArray::from_raw(values);
+        let schema = FFI_ArrowSchema::from_raw(schema);
+
+        let data = from_ffi(values, &schema)?;
+        let batch = RecordBatch::from(StructArray::from(data));
+        state.bound = Some((batch.schema(), vec![batch]));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementBindStream(
+    statement: *mut AdbcStatement,
+    stream: *mut FFI_ArrowArrayStream,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let state: &mut StatementState = state(statement.as_mut())?;
+        if stream.is_null() {
+            return Err(Failure::new(ADBC_STATUS_INVALID_ARGUMENT, "stream is null"));
+        }
+
+        let reader = ArrowArrayStreamReader::from_raw(stream)?;
+        let schema = arrow::record_batch::RecordBatchReader::schema(&reader);
+        let batches = reader.collect::<Result<Vec<_>, _>>()?;
+        state.bound = Some((schema, batches));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementExecuteQuery(
+    statement: *mut AdbcStatement,
+    out: *mut FFI_ArrowArrayStream,
+    rows_affected: *mut i64,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        let state: &mut StatementState = state(statement.as_mut())?;
+        let conn_state = &mut *state.conn;
+        let conn = conn_state.connector()?;
+
+        let affected = if let Some(table_name) = &state.ingest_table {
+            ingest(
+                conn.as_mut(),
+                table_name,
+                state.ingest_create,
+                &mut state.bound,
+            )?
+        } else if let Some(query) = &state.query {
+            let params = state.bound.as_ref().map(|(_, batches)| batches.as_slice());
+
+            // the stream borrows the connection until it is released, which ADBC requires
+            // to happen before the connection is used again
+            let conn: &'static mut dyn DynConnector = &mut *(conn.as_mut() as *mut _);
+            let stream = QueryStream::new(conn, query, params)?;
+            if out.is_null() {
+                for batch in stream {
+                    batch?;
+                }
+            } else {
+                let stream = ResultStream {
+                    inner: stream,
+                    _guard: StreamingGuard::new(&conn_state.streaming),
+                };
+                std::ptr::write(out, FFI_ArrowArrayStream::new(Box::new(stream)));
+            }
+            -1
+        } else {
+            return Err(Failure::invalid_state("query is not set"));
+        };
+
+        if let Some(rows_affected) = rows_affected.as_mut() {
+            *rows_affected = affected;
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn AdbcStatementExecutePartitions(
+    _statement: *mut AdbcStatement,
+    _schema: *mut FFI_ArrowSchema,
+    _partitions: *mut AdbcPartitions,
+    _rows_affected: *mut i64,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    run(error, || {
+        Err(Failure::not_implemented("partitioned results"))
+    })
+}
+
+/// Results of a query, exported from [AdbcStatementExecuteQuery].
+struct ResultStream {
+    inner: QueryStream<'static>,
+    // declared after the stream, so the connection is not used until the stream is dropped
+    _guard: StreamingGuard,
+}
+
+// SAFETY: ADBC requires that a connection and its result streams are not used concurrently,
+// so the connection can be used from the thread that reads the stream
+unsafe impl Send for ResultStream {}
+
+impl Iterator for ResultStream {
+    type Item = Result<RecordBatch, arrow::error::ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl RecordBatchReader for ResultStream {
+    fn schema(&self) -> SchemaRef {
+        self.inner.schema()
+    }
+}
+
+/// Marks the connection as borrowed by a result stream, until dropped.
+struct StreamingGuard(Arc<AtomicBool>);
+
+impl StreamingGuard {
+    fn new(streaming: &Arc<AtomicBool>) -> Self {
+        streaming.store(true, Ordering::Release);
+        StreamingGuard(streaming.clone())
+    }
+}
+
+impl Drop for StreamingGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// Appends bound data into the table, creating it first if needed.
+fn ingest(
+    conn: &mut dyn DynConnector,
+    table_name: &str,
+    create: bool,
+    bound: &mut Option<(SchemaRef, Vec<RecordBatch>)>,
+) -> Result<i64, Failure> {
+    let (schema, batches) = bound
+        .take()
+        .ok_or_else(|| Failure::invalid_state("no data is bound for ingestion"))?;
+
+    if create {
+        conn.table_create(table_name, schema)?;
+    }
+
+    let num_rows = batches.iter().map(|b| b.num_rows() as i64).sum();
+    conn.append(table_name, batches)?;
+    Ok(num_rows)
+}
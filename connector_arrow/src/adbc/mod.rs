@@ -0,0 +1,474 @@
+//! [ADBC](https://arrow.apache.org/adbc/) driver, so ADBC driver managers can load
+//! the connector_arrow cdylib.
+//!
+//! The driver implements version 1.0.0 of the ADBC C API on top of [crate::dynamic].
+//! The database is chosen with the `uri` option, which accepts the URLs of
+//! [crate::connect]:
+//!
+//! ```python
+//! import adbc_driver_manager.dbapi
+//!
+//! conn = adbc_driver_manager.dbapi.connect(
+//!     driver="libconnector_arrow.so",
+//!     db_kwargs={"uri": "sqlite://:memory:"},
+//! )
+//! ```
+//!
+//! Supported:
+//! - queries, with parameters bound with `AdbcStatementBind` or `AdbcStatementBindStream`,
+//! - bulk ingest, by setting the `adbc.ingest.target_table` statement option,
+//! - `AdbcConnectionGetObjects`, `AdbcConnectionGetTableSchema` and
+//!   `AdbcConnectionGetTableTypes`.
+//!
+//! Connections are always in autocommit mode. Catalogs and database schemas are not
+//! exposed: all tables are reported in a single catalog and schema, both without a name.
+//!
+//! All functions are exported both as symbols and via the table filled in by [AdbcDriverInit].
+
+mod functions;
+mod objects;
+
+use std::ffi::{c_char, c_int, c_void, CString};
+
+use arrow::ffi::{FFI_ArrowArray, FFI_ArrowSchema};
+use arrow::ffi_stream::FFI_ArrowArrayStream;
+
+use crate::errors::{ConnectorError, DbErrorKind, TableCreateError};
+
+pub use functions::*;
+
+pub type AdbcStatusCode = u8;
+
+pub const ADBC_STATUS_OK: AdbcStatusCode = 0;
+pub const ADBC_STATUS_UNKNOWN: AdbcStatusCode = 1;
+pub const ADBC_STATUS_NOT_IMPLEMENTED: AdbcStatusCode = 2;
+pub const ADBC_STATUS_NOT_FOUND: AdbcStatusCode = 3;
+pub const ADBC_STATUS_ALREADY_EXISTS: AdbcStatusCode = 4;
+pub const ADBC_STATUS_INVALID_ARGUMENT: AdbcStatusCode = 5;
+pub const ADBC_STATUS_INVALID_STATE: AdbcStatusCode = 6;
+pub const ADBC_STATUS_INVALID_DATA: AdbcStatusCode = 7;
+pub const ADBC_STATUS_INTEGRITY: AdbcStatusCode = 8;
+pub const ADBC_STATUS_INTERNAL: AdbcStatusCode = 9;
+pub const ADBC_STATUS_IO: AdbcStatusCode = 10;
+pub const ADBC_STATUS_CANCELLED: AdbcStatusCode = 11;
+pub const ADBC_STATUS_TIMEOUT: AdbcStatusCode = 12;
+pub const ADBC_STATUS_UNAUTHENTICATED: AdbcStatusCode = 13;
+pub const ADBC_STATUS_UNAUTHORIZED: AdbcStatusCode = 14;
+
+pub const ADBC_VERSION_1_0_0: c_int = 1_000_000;
+
+pub const ADBC_OBJECT_DEPTH_ALL: c_int = 0;
+pub const ADBC_OBJECT_DEPTH_CATALOGS: c_int = 1;
+pub const ADBC_OBJECT_DEPTH_DB_SCHEMAS: c_int = 2;
+pub const ADBC_OBJECT_DEPTH_TABLES: c_int = 3;
+pub const ADBC_OBJECT_DEPTH_COLUMNS: c_int = ADBC_OBJECT_DEPTH_ALL;
+
+pub const ADBC_OPTION_URI: &str = "uri";
+pub const ADBC_CONNECTION_OPTION_AUTOCOMMIT: &str = "adbc.connection.autocommit";
+pub const ADBC_INGEST_OPTION_TARGET_TABLE: &str = "adbc.ingest.target_table";
+pub const ADBC_INGEST_OPTION_MODE: &str = "adbc.ingest.mode";
+pub const ADBC_INGEST_OPTION_MODE_CREATE: &str = "adbc.ingest.mode.create";
+pub const ADBC_INGEST_OPTION_MODE_APPEND: &str = "adbc.ingest.mode.append";
+
+#[repr(C)]
+pub struct AdbcError {
+    pub message: *mut c_char,
+    pub vendor_code: i32,
+    pub sqlstate: [c_char; 5],
+    pub release: Option<unsafe extern "C" fn(*mut AdbcError)>,
+}
+
+#[repr(C)]
+pub struct AdbcDatabase {
+    pub private_data: *mut c_void,
+    pub private_driver: *mut AdbcDriver,
+}
+
+#[repr(C)]
+pub struct AdbcConnection {
+    pub private_data: *mut c_void,
+    pub private_driver: *mut AdbcDriver,
+}
+
+#[repr(C)]
+pub struct AdbcStatement {
+    pub private_data: *mut c_void,
+    pub private_driver: *mut AdbcDriver,
+}
+
+#[repr(C)]
+pub struct AdbcPartitions {
+    pub num_partitions: usize,
+    pub partitions: *mut *const u8,
+    pub partition_lengths: *const usize,
+    pub private_data: *mut c_void,
+    pub release: Option<unsafe extern "C" fn(*mut AdbcPartitions)>,
+}
+
+type Fn1<T> = Option<unsafe extern "C" fn(*mut T, *mut AdbcError) -> AdbcStatusCode>;
+
+#[repr(C)]
+#[allow(non_snake_case)]
+pub struct AdbcDriver {
+    pub private_data: *mut c_void,
+    pub private_manager: *mut c_void,
+
+    pub release: Fn1<AdbcDriver>,
+
+    pub DatabaseInit: Fn1<AdbcDatabase>,
+    pub DatabaseNew: Fn1<AdbcDatabase>,
+    pub DatabaseSetOption: Option<
+        unsafe extern "C" fn(
+            *mut AdbcDatabase,
+            *const c_char,
+            *const c_char,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub DatabaseRelease: Fn1<AdbcDatabase>,
+
+    pub ConnectionCommit: Fn1<AdbcConnection>,
+    pub ConnectionGetInfo: Option<
+        unsafe extern "C" fn(
+            *mut AdbcConnection,
+            *const u32,
+            usize,
+            *mut FFI_ArrowArrayStream,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub ConnectionGetObjects: Option<
+        unsafe extern "C" fn(
+            *mut AdbcConnection,
+            c_int,
+            *const c_char,
+            *const c_char,
+            *const c_char,
+            *const *const c_char,
+            *const c_char,
+            *mut FFI_ArrowArrayStream,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub ConnectionGetTableSchema: Option<
+        unsafe extern "C" fn(
+            *mut AdbcConnection,
+            *const c_char,
+            *const c_char,
+            *const c_char,
+            *mut FFI_ArrowSchema,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub ConnectionGetTableTypes: Option<
+        unsafe extern "C" fn(
+            *mut AdbcConnection,
+            *mut FFI_ArrowArrayStream,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub ConnectionInit: Option<
+        unsafe extern "C" fn(
+            *mut AdbcConnection,
+            *mut AdbcDatabase,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub ConnectionNew: Fn1<AdbcConnection>,
+    pub ConnectionSetOption: Option<
+        unsafe extern "C" fn(
+            *mut AdbcConnection,
+            *const c_char,
+            *const c_char,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub ConnectionReadPartition: Option<
+        unsafe extern "C" fn(
+            *mut AdbcConnection,
+            *const u8,
+            usize,
+            *mut FFI_ArrowArrayStream,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub ConnectionRelease: Fn1<AdbcConnection>,
+    pub ConnectionRollback: Fn1<AdbcConnection>,
+
+    pub StatementBind: Option<
+        unsafe extern "C" fn(
+            *mut AdbcStatement,
+            *mut FFI_ArrowArray,
+            *mut FFI_ArrowSchema,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub StatementBindStream: Option<
+        unsafe extern "C" fn(
+            *mut AdbcStatement,
+            *mut FFI_ArrowArrayStream,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub StatementExecuteQuery: Option<
+        unsafe extern "C" fn(
+            *mut AdbcStatement,
+            *mut FFI_ArrowArrayStream,
+            *mut i64,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub StatementExecutePartitions: Option<
+        unsafe extern "C" fn(
+            *mut AdbcStatement,
+            *mut FFI_ArrowSchema,
+            *mut AdbcPartitions,
+            *mut i64,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub StatementGetParameterSchema: Option<
+        unsafe extern "C" fn(
+            *mut AdbcStatement,
+            *mut FFI_ArrowSchema,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub StatementNew: Option<
+        unsafe extern "C" fn(
+            *mut AdbcConnection,
+            *mut AdbcStatement,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub StatementPrepare: Fn1<AdbcStatement>,
+    pub StatementRelease: Fn1<AdbcStatement>,
+    pub StatementSetOption: Option<
+        unsafe extern "C" fn(
+            *mut AdbcStatement,
+            *const c_char,
+            *const c_char,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+    pub StatementSetSqlQuery: Option<
+        unsafe extern "C" fn(*mut AdbcStatement, *const c_char, *mut AdbcError) -> AdbcStatusCode,
+    >,
+    pub StatementSetSubstraitPlan: Option<
+        unsafe extern "C" fn(
+            *mut AdbcStatement,
+            *const u8,
+            usize,
+            *mut AdbcError,
+        ) -> AdbcStatusCode,
+    >,
+}
+
+/// Entrypoint of the driver, which fills the function table of the driver.
+///
+/// # Safety
+///
+/// `raw_driver` must point to a writable [AdbcDriver] and `error` must be null or
+/// point to a valid [AdbcError].
+#[no_mangle]
+pub unsafe extern "C" fn AdbcDriverInit(
+    version: c_int,
+    raw_driver: *mut c_void,
+    error: *mut AdbcError,
+) -> AdbcStatusCode {
+    if version != ADBC_VERSION_1_0_0 {
+        let failure = Failure::new(
+            ADBC_STATUS_NOT_IMPLEMENTED,
+            format!("ADBC version {version} is not supported"),
+        );
+        return failure.report(error);
+    }
+    if raw_driver.is_null() {
+        return Failure::new(ADBC_STATUS_INVALID_ARGUMENT, "driver is null").report(error);
+    }
+
+    let driver = AdbcDriver {
+        private_data: std::ptr::null_mut(),
+        private_manager: std::ptr::null_mut(),
+        release: Some(driver_release),
+
+        DatabaseInit: Some(AdbcDatabaseInit),
+        DatabaseNew: Some(AdbcDatabaseNew),
+        DatabaseSetOption: Some(AdbcDatabaseSetOption),
+        DatabaseRelease: Some(AdbcDatabaseRelease),
+
+        ConnectionCommit: Some(AdbcConnectionCommit),
+        ConnectionGetInfo: Some(AdbcConnectionGetInfo),
+        ConnectionGetObjects: Some(AdbcConnectionGetObjects),
+        ConnectionGetTableSchema: Some(AdbcConnectionGetTableSchema),
+        ConnectionGetTableTypes: Some(AdbcConnectionGetTableTypes),
+        ConnectionInit: Some(AdbcConnectionInit),
+        ConnectionNew: Some(AdbcConnectionNew),
+        ConnectionSetOption: Some(AdbcConnectionSetOption),
+        ConnectionReadPartition: Some(AdbcConnectionReadPartition),
+        ConnectionRelease: Some(AdbcConnectionRelease),
+        ConnectionRollback: Some(AdbcConnectionRollback),
+
+        StatementBind: Some(AdbcStatementBind),
+        StatementBindStream: Some(AdbcStatementBindStream),
+        StatementExecuteQuery: Some(AdbcStatementExecuteQuery),
+        StatementExecutePartitions: Some(AdbcStatementExecutePartitions),
+        StatementGetParameterSchema: Some(AdbcStatementGetParameterSchema),
+        StatementNew: Some(AdbcStatementNew),
+        StatementPrepare: Some(AdbcStatementPrepare),
+        StatementRelease: Some(AdbcStatementRelease),
+        StatementSetOption: Some(AdbcStatementSetOption),
+        StatementSetSqlQuery: Some(AdbcStatementSetSqlQuery),
+        StatementSetSubstraitPlan: Some(AdbcStatementSetSubstraitPlan),
+    };
+    std::ptr::write(raw_driver as *mut AdbcDriver, driver);
+    ADBC_STATUS_OK
+}
+
+unsafe extern "C" fn driver_release(
+    driver: *mut AdbcDriver,
+    _error: *mut AdbcError,
+) -> AdbcStatusCode {
+    if let Some(driver) = driver.as_mut() {
+        driver.release = None;
+    }
+    ADBC_STATUS_OK
+}
+
+/// An error, as it is reported over the C API.
+struct Failure {
+    status: AdbcStatusCode,
+    message: String,
+    sqlstate: Option<String>,
+}
+
+impl Failure {
+    fn new(status: AdbcStatusCode, message: impl Into<String>) -> Self {
+        Failure {
+            status,
+            message: message.into(),
+            sqlstate: None,
+        }
+    }
+
+    fn invalid_state(message: impl Into<String>) -> Self {
+        Self::new(ADBC_STATUS_INVALID_STATE, message)
+    }
+
+    fn not_implemented(feature: &str) -> Self {
+        Self::new(
+            ADBC_STATUS_NOT_IMPLEMENTED,
+            format!("{feature} is not supported by connector_arrow"),
+        )
+    }
+
+    /// Writes the failure into `error` and returns its status code.
+    unsafe fn report(self, error: *mut AdbcError) -> AdbcStatusCode {
+        let Some(error) = error.as_mut() else {
+            return self.status;
+        };
+        if let Some(release) = error.release {
+            release(error);
+        }
+
+        let message = CString::new(self.message.replace('\0', "")).unwrap();
+        error.message = message.into_raw();
+        error.vendor_code = 0;
+        error.sqlstate = [0; 5];
+        if let Some(sqlstate) = self.sqlstate.filter(|s| s.len() == 5) {
+            for (dst, src) in error.sqlstate.iter_mut().zip(sqlstate.bytes()) {
+                *dst = src as c_char;
+            }
+        }
+        error.release = Some(error_release);
+
+        self.status
+    }
+}
+
+unsafe extern "C" fn error_release(error: *mut AdbcError) {
+    let Some(error) = error.as_mut() else {
+        return;
+    };
+    if !error.message.is_null() {
+        drop(CString::from_raw(error.message));
+        error.message = std::ptr::null_mut();
+    }
+    error.release = None;
+}
+
+impl From<ConnectorError> for Failure {
+    fn from(value: ConnectorError) -> Self {
+        let details = value.db_error();
+
+        let status = match (&value, details.as_ref().map(|d| d.kind)) {
+            (ConnectorError::NotSupported { .. }, _) => ADBC_STATUS_NOT_IMPLEMENTED,
+            (ConnectorError::InvalidUrl(_), _) => ADBC_STATUS_INVALID_ARGUMENT,
+            (ConnectorError::DataSchemaMismatch(_), _)
+            | (ConnectorError::IncompatibleSchema { .. }, _)
+            | (ConnectorError::DataOutOfRange, _) => ADBC_STATUS_INVALID_DATA,
+            (_, Some(DbErrorKind::Syntax)) => ADBC_STATUS_INVALID_ARGUMENT,
+            (_, Some(DbErrorKind::ConstraintViolation)) => ADBC_STATUS_INTEGRITY,
+            (_, Some(DbErrorKind::ConnectionLost)) => ADBC_STATUS_IO,
+            (_, Some(DbErrorKind::Timeout)) => ADBC_STATUS_TIMEOUT,
+            _ => ADBC_STATUS_UNKNOWN,
+        };
+
+        // some drivers only describe the error in its source
+        let mut message = value.to_string();
+        let mut source = std::error::Error::source(&value);
+        while let Some(s) = source {
+            let s_message = s.to_string();
+            if !message.contains(&s_message) {
+                message += ": ";
+                message += &s_message;
+            }
+            source = s.source();
+        }
+
+        Failure {
+            status,
+            message,
+            sqlstate: details.and_then(|d| d.code),
+        }
+    }
+}
+
+impl From<arrow::error::ArrowError> for Failure {
+    fn from(value: arrow::error::ArrowError) -> Self {
+        Failure::from(ConnectorError::from(value))
+    }
+}
+
+impl From<TableCreateError> for Failure {
+    fn from(value: TableCreateError) -> Self {
+        match value {
+            TableCreateError::TableExists => {
+                Failure::new(ADBC_STATUS_ALREADY_EXISTS, "table already exists")
+            }
+            TableCreateError::Connector(e) => e.into(),
+        }
+    }
+}
+
+/// Runs the body of an exported function, reporting its failure or panic into `error`.
+unsafe fn run<F>(error: *mut AdbcError, f: F) -> AdbcStatusCode
+where
+    F: FnOnce() -> Result<(), Failure>,
+{
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(Failure::new(
+            ADBC_STATUS_INTERNAL,
+            format!("panic: {message}"),
+        ))
+    });
+    match res {
+        Ok(()) => ADBC_STATUS_OK,
+        Err(failure) => failure.report(error),
+    }
+}
@@ -0,0 +1,287 @@
+//! Result of `AdbcConnectionGetObjects`.
+
+use std::ffi::c_int;
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::buffer::{NullBuffer, OffsetBuffer};
+use arrow::datatypes::{DataType, Field, FieldRef, Fields, Schema};
+
+use super::{
+    Failure, ADBC_OBJECT_DEPTH_CATALOGS, ADBC_OBJECT_DEPTH_DB_SCHEMAS, ADBC_OBJECT_DEPTH_TABLES,
+};
+use crate::api::METADATA_DB_TYPE;
+use crate::dynamic::DynConnector;
+
+/// The only table type reported by the driver.
+pub(super) const TABLE_TYPE: &str = "table";
+
+pub(super) struct Filter {
+    pub catalog: Option<String>,
+    pub db_schema: Option<String>,
+    pub table_name: Option<String>,
+    pub table_types: Option<Vec<String>>,
+    pub column_name: Option<String>,
+}
+
+pub(super) fn get_objects(
+    conn: &mut dyn DynConnector,
+    depth: c_int,
+    filter: &Filter,
+) -> Result<RecordBatch, Failure> {
+    // there is a single catalog and a single schema, both without a name,
+    // which only match a filter of an empty string
+    let matches_unnamed = |f: &Option<String>| f.as_deref().is_none_or(str::is_empty);
+    let num_catalogs = if matches_unnamed(&filter.catalog) && matches_unnamed(&filter.db_schema) {
+        1
+    } else {
+        0
+    };
+
+    let db_schemas = if depth == ADBC_OBJECT_DEPTH_CATALOGS {
+        new_null_array(&DataType::List(db_schema_item()), num_catalogs)
+    } else {
+        let tables = if depth == ADBC_OBJECT_DEPTH_DB_SCHEMAS {
+            new_null_array(&DataType::List(table_item()), num_catalogs)
+        } else if num_catalogs == 0 {
+            new_empty_array(&DataType::List(table_item()))
+        } else {
+            let tables = tables(conn, depth, filter)?;
+            let offsets = OffsetBuffer::from_lengths([tables.len()]);
+            Arc::new(ListArray::new(table_item(), offsets, tables, None))
+        };
+
+        let db_schemas = StructArray::new(
+            db_schema_fields(),
+            vec![new_null_array(&DataType::Utf8, num_catalogs), tables],
+            None,
+        );
+        let offsets = OffsetBuffer::from_lengths(vec![1; num_catalogs]);
+        Arc::new(ListArray::new(
+            db_schema_item(),
+            offsets,
+            Arc::new(db_schemas),
+            None,
+        ))
+    };
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("catalog_name", DataType::Utf8, true),
+        Field::new("catalog_db_schemas", DataType::List(db_schema_item()), true),
+    ]));
+    let catalog_names = new_null_array(&DataType::Utf8, num_catalogs);
+    Ok(RecordBatch::try_new(
+        schema,
+        vec![catalog_names, db_schemas],
+    )?)
+}
+
+fn tables(conn: &mut dyn DynConnector, depth: c_int, filter: &Filter) -> Result<ArrayRef, Failure> {
+    let type_matches = filter
+        .table_types
+        .as_ref()
+        .is_none_or(|types| types.iter().any(|t| t == TABLE_TYPE));
+
+    let mut table_names = Vec::new();
+    if type_matches {
+        for name in conn.table_list()? {
+            if like(filter.table_name.as_deref(), &name) {
+                table_names.push(name);
+            }
+        }
+    }
+    let num_tables = table_names.len();
+
+    let (columns, constraints) = if depth == ADBC_OBJECT_DEPTH_TABLES {
+        (
+            new_null_array(&DataType::List(column_item()), num_tables),
+            new_null_array(&DataType::List(constraint_item()), num_tables),
+        )
+    } else {
+        let mut lengths = Vec::with_capacity(num_tables);
+        let mut names = Vec::new();
+        let mut positions = Vec::new();
+        let mut type_names = Vec::new();
+        let mut nullables = Vec::new();
+        for table_name in &table_names {
+            let schema = conn.table_get(table_name)?;
+
+            let mut len = 0;
+            for (index, field) in schema.fields().iter().enumerate() {
+                if !like(filter.column_name.as_deref(), field.name()) {
+                    continue;
+                }
+                names.push(field.name().clone());
+                positions.push(index as i32 + 1);
+                type_names.push(field.metadata().get(METADATA_DB_TYPE).cloned());
+                nullables.push(field.is_nullable());
+                len += 1;
+            }
+            lengths.push(len);
+        }
+
+        let columns = columns(names, positions, type_names, nullables);
+        let columns = ListArray::new(
+            column_item(),
+            OffsetBuffer::from_lengths(lengths),
+            columns,
+            None,
+        );
+
+        // constraints are not known, so they are reported as empty lists
+        let constraints = ListArray::new(
+            constraint_item(),
+            OffsetBuffer::new_zeroed(num_tables),
+            new_empty_array(constraint_item().data_type()),
+            None,
+        );
+        (
+            Arc::new(columns) as ArrayRef,
+            Arc::new(constraints) as ArrayRef,
+        )
+    };
+
+    let table_types = vec![TABLE_TYPE; num_tables];
+    let tables = StructArray::new(
+        table_fields(),
+        vec![
+            Arc::new(StringArray::from(table_names)),
+            Arc::new(StringArray::from(table_types)),
+            columns,
+            constraints,
+        ],
+        None,
+    );
+    Ok(Arc::new(tables))
+}
+
+fn columns(
+    names: Vec<String>,
+    positions: Vec<i32>,
+    type_names: Vec<Option<String>>,
+    nullables: Vec<bool>,
+) -> ArrayRef {
+    let len = names.len();
+    let xdbc_nullable: Int16Array = nullables.iter().map(|n| Some(*n as i16)).collect();
+    let xdbc_is_nullable: StringArray = nullables
+        .iter()
+        .map(|n| Some(if *n { "YES" } else { "NO" }))
+        .collect();
+
+    let fields = column_fields();
+    let arrays = fields
+        .iter()
+        .map(|field| -> ArrayRef {
+            match field.name().as_str() {
+                "column_name" => Arc::new(StringArray::from(names.clone())),
+                "ordinal_position" => Arc::new(Int32Array::from(positions.clone())),
+                "xdbc_type_name" => Arc::new(StringArray::from(type_names.clone())),
+                "xdbc_nullable" => Arc::new(xdbc_nullable.clone()),
+                "xdbc_is_nullable" => Arc::new(xdbc_is_nullable.clone()),
+                _ => new_null_array(field.data_type(), len),
+            }
+        })
+        .collect();
+    Arc::new(StructArray::new(fields, arrays, None::<NullBuffer>))
+}
+
+/// Matches a pattern of SQL LIKE, where `%` matches any sequence and `_` any character.
+/// Missing pattern matches everything.
+fn like(pattern: Option<&str>, value: &str) -> bool {
+    fn matches(pattern: &[char], value: &[char]) -> bool {
+        match pattern.split_first() {
+            None => value.is_empty(),
+            Some(('%', rest)) => (0..=value.len()).any(|i| matches(rest, &value[i..])),
+            Some(('_', rest)) => !value.is_empty() && matches(rest, &value[1..]),
+            Some((c, rest)) => value.first() == Some(c) && matches(rest, &value[1..]),
+        }
+    }
+
+    let Some(pattern) = pattern else {
+        return true;
+    };
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+    matches(&pattern, &value)
+}
+
+fn db_schema_item() -> FieldRef {
+    Arc::new(Field::new_struct("item", db_schema_fields(), true))
+}
+
+fn db_schema_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("db_schema_name", DataType::Utf8, true),
+        Field::new("db_schema_tables", DataType::List(table_item()), true),
+    ])
+}
+
+fn table_item() -> FieldRef {
+    Arc::new(Field::new_struct("item", table_fields(), true))
+}
+
+fn table_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("table_name", DataType::Utf8, false),
+        Field::new("table_type", DataType::Utf8, false),
+        Field::new("table_columns", DataType::List(column_item()), true),
+        Field::new("table_constraints", DataType::List(constraint_item()), true),
+    ])
+}
+
+fn column_item() -> FieldRef {
+    Arc::new(Field::new_struct("item", column_fields(), true))
+}
+
+fn column_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("column_name", DataType::Utf8, false),
+        Field::new("ordinal_position", DataType::Int32, true),
+        Field::new("remarks", DataType::Utf8, true),
+        Field::new("xdbc_data_type", DataType::Int16, true),
+        Field::new("xdbc_type_name", DataType::Utf8, true),
+        Field::new("xdbc_column_size", DataType::Int32, true),
+        Field::new("xdbc_decimal_digits", DataType::Int16, true),
+        Field::new("xdbc_num_prec_radix", DataType::Int16, true),
+        Field::new("xdbc_nullable", DataType::Int16, true),
+        Field::new("xdbc_column_def", DataType::Utf8, true),
+        Field::new("xdbc_sql_data_type", DataType::Int16, true),
+        Field::new("xdbc_datetime_sub", DataType::Int16, true),
+        Field::new("xdbc_char_octet_length", DataType::Int32, true),
+        Field::new("xdbc_is_nullable", DataType::Utf8, true),
+        Field::new("xdbc_scope_catalog", DataType::Utf8, true),
+        Field::new("xdbc_scope_schema", DataType::Utf8, true),
+        Field::new("xdbc_scope_table", DataType::Utf8, true),
+        Field::new("xdbc_is_autoincrement", DataType::Boolean, true),
+        Field::new("xdbc_is_generatedcolumn", DataType::Boolean, true),
+    ])
+}
+
+fn constraint_item() -> FieldRef {
+    Arc::new(Field::new_struct("item", constraint_fields(), true))
+}
+
+fn constraint_fields() -> Fields {
+    let usage_fields = Fields::from(vec![
+        Field::new("fk_catalog", DataType::Utf8, true),
+        Field::new("fk_db_schema", DataType::Utf8, true),
+        Field::new("fk_table", DataType::Utf8, false),
+        Field::new("fk_column_name", DataType::Utf8, false),
+    ]);
+    let usage_item = Field::new_struct("item", usage_fields, true);
+
+    Fields::from(vec![
+        Field::new("constraint_name", DataType::Utf8, true),
+        Field::new("constraint_type", DataType::Utf8, false),
+        Field::new(
+            "constraint_column_names",
+            DataType::new_list(DataType::Utf8, true),
+            false,
+        ),
+        Field::new(
+            "constraint_column_usage",
+            DataType::List(Arc::new(usage_item)),
+            true,
+        ),
+    ])
+}
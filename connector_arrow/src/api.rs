@@ -11,6 +11,7 @@ use arrow::datatypes::{DataType, SchemaRef};
 use arrow::record_batch::RecordBatch;
 
 use crate::errors::{ConnectorError, TableCreateError, TableDropError};
+use crate::util::ResultRecordBatchReader;
 
 /// Ability to query data from a data store and append data into the data store.
 pub trait Connector {
@@ -56,6 +57,17 @@ pub trait Statement<'conn> {
 pub trait ResultReader<'stmt>: Iterator<Item = Result<RecordBatch, ConnectorError>> {
     /// Return the schema of the result.
     fn get_schema(&mut self) -> Result<SchemaRef, ConnectorError>;
+
+    /// Convert into arrow's [arrow::record_batch::RecordBatchReader], so the results can be
+    /// passed to other crates of the arrow ecosystem. Retrieves the schema eagerly.
+    fn into_record_batch_reader(
+        self,
+    ) -> Result<ResultRecordBatchReader<'stmt, Self>, ConnectorError>
+    where
+        Self: Sized,
+    {
+        ResultRecordBatchReader::new(self)
+    }
 }
 
 /// Key of the metadata on [arrow::datatypes::Field] that stores the name of the database type
@@ -108,11 +120,18 @@ pub mod unimplemented {
             &mut self,
             _: arrow::record_batch::RecordBatch,
         ) -> Result<(), crate::ConnectorError> {
-            unimplemented!()
+            Err(not_supported())
         }
 
         fn finish(self) -> Result<(), crate::ConnectorError> {
-            unimplemented!()
+            Err(not_supported())
+        }
+    }
+
+    fn not_supported() -> crate::ConnectorError {
+        crate::ConnectorError::NotSupported {
+            connector_name: "connector_arrow",
+            feature: "append".into(),
         }
     }
 }
@@ -1,21 +1,91 @@
+use arrow::array::Array;
 use arrow::datatypes::*;
 use arrow::record_batch::RecordBatch;
+use arrow::temporal_conversions::EPOCH_DAYS_FROM_CE;
+use chrono::{DateTime, Datelike, NaiveDate};
 use duckdb::{types::Value, Appender};
 use itertools::zip_eq;
 use itertools::Itertools;
 
+use super::scan;
 use crate::impl_consume_unsupported;
-use crate::types::{ArrowType, FixedSizeBinaryType, NullType};
-use crate::util::transport::{self, Consume, ConsumeTy};
+use crate::types::{
+    ArrowType, FixedSizeBinaryType, FixedSizeListType, LargeListType, ListType, MapType, NullType,
+    StructType,
+};
+use crate::util::escape::escaped_ident;
+use crate::util::registry;
+use crate::util::transport::{Consume, ConsumeTy};
 use crate::util::ArrayCellRef;
 use crate::{api::Append, ConnectorError};
 
 pub struct DuckDBAppender<'conn> {
     pub(super) inner: Appender<'conn>,
+    pub(super) conn: &'conn duckdb::Connection,
+    pub(super) owner: registry::Owner,
+    pub(super) table_name: String,
+
+    /// Schema of the table we are appending to.
+    pub(super) table_schema: SchemaRef,
 }
 
+/// Number of rows in a DuckDB data chunk (STANDARD_VECTOR_SIZE).
+const VECTOR_SIZE: usize = 2048;
+
 impl<'conn> Append<'conn> for DuckDBAppender<'conn> {
     fn append(&mut self, batch: RecordBatch) -> Result<(), ConnectorError> {
+        let schema = batch.schema();
+        let types = || schema.fields().iter().map(|f| f.data_type());
+        if types().any(is_scanned) {
+            if types().all(scan::is_scannable) {
+                return self.append_scanned(batch);
+            }
+            // appending rows fails for nested columns and intervals and truncates nanoseconds
+            return self.append_rows(batch);
+        }
+        if !is_native_compatible(&self.table_schema, &batch) {
+            return self.append_rows(batch);
+        }
+        if batch.columns().iter().any(|c| c.null_count() > 0) {
+            return self.append_scanned(batch);
+        }
+
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            let len = VECTOR_SIZE.min(batch.num_rows() - offset);
+            self.inner.append_record_batch(batch.slice(offset, len))?;
+            offset += len;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
+}
+
+impl<'conn> DuckDBAppender<'conn> {
+    /// Inserts the batch from [scan::ArrowScan], which, unlike the Arrow appender, writes nulls.
+    /// Also used for nested columns, which cannot be appended row by row.
+    fn append_scanned(&mut self, batch: RecordBatch) -> Result<(), ConnectorError> {
+        // rows buffered by the appender must be written first to keep the order
+        self.inner.flush()?;
+
+        let id = scan::register(self.owner, &self.table_name, batch.schema(), vec![batch])?;
+        let res = self.conn.execute(
+            &format!(
+                "INSERT INTO {} SELECT * FROM {}",
+                escaped_ident(&self.table_name),
+                scan::call(self.owner, id)
+            ),
+            [],
+        );
+        registry::unregister(id);
+        res?;
+        Ok(())
+    }
+
+    fn append_rows(&mut self, batch: RecordBatch) -> Result<(), ConnectorError> {
         let schema = batch.schema();
         let mut cell_refs = zip_eq(batch.columns(), schema.fields())
             .map(|(array, field)| ArrayCellRef {
@@ -25,12 +95,13 @@ impl<'conn> Append<'conn> for DuckDBAppender<'conn> {
             })
             .collect_vec();
 
+        let transporters = ArrayCellRef::transporters(schema.fields())?;
         for row_number in 0..batch.num_rows() {
             let mut row: Vec<Value> = Vec::new();
 
-            for cell_ref in &mut cell_refs {
+            for (cell_ref, transport) in zip_eq(&mut cell_refs, &transporters) {
                 cell_ref.row_number = row_number;
-                transport::transport(cell_ref.field, cell_ref as &_, &mut row).unwrap();
+                transport(cell_ref, &mut row)?;
             }
             let row = duckdb::appender_params_from_iter(row);
             self.inner.append_row(row)?;
@@ -38,10 +109,40 @@ impl<'conn> Append<'conn> for DuckDBAppender<'conn> {
 
         Ok(())
     }
+}
 
-    fn finish(self) -> Result<(), ConnectorError> {
-        Ok(())
-    }
+/// Nested columns and intervals cannot be appended row by row, and the appender converts
+/// timestamps into microseconds, which overflow or lose precision of the other units.
+fn is_scanned(ty: &DataType) -> bool {
+    ty.is_nested()
+        || matches!(ty, DataType::Interval(IntervalUnit::MonthDayNano))
+        || matches!(ty, DataType::Timestamp(unit, _) if unit != &TimeUnit::Microsecond)
+}
+
+/// DuckDB's Arrow appender does not convert between types and supports only some of the
+/// Arrow types. This checks that it will append the batch losslessly.
+/// It also ignores validity bitmaps, so batches with nulls are scanned instead.
+fn is_native_compatible(table_schema: &Schema, batch: &RecordBatch) -> bool {
+    table_schema.fields().len() == batch.num_columns()
+        && zip_eq(table_schema.fields(), batch.columns()).all(|(field, array)| {
+            let is_supported = matches!(
+                array.data_type(),
+                DataType::Boolean
+                    | DataType::Int8
+                    | DataType::Int16
+                    | DataType::Int32
+                    | DataType::Int64
+                    | DataType::UInt8
+                    | DataType::UInt16
+                    | DataType::UInt32
+                    | DataType::UInt64
+                    | DataType::Float32
+                    | DataType::Float64
+                    | DataType::Timestamp(TimeUnit::Microsecond, None)
+                    | DataType::Utf8
+            );
+            is_supported && field.data_type() == array.data_type()
+        })
 }
 
 impl Consume for Vec<Value> {}
@@ -53,37 +154,47 @@ macro_rules! impl_consume_ty {
 
     ($ArrTy: ty, $value_kind: expr, $conversion: expr) => {
         impl ConsumeTy<$ArrTy> for Vec<Value> {
-            fn consume(&mut self, _ty: &DataType, value: <$ArrTy as ArrowType>::Native) {
+            fn consume(
+                &mut self,
+                _ty: &DataType,
+                value: <$ArrTy as ArrowType>::Native,
+            ) -> Result<(), ConnectorError> {
                 self.push($value_kind(($conversion)(value)));
+                Ok(())
             }
 
-            fn consume_null(&mut self) {
+            fn consume_null(&mut self) -> Result<(), ConnectorError> {
                 self.push(Value::Null);
+                Ok(())
             }
         }
     };
 }
 
 impl ConsumeTy<NullType> for Vec<Value> {
-    fn consume(&mut self, _ty: &DataType, _value: ()) {
+    fn consume(&mut self, _ty: &DataType, _value: ()) -> Result<(), ConnectorError> {
         self.push(Value::Null);
+        Ok(())
     }
 
-    fn consume_null(&mut self) {
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
         self.push(Value::Null);
+        Ok(())
     }
 }
 
 impl ConsumeTy<TimestampMicrosecondType> for Vec<Value> {
-    fn consume(&mut self, _ty: &DataType, value: i64) {
+    fn consume(&mut self, _ty: &DataType, value: i64) -> Result<(), ConnectorError> {
         self.push(Value::Timestamp(
             duckdb::types::TimeUnit::Microsecond,
             value,
         ));
+        Ok(())
     }
 
-    fn consume_null(&mut self) {
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
         self.push(Value::Null);
+        Ok(())
     }
 }
 
@@ -100,9 +211,121 @@ impl_consume_ty!(Float16Type, Value::Float, f32::from);
 impl_consume_ty!(Float32Type, Value::Float);
 impl_consume_ty!(Float64Type, Value::Double);
 
-impl_consume_ty!(TimestampSecondType, Value::BigInt);
-impl_consume_ty!(TimestampMillisecondType, Value::BigInt);
-impl_consume_ty!(TimestampNanosecondType, Value::BigInt);
+// timestamps are appended in microseconds, which DuckDB casts into the unit of the column
+impl ConsumeTy<TimestampSecondType> for Vec<Value> {
+    fn consume(&mut self, _ty: &DataType, value: i64) -> Result<(), ConnectorError> {
+        let micros = value
+            .checked_mul(1_000_000)
+            .ok_or(ConnectorError::DataOutOfRange)?;
+        self.push(Value::Timestamp(
+            duckdb::types::TimeUnit::Microsecond,
+            micros,
+        ));
+        Ok(())
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        self.push(Value::Null);
+        Ok(())
+    }
+}
+
+impl ConsumeTy<TimestampMillisecondType> for Vec<Value> {
+    fn consume(&mut self, _ty: &DataType, value: i64) -> Result<(), ConnectorError> {
+        let micros = value
+            .checked_mul(1_000)
+            .ok_or(ConnectorError::DataOutOfRange)?;
+        self.push(Value::Timestamp(
+            duckdb::types::TimeUnit::Microsecond,
+            micros,
+        ));
+        Ok(())
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        self.push(Value::Null);
+        Ok(())
+    }
+}
+
+// nanoseconds are truncated, as the appender appends microseconds
+impl_consume_ty!(TimestampNanosecondType, |v| Value::Timestamp(
+    duckdb::types::TimeUnit::Nanosecond,
+    v
+));
+
+impl_consume_ty!(DurationSecondType, Value::BigInt);
+impl_consume_ty!(DurationMillisecondType, Value::BigInt);
+impl_consume_ty!(DurationMicrosecondType, Value::BigInt);
+impl_consume_ty!(DurationNanosecondType, Value::BigInt);
+
+// The appender cannot append DATE, TIME or INTERVAL values,
+// so these are appended as text, which DuckDB casts into the column type.
+macro_rules! impl_consume_temporal_ty {
+    ($ArrTy: ty, $conversion: expr) => {
+        impl ConsumeTy<$ArrTy> for Vec<Value> {
+            fn consume(
+                &mut self,
+                _ty: &DataType,
+                value: <$ArrTy as ArrowType>::Native,
+            ) -> Result<(), ConnectorError> {
+                self.push(Value::Text(($conversion)(value)?));
+                Ok(())
+            }
+
+            fn consume_null(&mut self) -> Result<(), ConnectorError> {
+                self.push(Value::Null);
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_consume_temporal_ty!(Date32Type, |v: i32| date_text(
+    v.checked_add(EPOCH_DAYS_FROM_CE)
+        .and_then(NaiveDate::from_num_days_from_ce_opt)
+));
+impl_consume_temporal_ty!(Date64Type, |v| date_text(
+    DateTime::from_timestamp_millis(v).map(|d| d.date_naive())
+));
+impl_consume_temporal_ty!(Time32SecondType, |v: i32| time_text(
+    i64::from(v).checked_mul(1_000_000)
+));
+impl_consume_temporal_ty!(Time32MillisecondType, |v: i32| time_text(
+    i64::from(v).checked_mul(1_000)
+));
+impl_consume_temporal_ty!(Time64MicrosecondType, |v| time_text(Some(v)));
+impl_consume_temporal_ty!(Time64NanosecondType, |v: i64| time_text(Some(v / 1_000)));
+
+/// DuckDB parses dates before year 1 with a `(BC)` suffix.
+fn date_text(value: Option<NaiveDate>) -> Result<String, ConnectorError> {
+    let value = value.ok_or(ConnectorError::DataOutOfRange)?;
+    let (year, suffix) = if value.year() > 0 {
+        (value.year(), "")
+    } else {
+        (1 - value.year(), " (BC)")
+    };
+    Ok(format!(
+        "{year:04}-{:02}-{:02}{suffix}",
+        value.month(),
+        value.day()
+    ))
+}
+
+/// Time of day, which DuckDB TIME stores in microseconds.
+fn time_text(micros: Option<i64>) -> Result<String, ConnectorError> {
+    let micros = micros
+        .filter(|m| (0..86_400_000_000).contains(m))
+        .ok_or(ConnectorError::DataOutOfRange)?;
+    let seconds = micros / 1_000_000;
+    Ok(format!(
+        "{:02}:{:02}:{:02}.{:06}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        micros % 1_000_000
+    ))
+}
 
 impl_consume_ty!(BinaryType, Value::Blob);
 impl_consume_ty!(LargeBinaryType, Value::Blob);
@@ -110,23 +333,43 @@ impl_consume_ty!(FixedSizeBinaryType, Value::Blob);
 impl_consume_ty!(Utf8Type, Value::Text);
 impl_consume_ty!(LargeUtf8Type, Value::Text);
 
+// decimals are appended as text, which DuckDB parses into DECIMAL without loss of precision
+impl ConsumeTy<Decimal128Type> for Vec<Value> {
+    fn consume(&mut self, ty: &DataType, value: i128) -> Result<(), ConnectorError> {
+        let value = crate::util::decimal::decimal128_to_string(ty, value);
+        self.push(Value::Text(value));
+        Ok(())
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        self.push(Value::Null);
+        Ok(())
+    }
+}
+
+impl ConsumeTy<Decimal256Type> for Vec<Value> {
+    fn consume(&mut self, ty: &DataType, value: i256) -> Result<(), ConnectorError> {
+        let value = crate::util::decimal::decimal256_to_string(ty, value);
+        self.push(Value::Text(value));
+        Ok(())
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        self.push(Value::Null);
+        Ok(())
+    }
+}
+
 impl_consume_unsupported!(
     Vec<Value>,
     (
-        Date32Type,
-        Date64Type,
-        Time32SecondType,
-        Time32MillisecondType,
-        Time64MicrosecondType,
-        Time64NanosecondType,
-        DurationSecondType,
-        DurationMillisecondType,
-        DurationMicrosecondType,
-        DurationNanosecondType,
         IntervalYearMonthType,
         IntervalDayTimeType,
         IntervalMonthDayNanoType,
-        Decimal128Type,
-        Decimal256Type,
+        ListType,
+        LargeListType,
+        FixedSizeListType,
+        StructType,
+        MapType,
     )
 );
@@ -0,0 +1,54 @@
+use crate::{DbErrorDetails, DbErrorKind};
+
+pub(crate) fn error_details(error: &duckdb::Error) -> Option<DbErrorDetails> {
+    let (error_type, message) = split_message(error)?;
+
+    let kind = match error_type {
+        "Parser" | "Binder" => DbErrorKind::Syntax,
+        // catalog errors also report objects that already exist
+        "Catalog" if catalog_error(error) == Some(CatalogError::DoesNotExist) => {
+            DbErrorKind::Syntax
+        }
+        "Constraint" => DbErrorKind::ConstraintViolation,
+        "TransactionContext" if message.contains("onflict") => DbErrorKind::SerializationFailure,
+        "INTERRUPT" => DbErrorKind::Timeout,
+        _ => DbErrorKind::Other,
+    };
+    let mut details = DbErrorDetails::new(kind, Some(error_type.to_string()));
+
+    if let Some((_, column)) = message.split_once("NOT NULL constraint failed: ") {
+        details.column = Some(column.to_string());
+    }
+    Some(details)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CatalogError {
+    AlreadyExists,
+    DoesNotExist,
+}
+
+/// Classifies catalog errors, i.e. "Catalog Error: Table with name t does not exist!"
+pub(crate) fn catalog_error(error: &duckdb::Error) -> Option<CatalogError> {
+    let ("Catalog", message) = split_message(error)? else {
+        return None;
+    };
+    if message.contains(" already exists") {
+        Some(CatalogError::AlreadyExists)
+    } else if message.contains(" does not exist") {
+        Some(CatalogError::DoesNotExist)
+    } else {
+        None
+    }
+}
+
+/// Splits the message into the type of the error and the rest of the message.
+///
+/// DuckDB does not have error codes, but prefixes messages with type of the error,
+/// i.e. "Constraint Error: NOT NULL constraint failed: t.a"
+fn split_message(error: &duckdb::Error) -> Option<(&str, &str)> {
+    let duckdb::Error::DuckDBFailure(_, Some(message)) = error else {
+        return None;
+    };
+    message.split_once(" Error: ")
+}
@@ -1,26 +1,118 @@
 //! Provides `connector_arrow` traits for [duckdb crate](https://docs.rs/duckdb).
 
 mod append;
+mod errors;
+mod scan;
 mod schema;
+mod types;
 
 #[doc(hidden)]
 pub use append::DuckDBAppender;
 
-use arrow::datatypes::{DataType, TimeUnit};
+pub(crate) use errors::error_details;
+
+use arrow::datatypes::{DataType, IntervalUnit, SchemaRef, TimeUnit};
 use arrow::record_batch::RecordBatch;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::api::{ArrowValue, Connector, ResultReader, Statement};
+use crate::api::{ArrowValue, Connector, ResultReader, SchemaGet, Statement};
 use crate::errors::ConnectorError;
+use crate::util::escape::escaped_ident;
+use crate::util::{registry, ReaderOptions};
 
 pub struct DuckDBConnection {
     inner: duckdb::Connection,
+    reader_options: ReaderOptions,
+
+    /// Ids of batches registered by [DuckDBConnection::register_batches], by view name.
+    registered: HashMap<String, u64>,
+    owner: registry::Owner,
+    scan_function_registered: bool,
 }
 
 impl DuckDBConnection {
     pub fn new(inner: duckdb::Connection) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            reader_options: ReaderOptions::default(),
+            registered: HashMap::new(),
+            owner: registry::Owner::new(),
+            scan_function_registered: false,
+        }
+    }
+
+    /// Sets how query results are read into arrow.
+    pub fn set_reader_options(&mut self, options: ReaderOptions) {
+        self.reader_options = options;
+    }
+
+    /// Registers in-memory batches as a temporary view that can be queried
+    /// (and joined with tables) for the lifetime of this connection.
+    ///
+    /// The data is not copied into DuckDB, but scanned each time the view is queried.
+    /// Registering another view with the same name replaces the previous one.
+    pub fn register_batches(
+        &mut self,
+        view_name: &str,
+        schema: SchemaRef,
+        batches: Vec<RecordBatch>,
+    ) -> Result<(), ConnectorError> {
+        self.register_scan_function()?;
+
+        let id = scan::register(self.owner, view_name, schema, batches)?;
+        let ddl = format!(
+            "CREATE OR REPLACE TEMPORARY VIEW {} AS SELECT * FROM {};",
+            escaped_ident(view_name),
+            scan::call(self.owner, id)
+        );
+        if let Err(e) = self.inner.execute(&ddl, []) {
+            registry::unregister(id);
+            return Err(e.into());
+        }
+
+        if let Some(replaced) = self.registered.insert(view_name.to_string(), id) {
+            registry::unregister(replaced);
+        }
+        Ok(())
+    }
+
+    fn register_scan_function(&mut self) -> Result<(), ConnectorError> {
+        if !self.scan_function_registered {
+            // the function is registered for the whole database, possibly by another connection
+            let exists: bool = self.inner.query_row(
+                "SELECT count(*) > 0 FROM duckdb_functions() WHERE function_name = ?",
+                [scan::FUNCTION_NAME],
+                |row| row.get(0),
+            )?;
+            if !exists {
+                self.inner
+                    .register_table_function::<scan::ArrowScan>(scan::FUNCTION_NAME)?;
+            }
+            self.scan_function_registered = true;
+        }
+        Ok(())
+    }
+
+    /// Collects all batches of a result and registers them as a temporary view.
+    /// See [DuckDBConnection::register_batches].
+    pub fn register_reader<'r, R: ResultReader<'r>>(
+        &mut self,
+        view_name: &str,
+        mut reader: R,
+    ) -> Result<(), ConnectorError> {
+        let schema = reader.get_schema()?;
+        let batches = reader.collect::<Result<Vec<_>, _>>()?;
+        self.register_batches(view_name, schema, batches)
+    }
+}
+
+impl Drop for DuckDBConnection {
+    fn drop(&mut self) {
+        for id in self.registered.values() {
+            registry::unregister(*id);
+        }
     }
 }
 
@@ -34,11 +126,21 @@ impl Connector for DuckDBConnection {
     fn query<'a>(&'a mut self, query: &str) -> Result<Self::Stmt<'a>, ConnectorError> {
         let stmt = self.inner.prepare(query)?;
 
-        Ok(DuckDBStatement { stmt })
+        Ok(DuckDBStatement {
+            stmt,
+            reader_options: &self.reader_options,
+        })
     }
     fn append<'a>(&'a mut self, table_name: &str) -> Result<Self::Append<'a>, ConnectorError> {
+        let table_schema = self.table_get(table_name)?;
+        // batches with nulls are appended by scanning them
+        self.register_scan_function()?;
         Ok(DuckDBAppender {
             inner: self.inner.appender(table_name)?,
+            conn: &self.inner,
+            owner: self.owner,
+            table_name: table_name.to_string(),
+            table_schema,
         })
     }
 
@@ -49,20 +151,30 @@ impl Connector for DuckDBConnection {
             "SMALLINT" => DataType::Int16,
             "INTEGER" => DataType::Int32,
             "BIGINT" => DataType::Int64,
+            "HUGEINT" => DataType::Decimal128(38, 0),
             "UTINYINT" => DataType::UInt8,
             "USMALLINT" => DataType::UInt16,
             "UINTEGER" => DataType::UInt32,
             "UBIGINT" => DataType::UInt64,
             "REAL" => DataType::Float32,
             "DOUBLE" => DataType::Float64,
+            "DECIMAL" => DataType::Decimal128(18, 3),
 
             "TIMESTAMP" => DataType::Timestamp(TimeUnit::Microsecond, None),
-
-            "DATE" => DataType::Date64,
+            "TIMESTAMP_NS" => DataType::Timestamp(TimeUnit::Nanosecond, None),
+            "TIMESTAMP_MS" => DataType::Timestamp(TimeUnit::Millisecond, None),
+            "TIMESTAMP_S" => DataType::Timestamp(TimeUnit::Second, None),
+            "TIMESTAMP WITH TIME ZONE" | "TIMESTAMPTZ" => {
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+            }
+            "DATE" => DataType::Date32,
+            "TIME" => DataType::Time64(TimeUnit::Microsecond),
+            "INTERVAL" => DataType::Interval(IntervalUnit::MonthDayNano),
 
             "BLOB" => DataType::Binary,
             "VARCHAR" => DataType::Utf8,
-            _ => return None,
+            "UUID" => DataType::Utf8,
+            _ => return types::parametrized_db_into_arrow(database_ty),
         })
     }
 
@@ -83,26 +195,47 @@ impl Connector for DuckDBConnection {
             DataType::Float32 => "REAL",
             DataType::Float64 => "DOUBLE",
 
-            DataType::Timestamp(TimeUnit::Microsecond, _) => "TIMESTAMP",
-            // timestamps are all converted to microseconds, so all other units
-            // overflow or lose precision, which counts as information loss.
-            // this means that we have to store them as int64.
-            DataType::Timestamp(_, _) => "BIGINT",
+            // DuckDB has a time zone only for microsecond timestamps.
+            // Other units are stored as UTC, so they keep their instants, but lose the time zone.
+            DataType::Timestamp(TimeUnit::Second, _) => "TIMESTAMP_S",
+            DataType::Timestamp(TimeUnit::Millisecond, _) => "TIMESTAMP_MS",
+            DataType::Timestamp(TimeUnit::Microsecond, None) => "TIMESTAMP",
+            DataType::Timestamp(TimeUnit::Microsecond, Some(_)) => "TIMESTAMPTZ",
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => "TIMESTAMP_NS",
+
+            // DuckDB TIME and INTERVAL store microseconds, so nanoseconds are truncated.
+            // Appending values out of range of these types fails.
+            DataType::Date32 | DataType::Date64 => "DATE",
+            DataType::Time32(_) | DataType::Time64(_) => "TIME",
+            DataType::Interval(IntervalUnit::MonthDayNano) => "INTERVAL",
+            DataType::Interval(_) => return None,
+
+            // INTERVAL is read as months, days and nanoseconds, so durations are stored as
+            // their number of units, which keeps the value but loses the unit
+            DataType::Duration(_) => "BIGINT",
 
-            DataType::Date32 => "DATE",
-            DataType::Date64 => "DATE",
-            DataType::Time32(_) => "TIME",
-            DataType::Time64(_) => "TIME",
-            DataType::Duration(_) => return None,
-            DataType::Interval(_) => "INTERVAL",
+            DataType::Binary
+            | DataType::FixedSizeBinary(_)
+            | DataType::LargeBinary
+            | DataType::BinaryView => "BLOB",
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => "VARCHAR",
 
-            DataType::Binary | DataType::FixedSizeBinary(_) | DataType::LargeBinary => "BLOB",
-            DataType::Utf8 | DataType::LargeUtf8 => "VARCHAR",
+            // DuckDB decimals have at most 38 digits and cannot have negative scale
+            DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale)
+                if *precision <= 38 && *scale >= 0 =>
+            {
+                return Some(format!("DECIMAL({precision}, {scale})"));
+            }
+            DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => "VARCHAR",
 
-            DataType::Decimal128(_, _) => todo!(),
-            DataType::Decimal256(_, _) => todo!(),
+            // dictionaries are appended as their values, which are read as strings
+            DataType::Dictionary(_, value)
+                if matches!(**value, DataType::Utf8 | DataType::LargeUtf8) =>
+            {
+                "VARCHAR"
+            }
 
-            _ => return None,
+            _ => return types::parametrized_arrow_into_db(ty),
         };
         Some(s.to_string())
     }
@@ -111,6 +244,7 @@ impl Connector for DuckDBConnection {
 #[doc(hidden)]
 pub struct DuckDBStatement<'conn> {
     stmt: duckdb::Statement<'conn>,
+    reader_options: &'conn ReaderOptions,
 }
 
 impl<'conn> Statement<'conn> for DuckDBStatement<'conn> {
@@ -123,18 +257,25 @@ impl<'conn> Statement<'conn> for DuckDBStatement<'conn> {
         I: IntoIterator<Item = &'p dyn ArrowValue>,
     {
         let arrow = self.stmt.query_arrow([])?;
-        Ok(DuckDBReader { arrow })
+        let schema = self.reader_options.apply(arrow.get_schema());
+        Ok(DuckDBReader {
+            arrow,
+            schema,
+            reader_options: self.reader_options,
+        })
     }
 }
 
 #[doc(hidden)]
 pub struct DuckDBReader<'stmt> {
     arrow: duckdb::Arrow<'stmt>,
+    schema: SchemaRef,
+    reader_options: &'stmt ReaderOptions,
 }
 
 impl<'stmt> ResultReader<'stmt> for DuckDBReader<'stmt> {
     fn get_schema(&mut self) -> Result<Arc<arrow::datatypes::Schema>, ConnectorError> {
-        Ok(self.arrow.get_schema())
+        Ok(self.schema.clone())
     }
 }
 
@@ -142,6 +283,10 @@ impl<'stmt> Iterator for DuckDBReader<'stmt> {
     type Item = Result<RecordBatch, ConnectorError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.arrow.next().map(Ok)
+        let batch = match types::repack_intervals(self.arrow.next()?) {
+            Ok(batch) => batch,
+            Err(e) => return Some(Err(e.into())),
+        };
+        Some(self.reader_options.apply_to_batch(&self.schema, batch))
     }
 }
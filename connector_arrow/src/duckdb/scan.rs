@@ -0,0 +1,454 @@
+//! Table function that scans record batches registered with
+//! [DuckDBConnection::register_batches](super::DuckDBConnection::register_batches).
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::ops::Range;
+use std::sync::Arc;
+
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::*;
+use arrow::record_batch::RecordBatch;
+use duckdb::ffi;
+use duckdb::vtab::{
+    BindInfo, DataChunk, Free, FunctionInfo, InitInfo, LogicalType, LogicalTypeId, VTab,
+};
+
+use crate::util::registry::{self, Owner, Registration};
+use crate::ConnectorError;
+
+/// Name of the table function, as registered in DuckDB.
+pub(super) const FUNCTION_NAME: &str = "connector_arrow_scan";
+
+/// Stores the batches so they can be scanned with [FUNCTION_NAME] and returns their id.
+pub(super) fn register(
+    owner: Owner,
+    table_name: &str,
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+) -> Result<u64, ConnectorError> {
+    registry::register(owner, table_name, schema, batches, "DuckDB", is_scannable)
+}
+
+/// SQL expression that scans the registered batches.
+///
+/// DuckDB registers table functions for the whole database and does not tell them
+/// which connection calls them, so the owner is passed as an argument. It appears only in
+/// temporary views and statements of the owner, which other connections cannot see.
+pub(super) fn call(owner: Owner, id: u64) -> String {
+    format!("{FUNCTION_NAME}({}::UBIGINT, {id}::UBIGINT)", owner.0)
+}
+
+/// Whether columns of the type can be scanned.
+pub(super) fn is_scannable(ty: &DataType) -> bool {
+    logical_type(ty).is_some()
+}
+
+fn logical_type(ty: &DataType) -> Option<LogicalType> {
+    let id = match ty {
+        DataType::Boolean => LogicalTypeId::Boolean,
+        DataType::Int8 => LogicalTypeId::Tinyint,
+        DataType::Int16 => LogicalTypeId::Smallint,
+        DataType::Int32 => LogicalTypeId::Integer,
+        DataType::Int64 => LogicalTypeId::Bigint,
+        DataType::UInt8 => LogicalTypeId::UTinyint,
+        DataType::UInt16 => LogicalTypeId::USmallint,
+        DataType::UInt32 => LogicalTypeId::UInteger,
+        DataType::UInt64 => LogicalTypeId::UBigint,
+        DataType::Float16 | DataType::Float32 => LogicalTypeId::Float,
+        DataType::Float64 => LogicalTypeId::Double,
+
+        DataType::Timestamp(TimeUnit::Second, _) => LogicalTypeId::TimestampS,
+        DataType::Timestamp(TimeUnit::Millisecond, _) => LogicalTypeId::TimestampMs,
+        DataType::Timestamp(TimeUnit::Microsecond, None) => LogicalTypeId::Timestamp,
+        DataType::Timestamp(TimeUnit::Microsecond, Some(_)) => LogicalTypeId::TimestampTZ,
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => LogicalTypeId::TimestampNs,
+        DataType::Date32 => LogicalTypeId::Date,
+        DataType::Time64(TimeUnit::Microsecond) => LogicalTypeId::Time,
+        DataType::Interval(IntervalUnit::MonthDayNano) => LogicalTypeId::Interval,
+        DataType::Duration(_) => LogicalTypeId::Bigint,
+
+        DataType::Decimal128(precision, scale) if *precision <= 38 && *scale >= 0 => {
+            return Some(LogicalType::decimal(*precision, *scale as u8));
+        }
+
+        DataType::Utf8 | DataType::LargeUtf8 => LogicalTypeId::Varchar,
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => {
+            LogicalTypeId::Blob
+        }
+
+        // fixed size lists are scanned as lists, which DuckDB casts into arrays
+        DataType::List(field) | DataType::LargeList(field) | DataType::FixedSizeList(field, _) => {
+            return Some(LogicalType::list(&logical_type(field.data_type())?));
+        }
+        DataType::Struct(fields) => {
+            let children = (fields.iter())
+                .map(|f| {
+                    // names are passed to DuckDB as C strings
+                    let name = Some(f.name().as_str()).filter(|n| !n.contains('\0'))?;
+                    Some((name, logical_type(f.data_type())?))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            return Some(LogicalType::struct_type(&children));
+        }
+        DataType::Map(entries, _) => {
+            let DataType::Struct(fields) = entries.data_type() else {
+                return None;
+            };
+            let [key, value] = &fields[..] else {
+                return None;
+            };
+            let key = logical_type(key.data_type())?;
+            let value = logical_type(value.data_type())?;
+            return Some(LogicalType::map(&key, &value));
+        }
+        _ => return None,
+    };
+    Some(LogicalType::new(id))
+}
+
+pub(super) struct ArrowScan;
+
+pub(super) struct ArrowScanBind {
+    registration: Option<Arc<Registration>>,
+}
+
+impl Free for ArrowScanBind {
+    fn free(&mut self) {
+        self.registration = None;
+    }
+}
+
+pub(super) struct ArrowScanInit {
+    batch_index: usize,
+    row_offset: usize,
+}
+
+impl Free for ArrowScanInit {}
+
+impl VTab for ArrowScan {
+    type InitData = ArrowScanInit;
+    type BindData = ArrowScanBind;
+
+    unsafe fn bind(bind: &BindInfo, data: *mut ArrowScanBind) -> Result<(), Box<dyn Error>> {
+        // data is uninitialized and will be freed even if bind fails
+        data.write(ArrowScanBind { registration: None });
+
+        let owner = Owner(bind.get_parameter(0).to_int64() as u64);
+        let id = bind.get_parameter(1).to_int64() as u64;
+        let registration = registry::get(owner, id);
+        let Some(registration) = registration else {
+            return Err(format!("batches with id {id} are not registered").into());
+        };
+
+        for field in registration.schema.fields() {
+            // types were checked on registration
+            let ty = logical_type(field.data_type()).unwrap();
+            bind.add_result_column(field.name(), ty);
+        }
+
+        let rows = registration
+            .batches
+            .iter()
+            .map(|b| b.num_rows())
+            .sum::<usize>();
+        bind.set_cardinality(rows as ffi::idx_t, true);
+
+        (*data).registration = Some(registration);
+        Ok(())
+    }
+
+    unsafe fn init(_: &InitInfo, data: *mut ArrowScanInit) -> Result<(), Box<dyn Error>> {
+        data.write(ArrowScanInit {
+            batch_index: 0,
+            row_offset: 0,
+        });
+        Ok(())
+    }
+
+    unsafe fn func(func: &FunctionInfo, output: &mut DataChunk) -> Result<(), Box<dyn Error>> {
+        let bind = &*func.get_bind_data::<ArrowScanBind>();
+        let init = &mut *func.get_init_data::<ArrowScanInit>();
+        let batches = &bind.registration.as_ref().unwrap().batches;
+
+        // skip exhausted batches
+        while let Some(batch) = batches.get(init.batch_index) {
+            if init.row_offset < batch.num_rows() {
+                break;
+            }
+            init.batch_index += 1;
+            init.row_offset = 0;
+        }
+        let Some(batch) = batches.get(init.batch_index) else {
+            output.set_len(0);
+            return Ok(());
+        };
+
+        // chunks have a fixed capacity
+        let len = usize::min(
+            batch.num_rows() - init.row_offset,
+            ffi::duckdb_vector_size() as usize,
+        );
+        let batch = batch.slice(init.row_offset, len);
+        init.row_offset += len;
+
+        for (index, array) in batch.columns().iter().enumerate() {
+            let vector = ffi::duckdb_data_chunk_get_vector(output.get_ptr(), index as ffi::idx_t);
+            write_vector(array.as_ref(), vector)?;
+        }
+        output.set_len(len);
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalType>> {
+        Some(vec![
+            LogicalType::new(LogicalTypeId::UBigint),
+            LogicalType::new(LogicalTypeId::UBigint),
+        ])
+    }
+}
+
+/// Writes an array into a DuckDB vector of type produced by [logical_type].
+///
+/// # Safety
+///
+/// `vector` must be a valid vector with capacity of at least the length of the array.
+unsafe fn write_vector(
+    array: &dyn Array,
+    vector: ffi::duckdb_vector,
+) -> Result<(), ConnectorError> {
+    let len = array.len();
+
+    match array.data_type() {
+        DataType::Boolean => {
+            let slice = data_slice::<bool>(vector, len);
+            for (index, value) in array.as_boolean().values().iter().enumerate() {
+                slice[index] = value;
+            }
+        }
+        DataType::Int8 => copy_primitive::<Int8Type>(array, vector),
+        DataType::Int16 => copy_primitive::<Int16Type>(array, vector),
+        DataType::Int32 => copy_primitive::<Int32Type>(array, vector),
+        DataType::Int64 => copy_primitive::<Int64Type>(array, vector),
+        DataType::UInt8 => copy_primitive::<UInt8Type>(array, vector),
+        DataType::UInt16 => copy_primitive::<UInt16Type>(array, vector),
+        DataType::UInt32 => copy_primitive::<UInt32Type>(array, vector),
+        DataType::UInt64 => copy_primitive::<UInt64Type>(array, vector),
+        DataType::Float16 => {
+            let slice = data_slice::<f32>(vector, len);
+            for (index, value) in array
+                .as_primitive::<Float16Type>()
+                .values()
+                .iter()
+                .enumerate()
+            {
+                slice[index] = value.to_f32();
+            }
+        }
+        DataType::Float32 => copy_primitive::<Float32Type>(array, vector),
+        DataType::Float64 => copy_primitive::<Float64Type>(array, vector),
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            copy_primitive::<TimestampSecondType>(array, vector)
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            copy_primitive::<TimestampMillisecondType>(array, vector)
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            copy_primitive::<TimestampMicrosecondType>(array, vector)
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            copy_primitive::<TimestampNanosecondType>(array, vector)
+        }
+        DataType::Date32 => copy_primitive::<Date32Type>(array, vector),
+        DataType::Time64(_) => copy_primitive::<Time64MicrosecondType>(array, vector),
+        DataType::Interval(_) => {
+            // DuckDB intervals store microseconds, so nanoseconds are truncated
+            let values = array.as_primitive::<IntervalMonthDayNanoType>().values();
+            let slice = data_slice::<ffi::duckdb_interval>(vector, len);
+            for (index, value) in values.iter().enumerate() {
+                let (months, days, nanos) = IntervalMonthDayNanoType::to_parts(*value);
+                slice[index] = ffi::duckdb_interval {
+                    months,
+                    days,
+                    micros: nanos / 1_000,
+                };
+            }
+        }
+        DataType::Duration(TimeUnit::Second) => copy_primitive::<DurationSecondType>(array, vector),
+        DataType::Duration(TimeUnit::Millisecond) => {
+            copy_primitive::<DurationMillisecondType>(array, vector)
+        }
+        DataType::Duration(TimeUnit::Microsecond) => {
+            copy_primitive::<DurationMicrosecondType>(array, vector)
+        }
+        DataType::Duration(TimeUnit::Nanosecond) => {
+            copy_primitive::<DurationNanosecondType>(array, vector)
+        }
+        DataType::Decimal128(precision, _) => {
+            // DuckDB stores decimals in the smallest integer that fits the precision
+            let values = array.as_primitive::<Decimal128Type>().values();
+            match precision {
+                ..=4 => copy_cast::<i16>(values, vector)?,
+                5..=9 => copy_cast::<i32>(values, vector)?,
+                10..=18 => copy_cast::<i64>(values, vector)?,
+                _ => {
+                    // hugeint_t is a pair of lower and upper 64 bits
+                    let slice = data_slice::<[u64; 2]>(vector, len);
+                    for (index, value) in values.iter().enumerate() {
+                        slice[index] = [*value as u64, (*value >> 64) as u64];
+                    }
+                }
+            }
+        }
+        DataType::Utf8 => {
+            for (index, value) in array.as_string::<i32>().iter().enumerate() {
+                assign_bytes(vector, index, value.map(str::as_bytes));
+            }
+        }
+        DataType::LargeUtf8 => {
+            for (index, value) in array.as_string::<i64>().iter().enumerate() {
+                assign_bytes(vector, index, value.map(str::as_bytes));
+            }
+        }
+        DataType::Binary => {
+            for (index, value) in array.as_binary::<i32>().iter().enumerate() {
+                assign_bytes(vector, index, value);
+            }
+        }
+        DataType::LargeBinary => {
+            for (index, value) in array.as_binary::<i64>().iter().enumerate() {
+                assign_bytes(vector, index, value);
+            }
+        }
+        DataType::FixedSizeBinary(_) => {
+            for (index, value) in array.as_fixed_size_binary().iter().enumerate() {
+                assign_bytes(vector, index, value);
+            }
+        }
+        DataType::List(_) => {
+            let array = array.as_list::<i32>();
+            let offsets = array.value_offsets();
+            let ranges = (0..len).map(|i| offsets[i] as usize..offsets[i + 1] as usize);
+            write_list(vector, ranges, array.values().as_ref())?;
+        }
+        DataType::LargeList(_) => {
+            let array = array.as_list::<i64>();
+            let offsets = array.value_offsets();
+            let ranges = (0..len).map(|i| offsets[i] as usize..offsets[i + 1] as usize);
+            write_list(vector, ranges, array.values().as_ref())?;
+        }
+        DataType::FixedSizeList(_, size) => {
+            let array = array.as_fixed_size_list();
+            let size = *size as usize;
+            let ranges = (0..len).map(|i| {
+                let start = array.value_offset(i) as usize;
+                start..start + size
+            });
+            write_list(vector, ranges, array.values().as_ref())?;
+        }
+        DataType::Map(_, _) => {
+            // maps are lists of key-value structs
+            let array = array.as_map();
+            let offsets = array.value_offsets();
+            let ranges = (0..len).map(|i| offsets[i] as usize..offsets[i + 1] as usize);
+            write_list(vector, ranges, array.entries())?;
+        }
+        DataType::Struct(_) => {
+            let array = array.as_struct();
+            for (index, column) in array.columns().iter().enumerate() {
+                let child = ffi::duckdb_struct_vector_get_child(vector, index as ffi::idx_t);
+                write_vector(column.as_ref(), child)?;
+
+                // children of null structs must be null as well
+                set_nulls(array, child);
+            }
+        }
+        _ => unreachable!("types were checked on registration"),
+    }
+
+    set_nulls(array, vector);
+    Ok(())
+}
+
+/// Writes list entries into the vector and the elements into its child vector.
+///
+/// # Safety
+///
+/// `vector` must be a valid list vector with capacity of at least the number of the ranges.
+unsafe fn write_list(
+    vector: ffi::duckdb_vector,
+    ranges: impl ExactSizeIterator<Item = Range<usize>> + Clone,
+    values: &dyn Array,
+) -> Result<(), ConnectorError> {
+    // elements of the first list are written at the start of the child vector
+    let start = ranges.clone().next().map_or(0, |r| r.start);
+    let end = ranges.clone().last().map_or(0, |r| r.end);
+    let values = values.slice(start, end - start);
+
+    let state = ffi::duckdb_list_vector_reserve(vector, values.len() as ffi::idx_t);
+    if state != ffi::DuckDBSuccess {
+        return Err(ConnectorError::DataOutOfRange);
+    }
+
+    let entries = data_slice::<ffi::duckdb_list_entry>(vector, ranges.len());
+    for (index, range) in ranges.enumerate() {
+        entries[index] = ffi::duckdb_list_entry {
+            offset: (range.start - start) as u64,
+            length: range.len() as u64,
+        };
+    }
+
+    write_vector(values.as_ref(), ffi::duckdb_list_vector_get_child(vector))?;
+    ffi::duckdb_list_vector_set_size(vector, values.len() as ffi::idx_t);
+    Ok(())
+}
+
+/// Marks rows that are null in the array as invalid in the vector.
+unsafe fn set_nulls(array: &dyn Array, vector: ffi::duckdb_vector) {
+    let Some(nulls) = array.nulls().filter(|n| n.null_count() > 0) else {
+        return;
+    };
+    ffi::duckdb_vector_ensure_validity_writable(vector);
+    let validity = ffi::duckdb_vector_get_validity(vector);
+    for index in nulls.iter().enumerate().filter(|(_, v)| !v).map(|(i, _)| i) {
+        ffi::duckdb_validity_set_row_invalid(validity, index as ffi::idx_t);
+    }
+}
+
+/// Data of the vector, which might be larger than a chunk when it is a child of a list.
+///
+/// # Safety
+///
+/// `vector` must have capacity of at least `len` values of type `T`.
+unsafe fn data_slice<'a, T>(vector: ffi::duckdb_vector, len: usize) -> &'a mut [T] {
+    std::slice::from_raw_parts_mut(ffi::duckdb_vector_get_data(vector).cast(), len)
+}
+
+unsafe fn copy_primitive<T: ArrowPrimitiveType>(array: &dyn Array, vector: ffi::duckdb_vector) {
+    let values = array.as_primitive::<T>().values();
+    data_slice::<T::Native>(vector, values.len()).copy_from_slice(values);
+}
+
+/// Fails for values that do not fit the precision of the decimal, as DuckDB could not represent them.
+unsafe fn copy_cast<T: TryFrom<i128>>(
+    values: &[i128],
+    vector: ffi::duckdb_vector,
+) -> Result<(), ConnectorError> {
+    let slice = data_slice::<T>(vector, values.len());
+    for (index, value) in values.iter().enumerate() {
+        slice[index] = T::try_from(*value).map_err(|_| ConnectorError::DataOutOfRange)?;
+    }
+    Ok(())
+}
+
+/// Copies bytes of a string or a blob into the vector. Null values are skipped.
+unsafe fn assign_bytes(vector: ffi::duckdb_vector, index: usize, value: Option<&[u8]>) {
+    if let Some(value) = value {
+        ffi::duckdb_vector_assign_string_element_len(
+            vector,
+            index as ffi::idx_t,
+            value.as_ptr().cast(),
+            value.len() as ffi::idx_t,
+        );
+    }
+}
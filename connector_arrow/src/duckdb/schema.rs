@@ -1,10 +1,10 @@
-use arrow::datatypes::{DataType, SchemaRef, TimeUnit};
-use itertools::Itertools;
+use arrow::datatypes::{DataType, SchemaRef};
 
-use crate::api::{SchemaEdit, SchemaGet};
+use crate::api::{Connector, SchemaEdit, SchemaGet};
 use crate::util::escape::escaped_ident;
 use crate::{ConnectorError, TableCreateError, TableDropError};
 
+use super::errors::{catalog_error, CatalogError};
 use super::DuckDBConnection;
 
 impl SchemaGet for DuckDBConnection {
@@ -36,15 +36,27 @@ impl SchemaEdit for DuckDBConnection {
             .fields()
             .iter()
             .map(|field| {
-                let ty = ty_from_arrow(field.data_type());
+                let ty =
+                    DuckDBConnection::type_arrow_into_db(field.data_type()).ok_or_else(|| {
+                        ConnectorError::IncompatibleSchema {
+                            table_name: name.to_string(),
+                            message: format!(
+                                "cannot store arrow type {} of column {} in DuckDB",
+                                field.data_type(),
+                                field.name()
+                            ),
+                            hint: None,
+                        }
+                    })?;
 
                 let is_nullable =
                     field.is_nullable() || matches!(field.data_type(), DataType::Null);
                 let not_null = if is_nullable { "" } else { " NOT NULL" };
 
                 let name = escaped_ident(field.name());
-                format!("{name} {ty}{not_null}")
+                Ok(format!("{name} {ty}{not_null}"))
             })
+            .collect::<Result<Vec<_>, ConnectorError>>()?
             .join(",");
 
         let ddl = format!("CREATE TABLE {} ({column_defs});", escaped_ident(name));
@@ -52,10 +64,7 @@ impl SchemaEdit for DuckDBConnection {
         let res = self.inner.execute(&ddl, []);
         match res {
             Ok(_) => Ok(()),
-            Err(e)
-                if e.to_string().starts_with("Catalog Error: Table with name")
-                    && e.to_string().contains("already exists!") =>
-            {
+            Err(e) if catalog_error(&e) == Some(CatalogError::AlreadyExists) => {
                 Err(TableCreateError::TableExists)
             }
             Err(e) => Err(TableCreateError::Connector(ConnectorError::DuckDB(e))),
@@ -70,62 +79,10 @@ impl SchemaEdit for DuckDBConnection {
 
         match res {
             Ok(_) => Ok(()),
-            Err(e)
-                if e.to_string().starts_with("Catalog Error: Table with name ")
-                    && e.to_string().contains("does not exist!") =>
-            {
+            Err(e) if catalog_error(&e) == Some(CatalogError::DoesNotExist) => {
                 Err(TableDropError::TableNonexistent)
             }
             Err(e) => Err(TableDropError::Connector(e.into())),
         }
     }
 }
-
-fn ty_from_arrow(data_type: &DataType) -> &'static str {
-    match data_type {
-        // there is no Null type in DuckDB, so we fallback to some other type that is nullable
-        DataType::Null => "BIGINT",
-
-        DataType::Boolean => "BOOLEAN",
-        DataType::Int8 => "TINYINT",
-        DataType::Int16 => "SMALLINT",
-        DataType::Int32 => "INTEGER",
-        DataType::Int64 => "BIGINT",
-        DataType::UInt8 => "UTINYINT",
-        DataType::UInt16 => "USMALLINT",
-        DataType::UInt32 => "UINTEGER",
-        DataType::UInt64 => "UBIGINT",
-        DataType::Float16 => "REAL",
-        DataType::Float32 => "REAL",
-        DataType::Float64 => "DOUBLE",
-        DataType::Timestamp(TimeUnit::Nanosecond, _) => "BIGINT",
-        DataType::Timestamp(TimeUnit::Microsecond, _) => "TIMESTAMP",
-        DataType::Timestamp(TimeUnit::Millisecond, _) => "BIGINT",
-        DataType::Timestamp(TimeUnit::Second, _) => "BIGINT",
-        DataType::Date32 => unimplemented!(),
-        DataType::Date64 => unimplemented!(),
-        DataType::Time32(_) => unimplemented!(),
-        DataType::Time64(_) => unimplemented!(),
-        DataType::Duration(_) => unimplemented!(),
-        DataType::Interval(_) => unimplemented!(),
-        DataType::Binary => "BLOB",
-        DataType::FixedSizeBinary(_) => "BLOB",
-        DataType::LargeBinary => "BLOB",
-        DataType::Utf8 => "VARCHAR",
-        DataType::LargeUtf8 => "VARCHAR",
-        DataType::List(_) => unimplemented!(),
-        DataType::FixedSizeList(_, _) => unimplemented!(),
-        DataType::LargeList(_) => unimplemented!(),
-        DataType::Struct(_) => unimplemented!(),
-        DataType::Union(_, _) => unimplemented!(),
-        DataType::Dictionary(_, _) => unimplemented!(),
-        DataType::Decimal128(_, _) => unimplemented!(),
-        DataType::Decimal256(_, _) => unimplemented!(),
-        DataType::Map(_, _) => unimplemented!(),
-        DataType::RunEndEncoded(_, _) => unimplemented!(),
-        DataType::BinaryView => todo!(),
-        DataType::Utf8View => todo!(),
-        DataType::ListView(_) => todo!(),
-        DataType::LargeListView(_) => todo!(),
-    }
-}
@@ -0,0 +1,207 @@
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, AsArray};
+use arrow::datatypes::{DataType, Field, IntervalMonthDayNanoType, IntervalUnit};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+
+use crate::api::Connector;
+
+use super::DuckDBConnection;
+
+/// Converts DuckDB types that have parameters or are nested (i.e. `DECIMAL(10,2)`,
+/// `INTEGER[]`, `STRUCT(a INTEGER, b VARCHAR)`), as they are formatted by `DESCRIBE`.
+pub fn parametrized_db_into_arrow(ty: &str) -> Option<DataType> {
+    // field names of list items match the ones DuckDB uses when exporting to arrow
+    if let Some(item) = ty.strip_suffix("[]") {
+        let item = DuckDBConnection::type_db_into_arrow(item)?;
+        return Some(DataType::List(Arc::new(Field::new("l", item, true))));
+    }
+    if let Some((item, size)) = ty.strip_suffix(']').and_then(|t| t.rsplit_once('[')) {
+        let item = DuckDBConnection::type_db_into_arrow(item)?;
+        let size = size.parse().ok()?;
+        return Some(DataType::FixedSizeList(
+            Arc::new(Field::new("", item, true)),
+            size,
+        ));
+    }
+
+    let (name, params) = ty.split_once('(')?;
+    let params = split_params(params.strip_suffix(')')?);
+    Some(match name {
+        "DECIMAL" | "NUMERIC" => {
+            let [precision, scale] = params.as_slice() else {
+                return None;
+            };
+            DataType::Decimal128(precision.parse().ok()?, scale.parse().ok()?)
+        }
+        "STRUCT" => {
+            let fields = params
+                .into_iter()
+                .map(|param| {
+                    let (name, ty) = split_field_name(param)?;
+                    let ty = DuckDBConnection::type_db_into_arrow(ty)?;
+                    Some(Field::new(name, ty, true))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            DataType::Struct(fields.into())
+        }
+        "MAP" => {
+            let [key, value] = params.as_slice() else {
+                return None;
+            };
+            let key = DuckDBConnection::type_db_into_arrow(key)?;
+            let value = DuckDBConnection::type_db_into_arrow(value)?;
+            let entries = DataType::Struct(
+                vec![
+                    Field::new("key", key, true),
+                    Field::new("value", value, true),
+                ]
+                .into(),
+            );
+            DataType::Map(Arc::new(Field::new("entries", entries, true)), false)
+        }
+        "ENUM" => {
+            // DuckDB picks the smallest integer that can index all of the variants
+            let key = if params.len() <= u8::MAX as usize {
+                DataType::UInt8
+            } else if params.len() <= u16::MAX as usize {
+                DataType::UInt16
+            } else {
+                DataType::UInt32
+            };
+            DataType::Dictionary(Box::new(key), Box::new(DataType::Utf8))
+        }
+        _ => return None,
+    })
+}
+
+/// Converts arrow types that map to parametrized or nested DuckDB types.
+pub fn parametrized_arrow_into_db(ty: &DataType) -> Option<String> {
+    Some(match ty {
+        DataType::List(field) | DataType::LargeList(field) => {
+            format!(
+                "{}[]",
+                DuckDBConnection::type_arrow_into_db(field.data_type())?
+            )
+        }
+        DataType::FixedSizeList(field, size) => {
+            let item = DuckDBConnection::type_arrow_into_db(field.data_type())?;
+            format!("{item}[{size}]")
+        }
+        DataType::Struct(fields) => {
+            let fields = fields
+                .iter()
+                .map(|field| {
+                    let ty = DuckDBConnection::type_arrow_into_db(field.data_type())?;
+                    Some(format!(
+                        "{} {ty}",
+                        crate::util::escape::escaped_ident(field.name())
+                    ))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            format!("STRUCT({})", fields.join(", "))
+        }
+        DataType::Map(entries, _) => {
+            let DataType::Struct(fields) = entries.data_type() else {
+                return None;
+            };
+            let [key, value] = fields.iter().collect::<Vec<_>>()[..] else {
+                return None;
+            };
+            let key = DuckDBConnection::type_arrow_into_db(key.data_type())?;
+            let value = DuckDBConnection::type_arrow_into_db(value.data_type())?;
+            format!("MAP({key}, {value})")
+        }
+        _ => return None,
+    })
+}
+
+/// Splits a comma-separated list of type parameters,
+/// ignoring commas within nested parenthesis and quotes.
+fn split_params(params: &str) -> Vec<&str> {
+    let mut res = Vec::new();
+
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (index, c) in params.char_indices() {
+        match (c, quote) {
+            ('\'' | '"', None) => quote = Some(c),
+            (_, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => {}
+            ('(', None) => depth += 1,
+            (')', None) => depth -= 1,
+            (',', None) if depth == 0 => {
+                res.push(params[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if !params[start..].trim().is_empty() {
+        res.push(params[start..].trim());
+    }
+    res
+}
+
+/// Splits a struct field definition into field name and its type.
+fn split_field_name(field: &str) -> Option<(String, &str)> {
+    if let Some(quoted) = field.strip_prefix('"') {
+        // quotes within the name are escaped by doubling them
+        let mut end = None;
+        let mut chars = quoted.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            if c == '"' {
+                if chars.peek().map(|(_, c)| *c) == Some('"') {
+                    chars.next();
+                    continue;
+                }
+                end = Some(index);
+                break;
+            }
+        }
+        let end = end?;
+        let name = quoted[..end].replace("\"\"", "\"");
+        Some((name, quoted[end + 1..].trim()))
+    } else {
+        let (name, ty) = field.split_once(' ')?;
+        Some((name.to_string(), ty.trim()))
+    }
+}
+
+/// DuckDB exports intervals in the memory layout of the Arrow format (months, days and
+/// nanoseconds in this order), but arrow-rs packs them into i128 with months in the high bits.
+/// This repacks top-level interval columns, so their parts can be read with arrow-rs.
+pub fn repack_intervals(batch: RecordBatch) -> Result<RecordBatch, ArrowError> {
+    let is_interval = |c: &ArrayRef| {
+        matches!(
+            c.data_type(),
+            DataType::Interval(IntervalUnit::MonthDayNano)
+        )
+    };
+    if !batch.columns().iter().any(is_interval) {
+        return Ok(batch);
+    }
+
+    let columns = batch
+        .columns()
+        .iter()
+        .map(|column| {
+            if !is_interval(column) {
+                return column.clone();
+            }
+            let array = column
+                .as_primitive::<IntervalMonthDayNanoType>()
+                .unary::<_, IntervalMonthDayNanoType>(|v| {
+                    IntervalMonthDayNanoType::make_value(
+                        v as i32,
+                        (v >> 32) as i32,
+                        (v >> 64) as i64,
+                    )
+                });
+            Arc::new(array) as ArrayRef
+        })
+        .collect();
+    RecordBatch::try_new(batch.schema(), columns)
+}
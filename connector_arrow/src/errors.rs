@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::string::FromUtf8Error;
 
 use thiserror::Error;
@@ -30,15 +31,22 @@ pub enum ConnectorError {
     #[error("{connector_name} does not support {feature}")]
     NotSupported {
         connector_name: &'static str,
-        feature: &'static str,
+        feature: Cow<'static, str>,
     },
 
     #[error(transparent)]
     UrlEncoding(#[from] FromUtf8Error),
 
+    #[error("Invalid connection URL: {0}")]
+    InvalidUrl(String),
+
     #[error(transparent)]
     Arrow(#[from] arrow::error::ArrowError),
 
+    #[cfg(feature = "ffi")]
+    #[error("Thread executing the query panicked: {0}")]
+    Panicked(String),
+
     #[cfg(feature = "src_sqlite")]
     #[error(transparent)]
     SQLite(#[from] rusqlite::Error),
@@ -58,6 +66,117 @@ pub enum ConnectorError {
     #[cfg(feature = "src_tiberius")]
     #[error(transparent)]
     Tiberius(#[from] tiberius::error::Error),
+
+    #[cfg(feature = "src_file")]
+    #[error(transparent)]
+    File(#[from] super::file::FileError),
+
+    #[cfg(feature = "src_flight_sql")]
+    #[error(transparent)]
+    FlightSql(#[from] super::flight_sql::FlightSqlError),
+}
+
+impl ConnectorError {
+    /// Details of the error as reported by the database.
+    ///
+    /// Returns None for errors that did not originate from the database or its driver.
+    pub fn db_error(&self) -> Option<DbErrorDetails> {
+        match self {
+            #[cfg(feature = "src_sqlite")]
+            ConnectorError::SQLite(e) => crate::sqlite::error_details(e),
+
+            #[cfg(feature = "src_duckdb")]
+            ConnectorError::DuckDB(e) => crate::duckdb::error_details(e),
+
+            #[cfg(feature = "src_postgres")]
+            ConnectorError::Postgres(e) => crate::postgres::error_details(e),
+
+            #[cfg(feature = "src_mysql")]
+            ConnectorError::MySQL(e) => crate::mysql::error_details(e),
+
+            #[cfg(feature = "src_tiberius")]
+            ConnectorError::Tiberius(e) => crate::tiberius::error_details(e),
+
+            _ => None,
+        }
+    }
+
+    /// Whether the operation that caused this error might succeed if it is retried.
+    pub fn is_retryable(&self) -> bool {
+        self.db_error().is_some_and(|d| d.kind.is_retryable())
+    }
+}
+
+/// Details of an error reported by a database, in a form that is uniform across connectors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DbErrorDetails {
+    pub kind: DbErrorKind,
+
+    /// SQLSTATE or, for databases that don't use it, vendor-specific error code.
+    pub code: Option<String>,
+
+    /// Name of the violated constraint.
+    pub constraint: Option<String>,
+
+    /// Name of the column the error relates to.
+    pub column: Option<String>,
+
+    /// Position in the query where the error occurred, as 1-based character index.
+    pub position: Option<u32>,
+}
+
+impl DbErrorDetails {
+    // unused when no sources are enabled
+    #[allow(dead_code)]
+    pub(crate) fn new(kind: DbErrorKind, code: Option<String>) -> Self {
+        DbErrorDetails {
+            kind,
+            code,
+            constraint: None,
+            column: None,
+            position: None,
+        }
+    }
+}
+
+/// Classification of database errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DbErrorKind {
+    /// The query could not be parsed or refers to objects that don't exist.
+    Syntax,
+
+    /// A unique, primary key, foreign key, not null or check constraint was violated.
+    ConstraintViolation,
+
+    /// The transaction conflicted with a concurrent transaction or was chosen as a deadlock victim.
+    SerializationFailure,
+
+    /// The connection to the database was lost or could not be established.
+    ConnectionLost,
+
+    /// The operation timed out, was canceled or could not acquire a lock in time.
+    Timeout,
+
+    Other,
+}
+
+impl DbErrorKind {
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            DbErrorKind::SerializationFailure | DbErrorKind::ConnectionLost | DbErrorKind::Timeout
+        )
+    }
+}
+
+impl From<ConnectorError> for arrow::error::ArrowError {
+    fn from(value: ConnectorError) -> Self {
+        match value {
+            ConnectorError::Arrow(e) => e,
+            e => arrow::error::ArrowError::ExternalError(Box::new(e)),
+        }
+    }
 }
 
 #[derive(Error, Debug)]
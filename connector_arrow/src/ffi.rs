@@ -0,0 +1,127 @@
+//! Exchange of data over [Arrow C stream interface](https://arrow.apache.org/docs/format/CStreamInterface.html),
+//! for passing results to other languages without copying the arrays.
+
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::thread::JoinHandle;
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::api::{Append, Connector, ResultReader, Statement};
+use crate::errors::ConnectorError;
+
+/// Executes a query and exports its results as a C stream.
+///
+/// The stream must own the data it produces, but readers borrow their statement and
+/// connection. This is why the query is executed on a thread that owns the connection,
+/// which reads the next batch only when the consumer of the stream has taken the previous one.
+/// The connection is dropped when the results are exhausted or the stream is released.
+pub fn export_query<C>(conn: C, query: &str) -> Result<FFI_ArrowArrayStream, ConnectorError>
+where
+    C: Connector + Send + 'static,
+{
+    let (schema_sender, schema_receiver) = sync_channel(1);
+    let (batch_sender, batch_receiver) = sync_channel(0);
+
+    let query = query.to_string();
+    let thread = std::thread::spawn(move || {
+        let mut conn = conn;
+        if let Err(e) = send_results(&mut conn, &query, &schema_sender, &batch_sender) {
+            // errors of batches are sent with the batches, so the schema was not sent yet
+            let _ = schema_sender.send(Err(e));
+        }
+    });
+
+    // the thread sends either the schema or an error before it finishes, unless it panics
+    let Ok(schema) = schema_receiver.recv() else {
+        let message = join_panic(thread).unwrap_or_default();
+        return Err(ConnectorError::Panicked(message));
+    };
+    let reader = ChannelReader {
+        schema: schema?,
+        batches: batch_receiver,
+        thread: Some(thread),
+    };
+    Ok(FFI_ArrowArrayStream::new(Box::new(reader)))
+}
+
+/// Executes the query and sends its schema and then batches, until the receiver is dropped.
+fn send_results<C: Connector>(
+    conn: &mut C,
+    query: &str,
+    schema_sender: &SyncSender<Result<SchemaRef, ConnectorError>>,
+    batch_sender: &SyncSender<Result<RecordBatch, ConnectorError>>,
+) -> Result<(), ConnectorError> {
+    let mut stmt = conn.query(query)?;
+    let mut reader = stmt.start([])?;
+    if schema_sender.send(Ok(reader.get_schema()?)).is_err() {
+        return Ok(());
+    }
+
+    for batch in reader {
+        // the stream was released
+        if batch_sender.send(batch).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Waits for the thread to finish and returns the message of its panic, if it panicked.
+fn join_panic(thread: JoinHandle<()>) -> Option<String> {
+    let panic = thread.join().err()?;
+    let message = panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_default();
+    Some(message)
+}
+
+/// Reads batches sent by the thread of [export_query].
+struct ChannelReader {
+    schema: SchemaRef,
+    batches: Receiver<Result<RecordBatch, ConnectorError>>,
+
+    /// Thread sending the batches, until it has finished.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Iterator for ChannelReader {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.batches.recv() {
+            Ok(batch) => Some(batch.map_err(ArrowError::from)),
+
+            // the results are exhausted, unless the thread panicked
+            Err(_) => {
+                let message = join_panic(self.thread.take()?)?;
+                Some(Err(ConnectorError::Panicked(message).into()))
+            }
+        }
+    }
+}
+
+impl RecordBatchReader for ChannelReader {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+/// Appends all batches of a C stream and finishes the appender.
+pub fn append_stream<'conn, A>(
+    mut appender: A,
+    stream: FFI_ArrowArrayStream,
+) -> Result<(), ConnectorError>
+where
+    A: Append<'conn>,
+{
+    let reader = ArrowArrayStreamReader::try_new(stream)?;
+    for batch in reader {
+        appender.append(batch?)?;
+    }
+    appender.finish()
+}
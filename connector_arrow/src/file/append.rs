@@ -0,0 +1,96 @@
+use std::fs::File;
+
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+
+use crate::api::Append;
+use crate::ConnectorError;
+
+use super::csv_file::CsvAppender;
+use super::{ipc_file, parquet_file, FileError, FileFormat, TableFile};
+
+pub struct FileAppender {
+    inner: Inner,
+}
+
+enum Inner {
+    Csv(Box<CsvAppender>),
+
+    /// Files that cannot be appended to in place are rewritten on finish.
+    Rewrite {
+        file: TableFile,
+        schema: SchemaRef,
+        batches: Vec<RecordBatch>,
+    },
+}
+
+impl FileAppender {
+    pub(super) fn new(file: TableFile) -> Result<Self, ConnectorError> {
+        let inner = match file.format {
+            FileFormat::Csv => Inner::Csv(Box::new(CsvAppender::new(&file.path)?)),
+            FileFormat::Parquet | FileFormat::ArrowIpc => {
+                let (schema, _) = file.read()?;
+                Inner::Rewrite {
+                    file,
+                    schema,
+                    batches: Vec::new(),
+                }
+            }
+        };
+        Ok(FileAppender { inner })
+    }
+}
+
+impl<'conn> Append<'conn> for FileAppender {
+    fn append(&mut self, batch: RecordBatch) -> Result<(), ConnectorError> {
+        match &mut self.inner {
+            Inner::Csv(appender) => appender.append(batch),
+            Inner::Rewrite {
+                schema, batches, ..
+            } => {
+                let batch_schema = batch.schema();
+                let batch_types = batch_schema.fields().iter().map(|f| f.data_type());
+                if !batch_types.eq(schema.fields().iter().map(|f| f.data_type())) {
+                    return Err(ConnectorError::DataSchemaMismatch(
+                        "types of the batch do not match the table".into(),
+                    ));
+                }
+
+                // names of the columns are taken from the table
+                let batch = RecordBatch::try_new(schema.clone(), batch.columns().to_vec())?;
+                batches.push(batch);
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), ConnectorError> {
+        let (file, schema, batches) = match self.inner {
+            Inner::Csv(appender) => return appender.finish(),
+            Inner::Rewrite {
+                file,
+                schema,
+                batches,
+            } => (file, schema, batches),
+        };
+
+        // write existing and new batches into a new file, which then replaces the old one
+        let (_, existing) = file.read()?;
+        let all = existing.chain(batches.into_iter().map(Ok));
+
+        let mut tmp_path = file.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp = File::create(&tmp_path).map_err(FileError::IO)?;
+        let res = match file.format {
+            FileFormat::Parquet => parquet_file::write(tmp, schema, all),
+            FileFormat::ArrowIpc => ipc_file::write(tmp, &schema, all),
+            FileFormat::Csv => unreachable!(),
+        };
+        if let Err(e) = res {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+        std::fs::rename(&tmp_path, &file.path).map_err(FileError::IO)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, BooleanArray, Float64Array, Int64Array, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+
+use crate::api::Connector;
+use crate::ConnectorError;
+
+use super::{FileBatches, FileConnection, FileError};
+
+const BATCH_SIZE: usize = 1024;
+
+/// Reads the header and declared types of a CSV file. Rows are read as the batches are iterated.
+pub(super) fn read(path: &Path) -> Result<(SchemaRef, FileBatches), ConnectorError> {
+    let mut reader = csv::Reader::from_path(path).map_err(FileError::CSV)?;
+    let names = reader.headers().map_err(FileError::CSV)?.clone();
+    let types = read_types(path, names.len())?;
+
+    let fields: Vec<Field> = names
+        .iter()
+        .zip(types)
+        .map(|(name, ty)| Field::new(name, ty, true))
+        .collect();
+    let schema = Arc::new(Schema::new(fields));
+
+    let batches = CsvBatches {
+        schema: schema.clone(),
+        records: reader.into_records(),
+    };
+    Ok((schema, Box::new(batches)))
+}
+
+/// Creates a CSV file with a header and declares types of its columns.
+pub(super) fn create(
+    file: File,
+    path: &Path,
+    schema: &Schema,
+    table_name: &str,
+) -> Result<(), ConnectorError> {
+    let mut types = Vec::with_capacity(schema.fields().len());
+    for field in schema.fields() {
+        let Some(ty) = FileConnection::type_arrow_into_db(field.data_type()) else {
+            return Err(ConnectorError::IncompatibleSchema {
+                table_name: table_name.to_string(),
+                message: format!(
+                    "cannot store arrow type {} of column {} in CSV",
+                    field.data_type(),
+                    field.name()
+                ),
+                hint: None,
+            });
+        };
+        types.push(ty);
+    }
+
+    let mut writer = csv::Writer::from_writer(file);
+    let header = schema.fields().iter().map(|f| f.name());
+    writer.write_record(header).map_err(FileError::CSV)?;
+    writer.flush().map_err(FileError::IO)?;
+
+    let mut writer = csv::Writer::from_path(types_path(path)).map_err(FileError::CSV)?;
+    writer.write_record(&types).map_err(FileError::CSV)?;
+    writer.flush().map_err(FileError::IO)?;
+    Ok(())
+}
+
+/// Removes the file that declares types of the columns, if it exists.
+pub(super) fn drop_types(path: &Path) -> Result<(), ConnectorError> {
+    match std::fs::remove_file(types_path(path)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(FileError::IO(e).into()),
+    }
+}
+
+/// Path of the file that declares types of the columns, `<table_name>.csv.types`.
+fn types_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".types");
+    PathBuf::from(path)
+}
+
+/// Reads declared types of the columns. Columns of files without declared types are text.
+fn read_types(path: &Path, num_columns: usize) -> Result<Vec<DataType>, ConnectorError> {
+    let types_path = types_path(path);
+    if !types_path.is_file() {
+        return Ok(vec![DataType::Utf8; num_columns]);
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_path(types_path)
+        .map_err(FileError::CSV)?;
+    let record = match reader.records().next() {
+        Some(record) => record.map_err(FileError::CSV)?,
+        None => csv::StringRecord::new(),
+    };
+    if record.len() != num_columns {
+        return Err(ConnectorError::DataSchemaMismatch(format!(
+            "{} declares {} types for {} columns",
+            path.display(),
+            record.len(),
+            num_columns
+        )));
+    }
+    record
+        .iter()
+        .map(|ty| {
+            FileConnection::type_db_into_arrow(ty).ok_or_else(|| {
+                ConnectorError::DataSchemaMismatch(format!("unknown declared type {ty}"))
+            })
+        })
+        .collect()
+}
+
+struct CsvBatches {
+    schema: SchemaRef,
+    records: csv::StringRecordsIntoIter<File>,
+}
+
+impl Iterator for CsvBatches {
+    type Item = Result<RecordBatch, ConnectorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut rows = Vec::with_capacity(BATCH_SIZE);
+        for record in self.records.by_ref().take(BATCH_SIZE) {
+            match record {
+                Ok(record) => rows.push(record),
+                Err(e) => return Some(Err(FileError::CSV(e).into())),
+            }
+        }
+        if rows.is_empty() {
+            return None;
+        }
+        Some(self.build_batch(&rows))
+    }
+}
+
+impl CsvBatches {
+    fn build_batch(&self, rows: &[csv::StringRecord]) -> Result<RecordBatch, ConnectorError> {
+        let columns = self
+            .schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let values = rows.iter().map(|r| r.get(index).unwrap_or_default());
+                build_array(field, values)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+        Ok(RecordBatch::try_new_with_options(
+            self.schema.clone(),
+            columns,
+            &options,
+        )?)
+    }
+}
+
+fn build_array<'a>(
+    field: &Field,
+    values: impl Iterator<Item = &'a str>,
+) -> Result<ArrayRef, ConnectorError> {
+    Ok(match field.data_type() {
+        DataType::Boolean => Arc::new(BooleanArray::from(parse_values(field, values, parse_bool)?)),
+        DataType::Int64 => Arc::new(Int64Array::from(parse_values(field, values, |v| {
+            v.parse().ok()
+        })?)),
+        DataType::Float64 => Arc::new(Float64Array::from(parse_values(field, values, |v| {
+            v.parse().ok()
+        })?)),
+        // empty text values are empty strings
+        _ => Arc::new(StringArray::from_iter_values(values)),
+    })
+}
+
+/// Parses non-empty values, reading empty values as nulls.
+fn parse_values<'a, T>(
+    field: &Field,
+    values: impl Iterator<Item = &'a str>,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<Vec<Option<T>>, ConnectorError> {
+    values
+        .map(|value| {
+            if value.is_empty() {
+                return Ok(None);
+            }
+            parse(value).map(Some).ok_or_else(|| {
+                ConnectorError::DataSchemaMismatch(format!(
+                    "cannot read {value:?} as {} of column {}",
+                    field.data_type(),
+                    field.name()
+                ))
+            })
+        })
+        .collect()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Appends rows at the end of a CSV file.
+pub(super) struct CsvAppender {
+    writer: csv::Writer<File>,
+    types: Vec<DataType>,
+}
+
+impl CsvAppender {
+    pub(super) fn new(path: &Path) -> Result<Self, ConnectorError> {
+        let num_columns = {
+            let mut reader = csv::Reader::from_path(path).map_err(FileError::CSV)?;
+            reader.headers().map_err(FileError::CSV)?.len()
+        };
+        let types = read_types(path, num_columns)?;
+
+        let file = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(FileError::IO)?;
+        Ok(CsvAppender {
+            writer: csv::Writer::from_writer(file),
+            types,
+        })
+    }
+
+    pub(super) fn append(&mut self, batch: RecordBatch) -> Result<(), ConnectorError> {
+        if batch.num_columns() != self.types.len() {
+            return Err(ConnectorError::DataSchemaMismatch(format!(
+                "expected {} columns, got {}",
+                self.types.len(),
+                batch.num_columns()
+            )));
+        }
+
+        // any value can be stored as text, but other columns must be read back as declared
+        let schema = batch.schema();
+        for ((field, array), ty) in schema.fields().iter().zip(batch.columns()).zip(&self.types) {
+            if ty == &DataType::Utf8 {
+                if array.null_count() > 0 {
+                    return Err(ConnectorError::NotSupported {
+                        connector_name: "connector_arrow::file",
+                        feature: format!("null values in TEXT column {} of CSV", field.name())
+                            .into(),
+                    });
+                }
+            } else if FileConnection::type_arrow_into_db(field.data_type())
+                != FileConnection::type_arrow_into_db(ty)
+            {
+                return Err(ConnectorError::DataSchemaMismatch(format!(
+                    "cannot append {} into column {} of type {}",
+                    field.data_type(),
+                    field.name(),
+                    ty
+                )));
+            }
+        }
+
+        let options = FormatOptions::default();
+        let formatters = batch
+            .columns()
+            .iter()
+            .map(|array| ArrayFormatter::try_new(array.as_ref(), &options))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut record = csv::StringRecord::with_capacity(0, self.types.len());
+        for row in 0..batch.num_rows() {
+            record.clear();
+            for formatter in &formatters {
+                record.push_field(&formatter.value(row).to_string());
+            }
+            self.writer.write_record(&record).map_err(FileError::CSV)?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn finish(mut self) -> Result<(), ConnectorError> {
+        self.writer.flush().map_err(FileError::IO)?;
+        Ok(())
+    }
+}
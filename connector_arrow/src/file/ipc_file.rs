@@ -0,0 +1,34 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::ipc::reader::FileReader;
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+
+use crate::ConnectorError;
+
+use super::{FileBatches, FileError};
+
+/// Reads the schema of an Arrow IPC file. Batches are read as they are iterated.
+pub(super) fn read(path: &Path) -> Result<(SchemaRef, FileBatches), ConnectorError> {
+    let file = File::open(path).map_err(FileError::IO)?;
+    let reader = FileReader::try_new(BufReader::new(file), None)?;
+    let schema = reader.schema();
+    Ok((schema, Box::new(reader.map(|b| Ok(b?)))))
+}
+
+pub(super) fn write(
+    file: File,
+    schema: &Schema,
+    batches: impl Iterator<Item = Result<RecordBatch, ConnectorError>>,
+) -> Result<(), ConnectorError> {
+    let mut writer = FileWriter::try_new(BufWriter::new(file), schema)?;
+    for batch in batches {
+        writer.write(&batch?)?;
+    }
+    writer.finish()?;
+    writer.into_inner()?.flush().map_err(FileError::IO)?;
+    Ok(())
+}
@@ -0,0 +1,308 @@
+//! Provides `connector_arrow` traits for a directory of Parquet, Arrow IPC and CSV files.
+//!
+//! Each file `<table_name>.<extension>` in the directory is a table, where the extension is
+//! one of `parquet`, `arrow` or `csv`. Tables are created in the [FileFormat] set with
+//! [FileConnection::set_format], which is Parquet by default.
+//!
+//! Parquet and Arrow IPC files store Arrow types as they are. These files cannot be appended
+//! to in place, so appending rewrites the file when the appender is finished.
+//!
+//! CSV files start with a header of column names and do not store types. Types of columns
+//! are declared in a sidecar file `<table_name>.csv.types`, which contains a record of
+//! database types (see [Connector::type_arrow_into_db]). Columns of files without it are
+//! read as [DataType::Utf8]. Empty values are nulls, except in TEXT columns, where they are
+//! empty strings. Because of this, nulls cannot be appended to TEXT columns.
+//!
+//! Supported queries are projections of a whole table: `SELECT * FROM table` and
+//! `SELECT a, b FROM table`.
+
+mod append;
+mod csv_file;
+mod ipc_file;
+mod parquet_file;
+mod schema;
+
+#[doc(hidden)]
+pub use append::FileAppender;
+
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use thiserror::Error;
+
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use crate::api::{ArrowValue, Connector, ResultReader, Statement};
+use crate::errors::ConnectorError;
+
+pub struct FileConnection {
+    dir: PathBuf,
+    format: FileFormat,
+}
+
+/// Format of the file of a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// [Apache Parquet](https://parquet.apache.org/), with extension `parquet`.
+    Parquet,
+    /// [Arrow IPC file format](https://arrow.apache.org/docs/format/Columnar.html#ipc-file-format),
+    /// with extension `arrow`.
+    ArrowIpc,
+    /// Comma-separated values, with extension `csv`.
+    Csv,
+}
+
+impl FileFormat {
+    const ALL: [FileFormat; 3] = [FileFormat::Parquet, FileFormat::ArrowIpc, FileFormat::Csv];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FileFormat::Parquet => "parquet",
+            FileFormat::ArrowIpc => "arrow",
+            FileFormat::Csv => "csv",
+        }
+    }
+}
+
+impl FileConnection {
+    /// Opens a directory as a data store. The directory must exist.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FileConnection {
+            dir: dir.into(),
+            format: FileFormat::Parquet,
+        }
+    }
+
+    /// Sets the format of tables created by [table_create](crate::api::SchemaEdit::table_create).
+    pub fn set_format(&mut self, format: FileFormat) {
+        self.format = format;
+    }
+
+    /// Path of the file of a table, which does not need to exist.
+    fn table_path(&self, table_name: &str, format: FileFormat) -> Result<PathBuf, ConnectorError> {
+        if table_name.is_empty() || table_name.contains(['/', '\\']) {
+            return Err(ConnectorError::IncompatibleSchema {
+                table_name: table_name.to_string(),
+                message: "table name must be a valid file name".into(),
+                hint: None,
+            });
+        }
+        let file_name = format!("{table_name}.{}", format.extension());
+        Ok(self.dir.join(file_name))
+    }
+
+    /// Finds the file of an existing table, in any of the formats.
+    fn table_file(&self, table_name: &str) -> Result<Option<TableFile>, ConnectorError> {
+        // prefer the format of new tables, in case there are files in multiple formats
+        let formats = std::iter::once(self.format).chain(FileFormat::ALL);
+        for format in formats {
+            let path = self.table_path(table_name, format)?;
+            if path.is_file() {
+                return Ok(Some(TableFile { path, format }));
+            }
+        }
+        Ok(None)
+    }
+
+    fn existing_table_file(&self, table_name: &str) -> Result<TableFile, ConnectorError> {
+        self.table_file(table_name)?.ok_or_else(|| {
+            let message = format!("table {table_name} does not exist");
+            FileError::IO(std::io::Error::new(std::io::ErrorKind::NotFound, message)).into()
+        })
+    }
+}
+
+struct TableFile {
+    path: PathBuf,
+    format: FileFormat,
+}
+
+impl TableFile {
+    fn read(&self) -> Result<(SchemaRef, FileBatches), ConnectorError> {
+        match self.format {
+            FileFormat::Parquet => parquet_file::read(&self.path),
+            FileFormat::ArrowIpc => ipc_file::read(&self.path),
+            FileFormat::Csv => csv_file::read(&self.path),
+        }
+    }
+}
+
+/// Batches of a file, which are read as they are iterated.
+type FileBatches = Box<dyn Iterator<Item = Result<RecordBatch, ConnectorError>>>;
+
+#[derive(Error, Debug)]
+pub enum FileError {
+    #[error(transparent)]
+    CSV(#[from] csv::Error),
+
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+}
+
+impl Connector for FileConnection {
+    type Stmt<'conn> = FileStatement<'conn> where Self: 'conn;
+
+    type Append<'conn> = FileAppender where Self: 'conn;
+
+    fn query<'a>(&'a mut self, query: &str) -> Result<Self::Stmt<'a>, ConnectorError> {
+        let (columns, table_name) = parse_query(query).ok_or(ConnectorError::NotSupported {
+            connector_name: "connector_arrow::file",
+            feature: "queries other than SELECT of columns FROM a table".into(),
+        })?;
+        Ok(FileStatement {
+            _conn: PhantomData,
+            file: self.existing_table_file(&table_name)?,
+            columns,
+        })
+    }
+
+    fn append<'a>(&'a mut self, table_name: &str) -> Result<Self::Append<'a>, ConnectorError> {
+        FileAppender::new(self.existing_table_file(table_name)?)
+    }
+
+    /// Types of CSV files. Parquet and Arrow IPC files store Arrow types.
+    fn type_db_into_arrow(database_ty: &str) -> Option<DataType> {
+        Some(match database_ty {
+            "BOOLEAN" => DataType::Boolean,
+            "INTEGER" => DataType::Int64,
+            "FLOAT" => DataType::Float64,
+            "TEXT" => DataType::Utf8,
+            _ => return None,
+        })
+    }
+
+    /// Types of CSV files. Parquet and Arrow IPC files store Arrow types.
+    fn type_arrow_into_db(ty: &DataType) -> Option<String> {
+        Some(
+            match ty {
+                DataType::Boolean => "BOOLEAN",
+                DataType::Int8
+                | DataType::Int16
+                | DataType::Int32
+                | DataType::Int64
+                | DataType::UInt8
+                | DataType::UInt16
+                | DataType::UInt32 => "INTEGER",
+                DataType::Float16 | DataType::Float32 | DataType::Float64 => "FLOAT",
+                DataType::Null
+                | DataType::Utf8
+                | DataType::LargeUtf8
+                | DataType::Utf8View
+                | DataType::Binary
+                | DataType::LargeBinary
+                | DataType::BinaryView
+                | DataType::FixedSizeBinary(_)
+                | DataType::Date32
+                | DataType::Date64
+                | DataType::Time32(_)
+                | DataType::Time64(_)
+                | DataType::Timestamp(_, _)
+                | DataType::Duration(_)
+                | DataType::Interval(_) => "TEXT",
+                _ => return None,
+            }
+            .to_string(),
+        )
+    }
+}
+
+static QUERY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?is)^\s*SELECT\s+(.+?)\s+FROM\s+("(?:[^"]|"")+"|\w+)\s*;?\s*$"#).unwrap()
+});
+
+static IDENT: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^\s*("(?:[^"]|"")+"|\w+)\s*$"#).unwrap());
+
+/// Parses `SELECT <columns> FROM <table>` into names of the columns (None for `*`)
+/// and name of the table.
+fn parse_query(query: &str) -> Option<(Option<Vec<String>>, String)> {
+    let captures = QUERY.captures(query)?;
+
+    let columns = captures.get(1).unwrap().as_str();
+    let columns = if columns.trim() == "*" {
+        None
+    } else {
+        let names = columns
+            .split(',')
+            .map(|c| {
+                IDENT
+                    .captures(c)
+                    .map(|c| unquote(c.get(1).unwrap().as_str()))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(names)
+    };
+
+    let table_name = unquote(captures.get(2).unwrap().as_str());
+    Some((columns, table_name))
+}
+
+fn unquote(ident: &str) -> String {
+    match ident.strip_prefix('"').and_then(|i| i.strip_suffix('"')) {
+        Some(quoted) => quoted.replace("\"\"", "\""),
+        None => ident.to_string(),
+    }
+}
+
+pub struct FileStatement<'conn> {
+    _conn: PhantomData<&'conn FileConnection>,
+    file: TableFile,
+    columns: Option<Vec<String>>,
+}
+
+impl<'conn> Statement<'conn> for FileStatement<'conn> {
+    type Reader<'stmt> = FileReader where Self: 'stmt;
+
+    fn start<'p, I>(&mut self, _params: I) -> Result<Self::Reader<'_>, ConnectorError>
+    where
+        I: IntoIterator<Item = &'p dyn ArrowValue>,
+    {
+        let (schema, batches) = self.file.read()?;
+
+        let Some(columns) = &self.columns else {
+            return Ok(FileReader { schema, batches });
+        };
+        let indices = columns
+            .iter()
+            .map(|c| schema.index_of(c))
+            .collect::<Result<Vec<_>, _>>()?;
+        let schema = std::sync::Arc::new(schema.project(&indices)?);
+        let batches = batches.map(move |b| Ok(b?.project(&indices)?));
+        Ok(FileReader {
+            schema,
+            batches: Box::new(batches),
+        })
+    }
+}
+
+pub struct FileReader {
+    schema: SchemaRef,
+    batches: FileBatches,
+}
+
+impl<'stmt> ResultReader<'stmt> for FileReader {
+    fn get_schema(&mut self) -> Result<SchemaRef, ConnectorError> {
+        Ok(self.schema.clone())
+    }
+}
+
+impl Iterator for FileReader {
+    type Item = Result<RecordBatch, ConnectorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.batches.next()
+    }
+}
+
+/// Name of the table stored in the file, if it is the file of a table.
+fn table_name_of(path: &Path) -> Option<String> {
+    let extension = path.extension()?;
+    if !FileFormat::ALL.iter().any(|f| extension == f.extension()) {
+        return None;
+    }
+    Some(path.file_stem()?.to_str()?.to_string())
+}
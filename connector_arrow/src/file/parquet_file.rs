@@ -0,0 +1,33 @@
+use std::fs::File;
+use std::path::Path;
+
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+
+use crate::ConnectorError;
+
+use super::{FileBatches, FileError};
+
+/// Reads the schema of a Parquet file. Batches are read as they are iterated.
+pub(super) fn read(path: &Path) -> Result<(SchemaRef, FileBatches), ConnectorError> {
+    let file = File::open(path).map_err(FileError::IO)?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file).map_err(FileError::Parquet)?;
+    let schema = builder.schema().clone();
+    let reader = builder.build().map_err(FileError::Parquet)?;
+    Ok((schema, Box::new(reader.map(|b| Ok(b?)))))
+}
+
+pub(super) fn write(
+    file: File,
+    schema: SchemaRef,
+    batches: impl Iterator<Item = Result<RecordBatch, ConnectorError>>,
+) -> Result<(), ConnectorError> {
+    let mut writer = ArrowWriter::try_new(file, schema, None).map_err(FileError::Parquet)?;
+    for batch in batches {
+        writer.write(&batch?).map_err(FileError::Parquet)?;
+    }
+    writer.close().map_err(FileError::Parquet)?;
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use std::fs::OpenOptions;
+
+use arrow::datatypes::SchemaRef;
+
+use crate::api::{SchemaEdit, SchemaGet};
+use crate::{ConnectorError, TableCreateError, TableDropError};
+
+use super::{csv_file, ipc_file, parquet_file, table_name_of, FileConnection, FileError};
+use super::{FileFormat, TableFile};
+
+impl SchemaGet for FileConnection {
+    fn table_list(&mut self) -> Result<Vec<String>, ConnectorError> {
+        let entries = std::fs::read_dir(&self.dir).map_err(FileError::IO)?;
+
+        let mut table_names = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(FileError::IO)?.path();
+            if path.is_file() {
+                table_names.extend(table_name_of(&path));
+            }
+        }
+        table_names.sort();
+        table_names.dedup();
+        Ok(table_names)
+    }
+
+    fn table_get(&mut self, name: &str) -> Result<SchemaRef, ConnectorError> {
+        let (schema, _) = self.existing_table_file(name)?.read()?;
+        Ok(schema)
+    }
+}
+
+impl SchemaEdit for FileConnection {
+    fn table_create(&mut self, name: &str, schema: SchemaRef) -> Result<(), TableCreateError> {
+        if self.table_file(name)?.is_some() {
+            return Err(TableCreateError::TableExists);
+        }
+        let path = self.table_path(name, self.format)?;
+
+        let file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                return Err(TableCreateError::TableExists)
+            }
+            Err(e) => return Err(ConnectorError::from(FileError::IO(e)).into()),
+        };
+
+        let res = match self.format {
+            FileFormat::Parquet => parquet_file::write(file, schema, std::iter::empty()),
+            FileFormat::ArrowIpc => ipc_file::write(file, &schema, std::iter::empty()),
+            FileFormat::Csv => csv_file::create(file, &path, &schema, name),
+        };
+        if let Err(e) = res {
+            // do not leave behind a table that cannot be read
+            let _ = std::fs::remove_file(&path);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn table_drop(&mut self, name: &str) -> Result<(), TableDropError> {
+        let Some(TableFile { path, format }) = self.table_file(name)? else {
+            return Err(TableDropError::TableNonexistent);
+        };
+
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(TableDropError::TableNonexistent)
+            }
+            Err(e) => return Err(ConnectorError::from(FileError::IO(e)).into()),
+        }
+        if format == FileFormat::Csv {
+            csv_file::drop_types(&path)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+
+use super::FlightSqlConnection;
+use crate::api::Append;
+use crate::util::flight_sql::{TableExistsOption, TableNotExistOption};
+use crate::ConnectorError;
+
+/// Buffers appended batches and sends them to the server in a single ingest request
+/// when finished. Batches appended to a dropped appender are discarded.
+pub struct FlightSqlAppender<'conn> {
+    conn: &'conn mut FlightSqlConnection,
+    table_name: String,
+    schema: Option<SchemaRef>,
+    batches: Vec<RecordBatch>,
+}
+
+impl<'conn> FlightSqlAppender<'conn> {
+    pub(super) fn new(conn: &'conn mut FlightSqlConnection, table_name: &str) -> Self {
+        FlightSqlAppender {
+            conn,
+            table_name: table_name.to_string(),
+            schema: None,
+            batches: Vec::new(),
+        }
+    }
+}
+
+impl<'conn> Append<'conn> for FlightSqlAppender<'conn> {
+    fn append(&mut self, batch: RecordBatch) -> Result<(), ConnectorError> {
+        let schema = self.schema.get_or_insert_with(|| batch.schema());
+
+        let batch_schema = batch.schema();
+        let batch_types = batch_schema.fields().iter().map(|f| f.data_type());
+        if !batch_types.eq(schema.fields().iter().map(|f| f.data_type())) {
+            return Err(ConnectorError::DataSchemaMismatch(
+                "types of the batch do not match the previous batches".into(),
+            ));
+        }
+
+        // all batches of a stream must share the schema of the first one
+        let batch = RecordBatch::try_new(schema.clone(), batch.columns().to_vec())?;
+        self.batches.push(batch);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), ConnectorError> {
+        let Some(schema) = self.schema else {
+            return Ok(());
+        };
+
+        self.conn.ingest(
+            &self.table_name,
+            TableNotExistOption::Fail,
+            TableExistsOption::Append,
+            schema,
+            self.batches,
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,328 @@
+//! Provides `connector_arrow` traits for [Arrow Flight SQL](https://arrow.apache.org/docs/format/FlightSql.html)
+//! endpoints.
+//!
+//! Data is exchanged in Arrow format, so results are passed through without conversion.
+//! Appending and creating tables uses the `CommandStatementIngest` command, which must be
+//! supported by the server. Dropping tables is not supported.
+//!
+//! All endpoints of a result are retrieved from the server the connection is connected to.
+
+mod append;
+mod schema;
+mod types;
+
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use arrow_flight::decode::FlightRecordBatchStream;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::sql::client::{FlightSqlServiceClient, PreparedStatement};
+use arrow_flight::{FlightData, FlightDescriptor, FlightInfo, Ticket};
+use futures::{StreamExt, TryStreamExt};
+use prost::Message;
+use thiserror::Error;
+use tokio::runtime::Runtime;
+use tonic::transport::Channel;
+
+use crate::api::{ArrowValue, Connector, ResultReader, Statement};
+use crate::errors::ConnectorError;
+use crate::util::flight_sql::{
+    CommandStatementIngest, TableDefinitionOptions, TableExistsOption, TableNotExistOption,
+};
+use crate::util::transport::transport;
+use crate::util::ArrowRowWriter;
+
+pub use append::FlightSqlAppender;
+
+pub struct FlightSqlConnection {
+    pub rt: Arc<Runtime>,
+    pub client: FlightSqlServiceClient<Channel>,
+}
+
+impl FlightSqlConnection {
+    pub fn new(rt: Arc<Runtime>, client: FlightSqlServiceClient<Channel>) -> Self {
+        FlightSqlConnection { rt, client }
+    }
+
+    /// Sends batches to be ingested into a table.
+    fn ingest(
+        &mut self,
+        table_name: &str,
+        if_not_exist: TableNotExistOption,
+        if_exists: TableExistsOption,
+        schema: SchemaRef,
+        batches: Vec<RecordBatch>,
+    ) -> Result<(), FlightSqlError> {
+        let command = CommandStatementIngest {
+            table_definition_options: Some(TableDefinitionOptions {
+                if_not_exist: if_not_exist as i32,
+                if_exists: if_exists as i32,
+            }),
+            table: table_name.to_string(),
+            ..Default::default()
+        };
+        let descriptor = FlightDescriptor::new_cmd(command.as_any().encode_to_vec());
+        let stream = FlightDataEncoderBuilder::new()
+            .with_flight_descriptor(Some(descriptor))
+            .with_schema(schema)
+            .build(futures::stream::iter(batches.into_iter().map(Ok)));
+
+        // client.do_put would convert the status into a string
+        let client = self.client.inner_mut();
+        self.rt.block_on(async {
+            let data: Vec<FlightData> = stream.try_collect().await?;
+            let mut results = client
+                .do_put(futures::stream::iter(data))
+                .await?
+                .into_inner();
+            while results.message().await?.is_some() {}
+            Ok(())
+        })
+    }
+}
+
+/// Connects to the server over HTTP/2, on a new single-threaded runtime.
+///
+/// Expects an URL in form of `grpc://host:port` or `grpc+tcp://host:port`.
+pub(crate) fn connect(url: &str) -> Result<FlightSqlConnection, ConnectorError> {
+    let address = url
+        .strip_prefix("grpc://")
+        .or_else(|| url.strip_prefix("grpc+tcp://"))
+        .ok_or_else(|| ConnectorError::InvalidUrl("expected grpc://host:port".into()))?;
+    let endpoint = tonic::transport::Endpoint::from_shared(format!("http://{address}"))
+        .map_err(|e| ConnectorError::InvalidUrl(e.to_string()))?;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(FlightSqlError::from)?;
+    let rt = Arc::new(rt);
+
+    let channel = rt
+        .block_on(endpoint.connect())
+        .map_err(FlightSqlError::from)?;
+    Ok(FlightSqlConnection::new(
+        rt,
+        FlightSqlServiceClient::new(channel),
+    ))
+}
+
+#[derive(Error, Debug)]
+pub enum FlightSqlError {
+    // boxed, as these would make ConnectorError very large
+    #[error(transparent)]
+    Status(Box<tonic::Status>),
+
+    #[error(transparent)]
+    Flight(Box<FlightError>),
+
+    #[error(transparent)]
+    Transport(#[from] tonic::transport::Error),
+
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+
+    #[error("Table {0} does not exist")]
+    TableNonexistent(String),
+}
+
+impl From<FlightError> for FlightSqlError {
+    fn from(value: FlightError) -> Self {
+        match value {
+            FlightError::Tonic(status) => FlightSqlError::Status(Box::new(status)),
+            e => FlightSqlError::Flight(Box::new(e)),
+        }
+    }
+}
+
+impl From<tonic::Status> for FlightSqlError {
+    fn from(value: tonic::Status) -> Self {
+        FlightSqlError::Status(Box::new(value))
+    }
+}
+
+impl Connector for FlightSqlConnection {
+    type Stmt<'conn>
+        = FlightSqlStatement<'conn>
+    where
+        Self: 'conn;
+
+    type Append<'conn>
+        = FlightSqlAppender<'conn>
+    where
+        Self: 'conn;
+
+    fn query<'a>(&'a mut self, query: &str) -> Result<Self::Stmt<'a>, ConnectorError> {
+        Ok(FlightSqlStatement {
+            conn: self,
+            query: query.to_string(),
+        })
+    }
+
+    fn append<'a>(&'a mut self, table_name: &str) -> Result<Self::Append<'a>, ConnectorError> {
+        Ok(FlightSqlAppender::new(self, table_name))
+    }
+
+    /// Parses names of Arrow types, as they are produced by [Self::type_arrow_into_db].
+    fn type_db_into_arrow(database_ty: &str) -> Option<DataType> {
+        types::parse_type_name(database_ty)
+    }
+
+    /// Arrow types are sent to the server as they are, which decides how to store them.
+    fn type_arrow_into_db(ty: &DataType) -> Option<String> {
+        Some(ty.to_string())
+    }
+}
+
+pub struct FlightSqlStatement<'conn> {
+    conn: &'conn mut FlightSqlConnection,
+    query: String,
+}
+
+impl<'conn> Statement<'conn> for FlightSqlStatement<'conn> {
+    type Reader<'stmt>
+        = FlightSqlReader<'stmt>
+    where
+        Self: 'stmt;
+
+    fn start<'p, I>(&mut self, params: I) -> Result<Self::Reader<'_>, ConnectorError>
+    where
+        I: IntoIterator<Item = &'p dyn ArrowValue>,
+    {
+        let params: Vec<_> = params.into_iter().collect();
+
+        let FlightSqlConnection { rt, client } = &mut *self.conn;
+        let query = self.query.clone();
+        let (info, prepared) = if params.is_empty() {
+            (rt.block_on(client.execute(query, None))?, None)
+        } else {
+            let params = params_batch(&params)?;
+            rt.block_on(async {
+                let mut stmt = client.prepare(query, None).await?;
+                stmt.set_parameters(params)?;
+                let info = stmt.execute().await?;
+                Ok::<_, ConnectorError>((info, Some(stmt)))
+            })?
+        };
+
+        FlightSqlReader::new(self.conn, info, prepared)
+    }
+}
+
+/// Converts query parameters into a batch with a single row.
+fn params_batch(params: &[&dyn ArrowValue]) -> Result<RecordBatch, ConnectorError> {
+    let fields: Vec<_> = params
+        .iter()
+        .enumerate()
+        .map(|(index, p)| Field::new(format!("${}", index + 1), p.get_data_type().clone(), true))
+        .collect();
+    let schema = Arc::new(Schema::new(fields));
+
+    let mut writer = ArrowRowWriter::new(schema.clone(), 1);
+    writer.prepare_for_batch(1)?;
+    for (field, param) in schema.fields().iter().zip(params) {
+        transport(field, *param, &mut writer)?;
+    }
+    let batches = writer.finish()?;
+    Ok(batches.into_iter().next().unwrap())
+}
+
+pub struct FlightSqlReader<'stmt> {
+    conn: &'stmt mut FlightSqlConnection,
+    schema: SchemaRef,
+    tickets: std::vec::IntoIter<Ticket>,
+    stream: Option<FlightRecordBatchStream>,
+
+    /// Statement the tickets were issued for, closed when the reader is dropped,
+    /// because servers may execute it only when the tickets are retrieved.
+    prepared: Option<PreparedStatement<Channel>>,
+}
+
+impl<'stmt> FlightSqlReader<'stmt> {
+    fn new(
+        conn: &'stmt mut FlightSqlConnection,
+        info: FlightInfo,
+        prepared: Option<PreparedStatement<Channel>>,
+    ) -> Result<Self, ConnectorError> {
+        let tickets: Vec<_> = info
+            .endpoint
+            .iter()
+            .filter_map(|e| e.ticket.clone())
+            .collect();
+        let mut reader = FlightSqlReader {
+            conn,
+            schema: Arc::new(Schema::empty()),
+            tickets: tickets.into_iter(),
+            stream: None,
+            prepared,
+        };
+        reader.schema = if info.schema.is_empty() {
+            reader.read_first_schema()?
+        } else {
+            Arc::new(info.try_decode_schema()?)
+        };
+        Ok(reader)
+    }
+
+    /// Reads the schema from the stream of the first endpoint, for servers that do not
+    /// know the schema before the query is executed.
+    fn read_first_schema(&mut self) -> Result<SchemaRef, ConnectorError> {
+        let Some(ticket) = self.tickets.next() else {
+            return Ok(Arc::new(Schema::empty()));
+        };
+        let stream = self.conn.rt.block_on(self.conn.client.do_get(ticket))?;
+
+        // decode messages until the schema, without consuming any of the batches
+        let mut decoder = stream.into_inner();
+        while decoder.schema().is_none() {
+            match self.conn.rt.block_on(decoder.next()) {
+                Some(data) => {
+                    data.map_err(FlightSqlError::from)?;
+                }
+                None => break,
+            }
+        }
+        let schema = decoder.schema().cloned();
+        self.stream = Some(FlightRecordBatchStream::new(decoder));
+        Ok(schema.unwrap_or_else(|| Arc::new(Schema::empty())))
+    }
+}
+
+impl<'stmt> ResultReader<'stmt> for FlightSqlReader<'stmt> {
+    fn get_schema(&mut self) -> Result<SchemaRef, ConnectorError> {
+        Ok(self.schema.clone())
+    }
+}
+
+impl<'stmt> Iterator for FlightSqlReader<'stmt> {
+    type Item = Result<RecordBatch, ConnectorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(stream) = &mut self.stream {
+                match self.conn.rt.block_on(stream.next()) {
+                    Some(batch) => return Some(batch.map_err(|e| FlightSqlError::from(e).into())),
+                    None => self.stream = None,
+                }
+            }
+
+            // read the next endpoint
+            let ticket = self.tickets.next()?;
+            match self.conn.rt.block_on(self.conn.client.do_get(ticket)) {
+                Ok(stream) => self.stream = Some(stream),
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+impl Drop for FlightSqlReader<'_> {
+    fn drop(&mut self) {
+        if let Some(stmt) = self.prepared.take() {
+            // the results were already read, so failing to close is not reported
+            let _ = self.conn.rt.block_on(stmt.close());
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use arrow::array::{Array, AsArray, RecordBatch};
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow_flight::sql::CommandGetTables;
+use arrow_flight::IpcMessage;
+use tonic::Code;
+
+use super::{FlightSqlConnection, FlightSqlError, FlightSqlReader};
+use crate::api::{SchemaEdit, SchemaGet};
+use crate::util::flight_sql::{TableExistsOption, TableNotExistOption};
+use crate::{ConnectorError, TableCreateError, TableDropError};
+
+impl FlightSqlConnection {
+    fn get_tables(&mut self, command: CommandGetTables) -> Result<RecordBatch, ConnectorError> {
+        let info = self.rt.block_on(self.client.get_tables(command))?;
+        let mut reader = FlightSqlReader::new(self, info, None)?;
+        let schema = reader.schema.clone();
+        let batches = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+        Ok(arrow::compute::concat_batches(&schema, &batches)?)
+    }
+}
+
+impl SchemaGet for FlightSqlConnection {
+    fn table_list(&mut self) -> Result<Vec<String>, ConnectorError> {
+        let tables = self.get_tables(CommandGetTables::default())?;
+
+        let names = table_names(&tables)?;
+        Ok(names.iter().flatten().map(|n| n.to_string()).collect())
+    }
+
+    fn table_get(&mut self, name: &str) -> Result<SchemaRef, ConnectorError> {
+        // the filter is a LIKE pattern, so exact name is matched below
+        let tables = self.get_tables(CommandGetTables {
+            table_name_filter_pattern: Some(name.to_string()),
+            include_schema: true,
+            ..Default::default()
+        })?;
+
+        let names = table_names(&tables)?;
+        let row = (0..names.len())
+            .find(|row| names.is_valid(*row) && names.value(*row) == name)
+            .ok_or_else(|| FlightSqlError::TableNonexistent(name.to_string()))?;
+
+        let schemas = tables
+            .column_by_name("table_schema")
+            .ok_or(ConnectorError::CannotConvertSchema)?
+            .as_binary::<i32>();
+        let schema_ipc = schemas.value(row).to_vec();
+        let schema = Schema::try_from(IpcMessage(schema_ipc.into()))?;
+        Ok(Arc::new(schema))
+    }
+}
+
+fn table_names(tables: &RecordBatch) -> Result<&arrow::array::StringArray, ConnectorError> {
+    let names = tables
+        .column_by_name("table_name")
+        .ok_or(ConnectorError::CannotConvertSchema)?;
+    Ok(names.as_string::<i32>())
+}
+
+impl SchemaEdit for FlightSqlConnection {
+    fn table_create(&mut self, name: &str, schema: SchemaRef) -> Result<(), TableCreateError> {
+        let res = self.ingest(
+            name,
+            TableNotExistOption::Create,
+            TableExistsOption::Fail,
+            schema,
+            Vec::new(),
+        );
+        match res {
+            Ok(()) => Ok(()),
+            Err(FlightSqlError::Status(s)) if s.code() == Code::AlreadyExists => {
+                Err(TableCreateError::TableExists)
+            }
+            Err(e) => Err(ConnectorError::from(e).into()),
+        }
+    }
+
+    fn table_drop(&mut self, _name: &str) -> Result<(), TableDropError> {
+        Err(ConnectorError::NotSupported {
+            connector_name: "connector_arrow::flight_sql",
+            feature: "dropping tables".into(),
+        }
+        .into())
+    }
+}
@@ -0,0 +1,95 @@
+use arrow::datatypes::{DataType, IntervalUnit, TimeUnit};
+
+/// Parses names of Arrow types, as they are formatted by `DataType`'s `Display`.
+/// Nested types are not supported.
+pub fn parse_type_name(name: &str) -> Option<DataType> {
+    let (name, args) = match name.split_once('(') {
+        Some((name, args)) => (name, Some(args.strip_suffix(')')?)),
+        None => (name, None),
+    };
+
+    Some(match (name, args) {
+        ("Null", None) => DataType::Null,
+        ("Boolean", None) => DataType::Boolean,
+        ("Int8", None) => DataType::Int8,
+        ("Int16", None) => DataType::Int16,
+        ("Int32", None) => DataType::Int32,
+        ("Int64", None) => DataType::Int64,
+        ("UInt8", None) => DataType::UInt8,
+        ("UInt16", None) => DataType::UInt16,
+        ("UInt32", None) => DataType::UInt32,
+        ("UInt64", None) => DataType::UInt64,
+        ("Float16", None) => DataType::Float16,
+        ("Float32", None) => DataType::Float32,
+        ("Float64", None) => DataType::Float64,
+        ("Date32", None) => DataType::Date32,
+        ("Date64", None) => DataType::Date64,
+        ("Binary", None) => DataType::Binary,
+        ("LargeBinary", None) => DataType::LargeBinary,
+        ("BinaryView", None) => DataType::BinaryView,
+        ("Utf8", None) => DataType::Utf8,
+        ("LargeUtf8", None) => DataType::LargeUtf8,
+        ("Utf8View", None) => DataType::Utf8View,
+
+        ("Timestamp", Some(args)) => {
+            let (unit, tz) = args.split_once(", ")?;
+            let tz = match tz {
+                "None" => None,
+                tz => Some(parse_quoted(tz.strip_prefix("Some(")?.strip_suffix(')')?)?.into()),
+            };
+            DataType::Timestamp(parse_time_unit(unit)?, tz)
+        }
+        ("Time32", Some(unit)) => DataType::Time32(parse_time_unit(unit)?),
+        ("Time64", Some(unit)) => DataType::Time64(parse_time_unit(unit)?),
+        ("Duration", Some(unit)) => DataType::Duration(parse_time_unit(unit)?),
+        ("Interval", Some(unit)) => DataType::Interval(match unit {
+            "YearMonth" => IntervalUnit::YearMonth,
+            "DayTime" => IntervalUnit::DayTime,
+            "MonthDayNano" => IntervalUnit::MonthDayNano,
+            _ => return None,
+        }),
+        ("FixedSizeBinary", Some(size)) => DataType::FixedSizeBinary(size.parse().ok()?),
+        ("Decimal128", Some(args)) => {
+            let (precision, scale) = args.split_once(", ")?;
+            DataType::Decimal128(precision.parse().ok()?, scale.parse().ok()?)
+        }
+        ("Decimal256", Some(args)) => {
+            let (precision, scale) = args.split_once(", ")?;
+            DataType::Decimal256(precision.parse().ok()?, scale.parse().ok()?)
+        }
+        _ => return None,
+    })
+}
+
+fn parse_time_unit(unit: &str) -> Option<TimeUnit> {
+    Some(match unit {
+        "Second" => TimeUnit::Second,
+        "Millisecond" => TimeUnit::Millisecond,
+        "Microsecond" => TimeUnit::Microsecond,
+        "Nanosecond" => TimeUnit::Nanosecond,
+        _ => return None,
+    })
+}
+
+/// Parses a string literal, as it is formatted by `Debug`.
+fn parse_quoted(s: &str) -> Option<String> {
+    let mut chars = s.strip_prefix('"')?.strip_suffix('"')?.chars();
+    let mut res = String::new();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        res.push(match chars.next()? {
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            _ => return None,
+        });
+    }
+    Some(res)
+}
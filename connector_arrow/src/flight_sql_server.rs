@@ -0,0 +1,604 @@
+//! [Arrow Flight SQL](https://arrow.apache.org/docs/format/FlightSql.html) server,
+//! so Flight SQL clients can query a connection and append data into it.
+//!
+//! ```no_run
+//! use connector_arrow::flight_sql_server::FlightSqlServer;
+//!
+//! # async fn serve() -> Result<(), Box<dyn std::error::Error>> {
+//! let conn = rusqlite::Connection::open_in_memory()?;
+//! let conn = connector_arrow::sqlite::SQLiteConnection::new(conn);
+//!
+//! tonic::transport::Server::builder()
+//!     .add_service(FlightSqlServer::new(conn).into_service())
+//!     .serve("127.0.0.1:50051".parse()?)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Supported:
+//! - `CommandStatementQuery`,
+//! - prepared statements, with parameters bound with DoPut,
+//! - `CommandGetDbSchemas` and `CommandGetTables`, via [SchemaGet](crate::api::SchemaGet),
+//! - `CommandStatementIngest`, via [Append](crate::api::Append) and
+//!   [SchemaEdit](crate::api::SchemaEdit).
+//!
+//! GetFlightInfo of a query only prepares it, to report errors, and returns a ticket with
+//! an opaque handle of the query. The query is executed on DoGet of the ticket and its results
+//! are streamed as they are read, so the returned info does not contain the schema.
+//! Statements with parameters are executed once for each row of the parameters.
+//! Tickets of prepared statements are valid until the statement is closed.
+//!
+//! Handles are random, so clients cannot execute statements of other clients.
+//! The server keeps only the latest tickets of statements, which expire as new ones are issued,
+//! and a limited number of prepared statements, so creating more fails until some are closed.
+//! See [FlightSqlServer::set_max_statements].
+//!
+//! All requests share a single connection, so they are executed one at a time.
+//! A DoGet holds the connection until its results are sent or the client cancels it.
+//! Catalogs and database schemas are not exposed: all tables are reported in a single
+//! catalog and schema, both without a name.
+
+// tonic::Status is large, but it is the error type of the service
+#![allow(clippy::result_large_err)]
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryInto;
+use std::hash::{BuildHasher, Hasher};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+use arrow_flight::decode::FlightRecordBatchStream;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::sql::server::{FlightSqlService, PeekableFlightDataStream};
+use arrow_flight::sql::{
+    ActionClosePreparedStatementRequest, ActionCreatePreparedStatementRequest,
+    ActionCreatePreparedStatementResult, Any, CommandGetDbSchemas, CommandGetTables,
+    CommandPreparedStatementQuery, CommandStatementQuery, DoPutUpdateResult, ProstMessageExt,
+    SqlInfo, TicketStatementQuery,
+};
+use arrow_flight::{
+    Action, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo, PutResult, Ticket,
+};
+use futures::{Stream, TryStreamExt};
+use prost::Message;
+use tokio::sync::{mpsc, oneshot};
+use tonic::{Request, Response, Status};
+
+use crate::dynamic::{DynConnector, QueryStream};
+use crate::errors::{ConnectorError, DbErrorKind, TableCreateError, TableDropError};
+use crate::util::flight_sql::{CommandStatementIngest, TableExistsOption, TableNotExistOption};
+
+/// Flight SQL service that executes requests over a connection.
+pub struct FlightSqlServer<C> {
+    conn: Arc<Mutex<C>>,
+    next_handle: AtomicU64,
+    max_statements: usize,
+    statements: Mutex<HashMap<u64, PreparedStatement>>,
+
+    /// Handles and queries of statement tickets, oldest first.
+    tickets: Mutex<VecDeque<(u64, String)>>,
+}
+
+struct PreparedStatement {
+    query: String,
+    params: Option<Vec<RecordBatch>>,
+}
+
+type DoGetStream = Pin<Box<dyn Stream<Item = Result<FlightData, Status>> + Send + 'static>>;
+
+impl<C> FlightSqlServer<C>
+where
+    C: DynConnector + Send + 'static,
+{
+    pub fn new(conn: C) -> Self {
+        FlightSqlServer {
+            conn: Arc::new(Mutex::new(conn)),
+            next_handle: AtomicU64::new(0),
+            max_statements: 1024,
+            statements: Mutex::new(HashMap::new()),
+            tickets: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Sets the maximum number of open prepared statements and of valid statement tickets.
+    /// Defaults to 1024.
+    ///
+    /// Creating a prepared statement over the limit fails, while issuing a statement ticket
+    /// over the limit expires the oldest ticket.
+    pub fn set_max_statements(&mut self, max: usize) {
+        self.max_statements = max;
+    }
+
+    /// Wraps the server into a service that can be added to a [tonic] server.
+    pub fn into_service(self) -> FlightServiceServer<Self> {
+        FlightServiceServer::new(self)
+    }
+
+    /// Runs a function with the connection, on a thread where blocking is allowed.
+    async fn run<T, F>(&self, f: F) -> Result<T, Status>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut C) -> Result<T, Status> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || f(&mut *lock(&conn)?))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+    }
+
+    /// Random handle of a statement or a ticket, which clients cannot guess.
+    fn new_handle(&self) -> u64 {
+        // RandomState is seeded randomly, so the hash of a counter is unpredictable
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(self.next_handle.fetch_add(1, Ordering::Relaxed));
+        hasher.finish()
+    }
+
+    /// Prepares the query, to report errors before it is executed with DoGet of the ticket.
+    async fn flight_info(
+        &self,
+        query: String,
+        ticket: Ticket,
+        descriptor: FlightDescriptor,
+    ) -> Result<Response<FlightInfo>, Status> {
+        self.run(move |conn| conn.prepare(&query).map(|_| ()).map_err(status))
+            .await?;
+
+        // schemas are not known before the statement is executed
+        let info = FlightInfo::new()
+            .with_endpoint(FlightEndpoint::new().with_ticket(ticket))
+            .with_descriptor(descriptor);
+        Ok(Response::new(info))
+    }
+
+    /// Executes the query and streams its results.
+    ///
+    /// The query is executed on a blocking thread, which reads the next batch only when
+    /// the previous one was taken by the response stream.
+    async fn execute(
+        &self,
+        query: String,
+        params: Option<Vec<RecordBatch>>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let (schema_sender, schema_receiver) = oneshot::channel();
+        let (batch_sender, mut batch_receiver) = mpsc::channel(1);
+
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = match lock(&conn) {
+                Ok(conn) => conn,
+                Err(e) => {
+                    let _ = schema_sender.send(Err(e));
+                    return;
+                }
+            };
+            let stream = match QueryStream::new(&mut *conn, &query, params.as_deref()) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    let _ = schema_sender.send(Err(status(e)));
+                    return;
+                }
+            };
+            if schema_sender.send(Ok(stream.schema())).is_err() {
+                return;
+            }
+            for batch in stream {
+                // the response was cancelled
+                if batch_sender.blocking_send(batch).is_err() {
+                    break;
+                }
+            }
+        });
+
+        // the thread sends either the schema or an error, unless it panicked
+        let schema = schema_receiver
+            .await
+            .map_err(|_| Status::internal("execution of the query panicked"))??;
+        let batches = futures::stream::poll_fn(move |cx| batch_receiver.poll_recv(cx))
+            .map_err(FlightError::from);
+        let stream = FlightDataEncoderBuilder::new()
+            .with_schema(schema)
+            .build(batches)
+            .map_err(Status::from);
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn ingest(
+        &self,
+        command: CommandStatementIngest,
+        request: Request<PeekableFlightDataStream>,
+    ) -> Result<Response<<Self as FlightService>::DoPutStream>, Status> {
+        if command.temporary {
+            return Err(Status::unimplemented("temporary tables are not supported"));
+        }
+        if command.transaction_id.is_some() {
+            return Err(Status::unimplemented("transactions are not supported"));
+        }
+        if command.catalog.is_some() || command.schema.is_some() {
+            return Err(Status::unimplemented(
+                "catalogs and database schemas are not supported",
+            ));
+        }
+
+        let (schema, batches) = read_batches(request).await?;
+        let record_count = self
+            .run(move |conn| ingest(conn, &command, schema, batches))
+            .await?;
+
+        let result = DoPutUpdateResult { record_count };
+        let output = futures::stream::iter(vec![Ok(PutResult {
+            app_metadata: result.as_any().encode_to_vec().into(),
+        })]);
+        Ok(Response::new(Box::pin(output)))
+    }
+}
+
+#[tonic::async_trait]
+impl<C> FlightSqlService for FlightSqlServer<C>
+where
+    C: DynConnector + Send + 'static,
+{
+    type FlightService = Self;
+
+    async fn get_flight_info_statement(
+        &self,
+        query: CommandStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        if query.transaction_id.is_some() {
+            return Err(Status::unimplemented("transactions are not supported"));
+        }
+        let handle = self.new_handle();
+        let ticket = TicketStatementQuery {
+            statement_handle: handle.to_be_bytes().to_vec().into(),
+        };
+        let info = self
+            .flight_info(
+                query.query.clone(),
+                ticket_of(&ticket),
+                request.into_inner(),
+            )
+            .await?;
+
+        let mut tickets = lock(&self.tickets)?;
+        if tickets.len() >= self.max_statements {
+            tickets.pop_front();
+        }
+        tickets.push_back((handle, query.query));
+        Ok(info)
+    }
+
+    async fn get_flight_info_prepared_statement(
+        &self,
+        query: CommandPreparedStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let handle = handle_of(&query.prepared_statement_handle)?;
+        if !lock(&self.statements)?.contains_key(&handle) {
+            return Err(Status::not_found("prepared statement does not exist"));
+        }
+
+        // the statement was prepared when it was created
+        let info = FlightInfo::new()
+            .with_endpoint(FlightEndpoint::new().with_ticket(ticket_of(&query)))
+            .with_descriptor(request.into_inner());
+        Ok(Response::new(info))
+    }
+
+    async fn get_flight_info_schemas(
+        &self,
+        query: CommandGetDbSchemas,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let schema = query.clone().into_builder().schema();
+        metadata_info(&query, &schema, request.into_inner())
+    }
+
+    async fn get_flight_info_tables(
+        &self,
+        query: CommandGetTables,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let schema = query.clone().into_builder().schema();
+        metadata_info(&query, &schema, request.into_inner())
+    }
+
+    async fn do_get_statement(
+        &self,
+        ticket: TicketStatementQuery,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let handle = handle_of(&ticket.statement_handle)?;
+        let query = lock(&self.tickets)?
+            .iter()
+            .find(|(h, _)| *h == handle)
+            .map(|(_, query)| query.clone())
+            .ok_or_else(|| Status::not_found("ticket has expired or does not exist"))?;
+        self.execute(query, None).await
+    }
+
+    async fn do_get_prepared_statement(
+        &self,
+        query: CommandPreparedStatementQuery,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let handle = handle_of(&query.prepared_statement_handle)?;
+        let (query, params) = {
+            let statements = lock(&self.statements)?;
+            let stmt = statements
+                .get(&handle)
+                .ok_or_else(|| Status::not_found("prepared statement does not exist"))?;
+            (stmt.query.clone(), stmt.params.clone())
+        };
+        self.execute(query, params).await
+    }
+
+    async fn do_get_schemas(
+        &self,
+        query: CommandGetDbSchemas,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let mut builder = query.into_builder();
+        builder.append("", "");
+        let schema = builder.schema();
+        let batch = builder.build()?;
+        Ok(Response::new(encode(schema, vec![batch])))
+    }
+
+    async fn do_get_tables(
+        &self,
+        query: CommandGetTables,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let include_schema = query.include_schema;
+        let tables = self
+            .run(move |conn| {
+                let names = conn.table_list().map_err(status)?;
+                let mut tables = Vec::with_capacity(names.len());
+                for name in names {
+                    let schema = if include_schema {
+                        conn.table_get(&name).map_err(status)?
+                    } else {
+                        Arc::new(Schema::empty())
+                    };
+                    tables.push((name, schema));
+                }
+                Ok(tables)
+            })
+            .await?;
+
+        let mut builder = query.into_builder();
+        for (name, schema) in tables {
+            builder.append("", "", name, "TABLE", &schema)?;
+        }
+        let schema = builder.schema();
+        let batch = builder.build()?;
+        Ok(Response::new(encode(schema, vec![batch])))
+    }
+
+    async fn do_put_fallback(
+        &self,
+        request: Request<PeekableFlightDataStream>,
+        message: Any,
+    ) -> Result<Response<<Self as FlightService>::DoPutStream>, Status> {
+        if message.type_url != CommandStatementIngest::TYPE_URL {
+            return Err(Status::unimplemented(format!(
+                "do_put: The defined request is invalid: {}",
+                message.type_url
+            )));
+        }
+        let command = CommandStatementIngest::decode(&*message.value)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        self.ingest(command, request).await
+    }
+
+    async fn do_put_prepared_statement_query(
+        &self,
+        query: CommandPreparedStatementQuery,
+        request: Request<PeekableFlightDataStream>,
+    ) -> Result<Response<<Self as FlightService>::DoPutStream>, Status> {
+        let handle = handle_of(&query.prepared_statement_handle)?;
+        let (_, batches) = read_batches(request).await?;
+
+        lock(&self.statements)?
+            .get_mut(&handle)
+            .ok_or_else(|| Status::not_found("prepared statement does not exist"))?
+            .params = Some(batches);
+        Ok(Response::new(Box::pin(futures::stream::empty())))
+    }
+
+    async fn do_action_create_prepared_statement(
+        &self,
+        query: ActionCreatePreparedStatementRequest,
+        _request: Request<Action>,
+    ) -> Result<ActionCreatePreparedStatementResult, Status> {
+        if query.transaction_id.is_some() {
+            return Err(Status::unimplemented("transactions are not supported"));
+        }
+
+        // prepare the statement only to report errors early
+        let sql = query.query.clone();
+        self.run(move |conn| conn.prepare(&sql).map(|_| ()).map_err(status))
+            .await?;
+
+        let handle = self.new_handle();
+        let stmt = PreparedStatement {
+            query: query.query,
+            params: None,
+        };
+        let mut statements = lock(&self.statements)?;
+        if statements.len() >= self.max_statements {
+            return Err(Status::resource_exhausted(
+                "too many prepared statements, close some of them first",
+            ));
+        }
+        statements.insert(handle, stmt);
+
+        // schemas are not known before the statement is executed
+        Ok(ActionCreatePreparedStatementResult {
+            prepared_statement_handle: handle.to_be_bytes().to_vec().into(),
+            dataset_schema: Default::default(),
+            parameter_schema: Default::default(),
+        })
+    }
+
+    async fn do_action_close_prepared_statement(
+        &self,
+        query: ActionClosePreparedStatementRequest,
+        _request: Request<Action>,
+    ) -> Result<(), Status> {
+        let handle = handle_of(&query.prepared_statement_handle)?;
+        lock(&self.statements)?.remove(&handle);
+        Ok(())
+    }
+
+    async fn register_sql_info(&self, _id: i32, _result: &SqlInfo) {}
+}
+
+fn ingest(
+    conn: &mut dyn DynConnector,
+    command: &CommandStatementIngest,
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+) -> Result<i64, Status> {
+    let options = command.table_definition_options.unwrap_or_default();
+    let table_name = command.table.as_str();
+
+    let exists = conn
+        .table_list()
+        .map_err(status)?
+        .iter()
+        .any(|t| t == table_name);
+    if exists {
+        match options.if_exists() {
+            TableExistsOption::Append => {}
+            TableExistsOption::Replace => {
+                conn.table_drop(table_name).map_err(drop_status)?;
+                conn.table_create(table_name, schema)
+                    .map_err(create_status)?;
+            }
+            TableExistsOption::Fail => {
+                return Err(Status::already_exists("table already exists"));
+            }
+            TableExistsOption::Unspecified => {
+                return Err(Status::invalid_argument("if_exists option is not set"));
+            }
+        }
+    } else {
+        match options.if_not_exist() {
+            TableNotExistOption::Create => {
+                conn.table_create(table_name, schema)
+                    .map_err(create_status)?;
+            }
+            TableNotExistOption::Fail => {
+                return Err(Status::not_found("table does not exist"));
+            }
+            TableNotExistOption::Unspecified => {
+                return Err(Status::invalid_argument("if_not_exist option is not set"));
+            }
+        }
+    }
+
+    let record_count: usize = batches.iter().map(|b| b.num_rows()).sum();
+    conn.append(table_name, batches).map_err(status)?;
+    Ok(record_count as i64)
+}
+
+/// Decodes the batches of a DoPut stream.
+async fn read_batches(
+    request: Request<PeekableFlightDataStream>,
+) -> Result<(SchemaRef, Vec<RecordBatch>), Status> {
+    let stream = request.into_inner().map_err(FlightError::from);
+    let mut stream = FlightRecordBatchStream::new_from_flight_data(stream);
+
+    let mut batches = Vec::new();
+    while let Some(batch) = stream.try_next().await? {
+        batches.push(batch);
+    }
+    let schema = stream
+        .schema()
+        .cloned()
+        .ok_or_else(|| Status::invalid_argument("stream does not contain a schema"))?;
+    Ok((schema, batches))
+}
+
+fn encode(schema: SchemaRef, batches: Vec<RecordBatch>) -> DoGetStream {
+    let stream = FlightDataEncoderBuilder::new()
+        .with_schema(schema)
+        .build(futures::stream::iter(batches.into_iter().map(Ok)))
+        .map_err(Status::from);
+    Box::pin(stream)
+}
+
+/// Info of a metadata request, which is executed on DoGet of the command itself.
+fn metadata_info<M: ProstMessageExt>(
+    command: &M,
+    schema: &Schema,
+    descriptor: FlightDescriptor,
+) -> Result<Response<FlightInfo>, Status> {
+    let info = FlightInfo::new()
+        .try_with_schema(schema)
+        .map_err(arrow_status)?
+        .with_endpoint(FlightEndpoint::new().with_ticket(ticket_of(command)))
+        .with_descriptor(descriptor);
+    Ok(Response::new(info))
+}
+
+fn ticket_of<M: ProstMessageExt>(message: &M) -> Ticket {
+    Ticket::new(message.as_any().encode_to_vec())
+}
+
+fn handle_of(bytes: &[u8]) -> Result<u64, Status> {
+    let bytes = bytes
+        .try_into()
+        .map_err(|_| Status::invalid_argument("invalid handle"))?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>, Status> {
+    mutex
+        .lock()
+        .map_err(|_| Status::internal("a previous request panicked"))
+}
+
+fn status(error: ConnectorError) -> Status {
+    let message = error.to_string();
+    match (&error, error.db_error().map(|d| d.kind)) {
+        (ConnectorError::NotSupported { .. }, _) => Status::unimplemented(message),
+        (ConnectorError::DataSchemaMismatch(_), _)
+        | (ConnectorError::IncompatibleSchema { .. }, _)
+        | (ConnectorError::DataOutOfRange, _) => Status::invalid_argument(message),
+        (_, Some(DbErrorKind::Syntax)) => Status::invalid_argument(message),
+        (_, Some(DbErrorKind::ConstraintViolation)) => Status::failed_precondition(message),
+        (_, Some(DbErrorKind::SerializationFailure)) => Status::aborted(message),
+        (_, Some(DbErrorKind::ConnectionLost)) => Status::unavailable(message),
+        (_, Some(DbErrorKind::Timeout)) => Status::deadline_exceeded(message),
+        _ => Status::internal(message),
+    }
+}
+
+fn create_status(error: TableCreateError) -> Status {
+    match error {
+        TableCreateError::TableExists => Status::already_exists("table already exists"),
+        TableCreateError::Connector(e) => status(e),
+    }
+}
+
+fn drop_status(error: TableDropError) -> Status {
+    match error {
+        TableDropError::TableNonexistent => Status::not_found("table does not exist"),
+        TableDropError::Connector(e) => status(e),
+    }
+}
+
+fn arrow_status(error: ArrowError) -> Status {
+    status(ConnectorError::from(error))
+}
@@ -37,14 +37,25 @@
 //!
 //! For a list of supported databases, refer to the [crates.io page](https://crates.io/crates/connector_arrow).
 
+#[cfg(feature = "adbc")]
+pub mod adbc;
 pub mod api;
+pub mod dynamic;
 mod errors;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "flight_sql_server")]
+pub mod flight_sql_server;
 mod params;
 pub mod types;
 pub mod util;
 
 #[cfg(feature = "src_duckdb")]
 pub mod duckdb;
+#[cfg(feature = "src_file")]
+pub mod file;
+#[cfg(feature = "src_flight_sql")]
+pub mod flight_sql;
 #[cfg(feature = "src_mysql")]
 pub mod mysql;
 #[cfg(feature = "src_postgres")]
@@ -55,6 +66,7 @@ pub mod sqlite;
 pub mod tiberius;
 
 pub use arrow;
+pub use dynamic::connect;
 pub use errors::*;
 
 use arrow::record_batch::RecordBatch;
@@ -1,33 +1,73 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
 use arrow::datatypes::*;
 use arrow::record_batch::RecordBatch;
+use arrow::temporal_conversions::EPOCH_DAYS_FROM_CE;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Timelike};
 use itertools::{zip_eq, Itertools};
 use mysql::prelude::Queryable;
-use mysql::Value;
+use mysql::{LocalInfileHandler, MySqlError, Value};
 
 use crate::api::Append;
-use crate::types::{FixedSizeBinaryType, NullType};
+use crate::types::{
+    FixedSizeBinaryType, FixedSizeListType, LargeListType, ListType, MapType, NullType, StructType,
+};
 use crate::util::escape::escaped_ident_bt;
-use crate::util::transport::{self, Consume, ConsumeTy};
+use crate::util::transport::{Consume, ConsumeTy};
 use crate::util::ArrayCellRef;
 use crate::{impl_consume_unsupported, ConnectorError};
 
+/// How [MySQLAppender] writes data into tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppendMode {
+    /// Multi-row `INSERT INTO ... VALUES` statements.
+    Insert,
+
+    /// `LOAD DATA LOCAL INFILE` statements, with each batch streamed from memory
+    /// as tab-separated values. Much faster for large loads.
+    ///
+    /// Requires `local_infile` to be enabled on the server. With `LOCAL`, the server
+    /// reports data conversion errors as warnings, so appends that produce warnings fail
+    /// and roll back the transaction.
+    LoadDataLocalInfile,
+}
+
+/// Contents of the file requested by the next `LOAD DATA LOCAL INFILE` statement.
+#[derive(Clone, Default)]
+pub(super) struct InfileBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl InfileBuffer {
+    pub(super) fn local_infile_handler(&self) -> LocalInfileHandler {
+        let buffer = self.0.clone();
+        LocalInfileHandler::new(move |_file_name, writer| {
+            let data = std::mem::take(&mut *buffer.lock().unwrap());
+            writer.write_all(&data)
+        })
+    }
+}
+
 pub struct MySQLAppender<'conn, C: Queryable> {
     table: String,
     client: &'conn mut C,
+    infile: Option<InfileBuffer>,
 }
 
 impl<'conn, C: Queryable> MySQLAppender<'conn, C> {
-    pub fn new(client: &'conn mut C, table_name: &str) -> Result<Self, ConnectorError> {
+    pub(super) fn new(
+        client: &'conn mut C,
+        infile: Option<InfileBuffer>,
+        table_name: &str,
+    ) -> Result<Self, ConnectorError> {
         client.query_drop("START TRANSACTION;")?;
         Ok(Self {
             table: table_name.to_owned(),
             client,
+            infile,
         })
     }
-}
 
-impl<'conn, C: Queryable> Append<'conn> for MySQLAppender<'conn, C> {
-    fn append(&mut self, batch: RecordBatch) -> Result<(), ConnectorError> {
+    fn append_insert(&mut self, batch: RecordBatch) -> Result<(), ConnectorError> {
         // TODO: 30 is a guess, we need benchmarking to find the optimum value
         const BATCH_SIZE: usize = 30;
 
@@ -37,7 +77,7 @@ impl<'conn, C: Queryable> Append<'conn> for MySQLAppender<'conn, C> {
         for batch_number in 0..(batch.num_rows() / BATCH_SIZE) {
             let rows_range = (batch_number * BATCH_SIZE)..((batch_number + 1) * BATCH_SIZE);
 
-            let params: Vec<Value> = collect_args(&batch, rows_range);
+            let params: Vec<Value> = collect_args(&batch, rows_range)?;
             self.client.exec_iter(&batch_query, params)?;
         }
 
@@ -45,13 +85,63 @@ impl<'conn, C: Queryable> Append<'conn> for MySQLAppender<'conn, C> {
             let rows_range = (batch.num_rows() - last_batch_size)..batch.num_rows();
 
             let last_query = insert_query(&self.table, batch.num_columns(), last_batch_size);
-            let params: Vec<Value> = collect_args(&batch, rows_range);
+            let params: Vec<Value> = collect_args(&batch, rows_range)?;
             self.client.exec_iter(&last_query, params)?;
         }
 
         Ok(())
     }
 
+    fn append_load_data(
+        &mut self,
+        infile: InfileBuffer,
+        batch: RecordBatch,
+    ) -> Result<(), ConnectorError> {
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+
+        {
+            let mut buffer = infile.0.lock().unwrap();
+            buffer.clear();
+            write_tsv(&batch, &mut buffer)?;
+        }
+
+        // file name is ignored by our local infile handler
+        let query = format!(
+            "LOAD DATA LOCAL INFILE 'batch.tsv' INTO TABLE {} CHARACTER SET binary",
+            escaped_ident_bt(&self.table)
+        );
+        let result = self.client.query_iter(query)?;
+        let warnings = result.warnings();
+        drop(result);
+
+        if warnings > 0 {
+            // rows with values that could not be converted were stored anyway
+            let warning: Option<(String, u16, String)> =
+                self.client.query_first("SHOW WARNINGS;")?;
+            self.client.query_drop("ROLLBACK;")?;
+
+            let (_, code, message) = warning.unwrap_or_default();
+            let error = MySqlError {
+                state: "HY000".to_string(),
+                message,
+                code,
+            };
+            return Err(mysql::Error::MySqlError(error).into());
+        }
+        Ok(())
+    }
+}
+
+impl<'conn, C: Queryable> Append<'conn> for MySQLAppender<'conn, C> {
+    fn append(&mut self, batch: RecordBatch) -> Result<(), ConnectorError> {
+        match self.infile.clone() {
+            Some(infile) => self.append_load_data(infile, batch),
+            None => self.append_insert(batch),
+        }
+    }
+
     fn finish(self) -> Result<(), ConnectorError> {
         self.client.query_drop("COMMIT;")?;
         Ok(())
@@ -72,7 +162,10 @@ fn insert_query(table_name: &str, cols: usize, rows: usize) -> String {
     )
 }
 
-fn collect_args(batch: &RecordBatch, rows_range: std::ops::Range<usize>) -> Vec<Value> {
+fn collect_args(
+    batch: &RecordBatch,
+    rows_range: std::ops::Range<usize>,
+) -> Result<Vec<Value>, ConnectorError> {
     let mut res = Vec::with_capacity(rows_range.len() * batch.num_columns());
 
     let schema = batch.schema();
@@ -84,13 +177,81 @@ fn collect_args(batch: &RecordBatch, rows_range: std::ops::Range<usize>) -> Vec<
         })
         .collect_vec();
 
+    let transporters = ArrayCellRef::transporters(schema.fields())?;
     for row_number in rows_range {
-        for cell in &mut row {
+        for (cell, transport) in zip_eq(&mut row, &transporters) {
+            cell.row_number = row_number;
+            transport(cell, &mut res)?;
+        }
+    }
+    Ok(res)
+}
+
+/// Writes the batch in the default format of `LOAD DATA`: fields terminated by tab,
+/// lines terminated by newline, special characters escaped by backslash and NULL as `\N`.
+fn write_tsv(batch: &RecordBatch, out: &mut Vec<u8>) -> Result<(), ConnectorError> {
+    let schema = batch.schema();
+    let mut row = zip_eq(batch.columns(), schema.fields())
+        .map(|(array, field)| ArrayCellRef {
+            array,
+            field,
+            row_number: 0,
+        })
+        .collect_vec();
+
+    let transporters = ArrayCellRef::transporters(schema.fields())?;
+    let mut values: Vec<Value> = Vec::with_capacity(row.len());
+    for row_number in 0..batch.num_rows() {
+        for (cell, transport) in zip_eq(&mut row, &transporters) {
             cell.row_number = row_number;
-            transport::transport(cell.field, cell as &_, &mut res).unwrap();
+            transport(cell, &mut values)?;
+        }
+
+        for (index, value) in values.drain(..).enumerate() {
+            if index > 0 {
+                out.push(b'\t');
+            }
+            write_tsv_value(value, out);
+        }
+        out.push(b'\n');
+    }
+    Ok(())
+}
+
+fn write_tsv_value(value: Value, out: &mut Vec<u8>) {
+    // writing into a Vec<u8> cannot fail
+    match value {
+        Value::NULL => out.extend_from_slice(b"\\N"),
+        Value::Bytes(bytes) => {
+            for byte in bytes {
+                match byte {
+                    b'\\' => out.extend_from_slice(b"\\\\"),
+                    b'\t' => out.extend_from_slice(b"\\t"),
+                    b'\n' => out.extend_from_slice(b"\\n"),
+                    b'\r' => out.extend_from_slice(b"\\r"),
+                    b'\0' => out.extend_from_slice(b"\\0"),
+                    _ => out.push(byte),
+                }
+            }
         }
+        Value::Int(v) => write!(out, "{v}").unwrap(),
+        Value::UInt(v) => write!(out, "{v}").unwrap(),
+        // f32 is widened, so the server does not round it out of range when parsing it as double
+        Value::Float(v) => write!(out, "{:e}", f64::from(v)).unwrap(),
+        Value::Double(v) => write!(out, "{v:e}").unwrap(),
+        Value::Date(year, month, day, hour, minute, second, micros) => write!(
+            out,
+            "{year:04}-{month:02}-{day:02} {hour:02}:{minute:02}:{second:02}.{micros:06}"
+        )
+        .unwrap(),
+        Value::Time(negative, days, hours, minutes, seconds, micros) => write!(
+            out,
+            "{}{}:{minutes:02}:{seconds:02}.{micros:06}",
+            if negative { "-" } else { "" },
+            days * 24 + u32::from(hours)
+        )
+        .unwrap(),
     }
-    res
 }
 
 impl Consume for Vec<Value> {}
@@ -106,13 +267,15 @@ macro_rules! impl_consume_ty {
                 &mut self,
                 _ty: &DataType,
                 value: <$ArrTy as crate::types::ArrowType>::Native,
-            ) {
+            ) -> Result<(), ConnectorError> {
                 let value: Value = $value_kind(($conversion)(value));
                 self.push(value);
+                Ok(())
             }
 
-            fn consume_null(&mut self) {
+            fn consume_null(&mut self) -> Result<(), ConnectorError> {
                 self.push(Value::NULL);
+                Ok(())
             }
         }
     };
@@ -130,43 +293,151 @@ impl_consume_ty!(UInt64Type, Value::UInt);
 impl_consume_ty!(Float16Type, Value::Float, f32::from);
 impl_consume_ty!(Float32Type, Value::Float);
 impl_consume_ty!(Float64Type, Value::Double);
+impl_consume_ty!(DurationSecondType, Value::Int);
+impl_consume_ty!(DurationMillisecondType, Value::Int);
+impl_consume_ty!(DurationMicrosecondType, Value::Int);
+impl_consume_ty!(DurationNanosecondType, Value::Int);
 impl_consume_ty!(Utf8Type, Value::Bytes, String::into_bytes);
 impl_consume_ty!(BinaryType, Value::Bytes);
 impl_consume_ty!(LargeBinaryType, Value::Bytes);
 impl_consume_ty!(FixedSizeBinaryType, Value::Bytes);
 
+// MySQL stores temporal values with microsecond precision, so nanoseconds are truncated
+macro_rules! impl_consume_temporal_ty {
+    ($ArrTy: ty, $conversion: expr) => {
+        impl ConsumeTy<$ArrTy> for Vec<Value> {
+            fn consume(
+                &mut self,
+                _ty: &DataType,
+                value: <$ArrTy as crate::types::ArrowType>::Native,
+            ) -> Result<(), ConnectorError> {
+                self.push(($conversion)(value)?);
+                Ok(())
+            }
+
+            fn consume_null(&mut self) -> Result<(), ConnectorError> {
+                self.push(Value::NULL);
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_consume_temporal_ty!(TimestampSecondType, |v| datetime_value(
+    DateTime::from_timestamp(v, 0)
+));
+impl_consume_temporal_ty!(TimestampMillisecondType, |v| datetime_value(
+    DateTime::from_timestamp_millis(v)
+));
+impl_consume_temporal_ty!(TimestampMicrosecondType, |v| datetime_value(
+    DateTime::from_timestamp_micros(v)
+));
+impl_consume_temporal_ty!(TimestampNanosecondType, |v| datetime_value(Some(
+    DateTime::from_timestamp_nanos(v)
+)));
+impl_consume_temporal_ty!(Date32Type, |v: i32| date_value(
+    v.checked_add(EPOCH_DAYS_FROM_CE)
+        .and_then(NaiveDate::from_num_days_from_ce_opt)
+));
+impl_consume_temporal_ty!(Date64Type, |v| date_value(
+    DateTime::from_timestamp_millis(v).map(|d| d.date_naive())
+));
+impl_consume_temporal_ty!(Time32SecondType, |v: i32| time_value(
+    i64::from(v).checked_mul(1_000_000)
+));
+impl_consume_temporal_ty!(Time32MillisecondType, |v: i32| time_value(
+    i64::from(v).checked_mul(1_000)
+));
+impl_consume_temporal_ty!(Time64MicrosecondType, |v| time_value(Some(v)));
+impl_consume_temporal_ty!(Time64NanosecondType, |v: i64| time_value(Some(v / 1_000)));
+
+/// MySQL DATETIME and DATE can store years from 1000 to 9999.
+fn datetime_value(value: Option<DateTime<chrono::Utc>>) -> Result<Value, ConnectorError> {
+    let value: NaiveDateTime = value
+        .map(|v| v.naive_utc())
+        .filter(|v| (1000..=9999).contains(&v.year()))
+        .ok_or(ConnectorError::DataOutOfRange)?;
+    Ok(Value::Date(
+        value.year() as u16,
+        value.month() as u8,
+        value.day() as u8,
+        value.hour() as u8,
+        value.minute() as u8,
+        value.second() as u8,
+        value.nanosecond() / 1_000,
+    ))
+}
+
+fn date_value(value: Option<NaiveDate>) -> Result<Value, ConnectorError> {
+    let value = value.ok_or(ConnectorError::DataOutOfRange)?;
+    datetime_value(Some(value.and_time(chrono::NaiveTime::MIN).and_utc()))
+}
+
+/// Time of day, which MySQL TIME can always store.
+fn time_value(micros: Option<i64>) -> Result<Value, ConnectorError> {
+    let micros = micros
+        .filter(|m| (0..86_400_000_000).contains(m))
+        .ok_or(ConnectorError::DataOutOfRange)?;
+    let seconds = micros / 1_000_000;
+    Ok(Value::Time(
+        false,
+        0,
+        (seconds / 3600) as u8,
+        (seconds / 60 % 60) as u8,
+        (seconds % 60) as u8,
+        (micros % 1_000_000) as u32,
+    ))
+}
+
 impl ConsumeTy<NullType> for Vec<Value> {
-    fn consume(&mut self, _ty: &DataType, _value: ()) {
+    fn consume(&mut self, _ty: &DataType, _value: ()) -> Result<(), ConnectorError> {
+        self.push(Value::NULL);
+        Ok(())
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        self.push(Value::NULL);
+        Ok(())
+    }
+}
+
+impl ConsumeTy<Decimal128Type> for Vec<Value> {
+    fn consume(&mut self, ty: &DataType, value: i128) -> Result<(), ConnectorError> {
+        let value = crate::util::decimal::decimal128_to_string(ty, value);
+        self.push(Value::Bytes(value.into_bytes()));
+        Ok(())
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
         self.push(Value::NULL);
+        Ok(())
     }
+}
 
-    fn consume_null(&mut self) {
+impl ConsumeTy<Decimal256Type> for Vec<Value> {
+    fn consume(&mut self, ty: &DataType, value: i256) -> Result<(), ConnectorError> {
+        let value = crate::util::decimal::decimal256_to_string(ty, value);
+        self.push(Value::Bytes(value.into_bytes()));
+        Ok(())
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
         self.push(Value::NULL);
+        Ok(())
     }
 }
 
 impl_consume_unsupported!(
     Vec<Value>,
     (
-        TimestampSecondType,
-        TimestampMillisecondType,
-        TimestampMicrosecondType,
-        TimestampNanosecondType,
-        Date32Type,
-        Date64Type,
-        Time32SecondType,
-        Time32MillisecondType,
-        Time64MicrosecondType,
-        Time64NanosecondType,
-        DurationSecondType,
-        DurationMillisecondType,
-        DurationMicrosecondType,
-        DurationNanosecondType,
         IntervalDayTimeType,
         IntervalMonthDayNanoType,
         IntervalYearMonthType,
-        Decimal128Type,
-        Decimal256Type,
         LargeUtf8Type,
+        ListType,
+        LargeListType,
+        FixedSizeListType,
+        StructType,
+        MapType,
     )
 );
@@ -0,0 +1,65 @@
+use mysql::{DriverError, MySqlError};
+
+use crate::{DbErrorDetails, DbErrorKind};
+
+pub(crate) fn error_details(error: &mysql::Error) -> Option<DbErrorDetails> {
+    match error {
+        mysql::Error::MySqlError(e) => Some(server_error_details(e)),
+        mysql::Error::IoError(_) | mysql::Error::CodecError(_) => {
+            Some(DbErrorDetails::new(DbErrorKind::ConnectionLost, None))
+        }
+        mysql::Error::DriverError(DriverError::ConnectTimeout | DriverError::Timeout) => {
+            Some(DbErrorDetails::new(DbErrorKind::Timeout, None))
+        }
+        mysql::Error::DriverError(DriverError::CouldNotConnect(_)) => {
+            Some(DbErrorDetails::new(DbErrorKind::ConnectionLost, None))
+        }
+        _ => None,
+    }
+}
+
+/// See: https://dev.mysql.com/doc/mysql-errors/8.0/en/server-error-reference.html
+fn server_error_details(error: &MySqlError) -> DbErrorDetails {
+    let kind = match error.code {
+        // ER_LOCK_DEADLOCK
+        1213 => DbErrorKind::SerializationFailure,
+        // ER_LOCK_WAIT_TIMEOUT, ER_QUERY_INTERRUPTED, ER_QUERY_TIMEOUT
+        1205 | 1317 | 3024 => DbErrorKind::Timeout,
+        _ => match error.state.get(0..2) {
+            Some("08") => DbErrorKind::ConnectionLost,
+            Some("23") => DbErrorKind::ConstraintViolation,
+            Some("40") => DbErrorKind::SerializationFailure,
+            Some("42") => DbErrorKind::Syntax,
+            _ => DbErrorKind::Other,
+        },
+    };
+    let mut details = DbErrorDetails::new(kind, Some(error.state.clone()));
+
+    // MySQL does not report the constraint or the column separately from the message
+    let message = error.message.as_str();
+    match error.code {
+        // ER_DUP_ENTRY: "Duplicate entry '1' for key 't.PRIMARY'"
+        1062 => {
+            details.constraint = message
+                .rsplit_once(" for key '")
+                .and_then(|(_, key)| key.strip_suffix('\''))
+                .map(str::to_string);
+        }
+        // ER_BAD_NULL_ERROR: "Column 'a' cannot be null"
+        1048 => {
+            details.column = message
+                .strip_prefix("Column '")
+                .and_then(|m| m.split_once('\''))
+                .map(|(column, _)| column.to_string());
+        }
+        // ER_ROW_IS_REFERENCED_2, ER_NO_REFERENCED_ROW_2: "... CONSTRAINT `fk` FOREIGN KEY ..."
+        1451 | 1452 => {
+            details.constraint = message
+                .split_once("CONSTRAINT `")
+                .and_then(|(_, m)| m.split_once('`'))
+                .map(|(constraint, _)| constraint.to_string());
+        }
+        _ => {}
+    }
+    details
+}
@@ -1,85 +1,155 @@
 mod append;
+mod errors;
 mod query;
 mod schema;
 mod types;
 
+use std::marker::PhantomData;
+
 use arrow::datatypes::*;
 use mysql::prelude::*;
 
 use crate::api::Connector;
+use crate::util::ReaderOptions;
 use crate::ConnectorError;
 
-pub struct MySQLConnection<C: Queryable> {
+pub use append::AppendMode;
+pub use query::MySQLProtocol;
+
+pub(crate) use errors::error_details;
+
+/// Connection to MySQL that implements [Connector], [crate::api::SchemaGet] and [crate::api::SchemaEdit].
+///
+/// Generic argument `P` selects the protocol used for queries (see [MySQLProtocol]):
+/// - [mysql::Binary] (default), which uses prepared statements,
+/// - [mysql::Text], which can also run statements that cannot be prepared.
+pub struct MySQLConnection<C: Queryable, P = mysql::Binary> {
     conn: C,
+    infile: Option<append::InfileBuffer>,
+    reader_options: ReaderOptions,
+    _protocol: PhantomData<P>,
 }
 
-impl<C: Queryable> MySQLConnection<C> {
+impl<C: Queryable, P> MySQLConnection<C, P> {
     pub fn new(conn: C) -> Self {
-        MySQLConnection { conn }
+        MySQLConnection {
+            conn,
+            infile: None,
+            reader_options: ReaderOptions::default(),
+            _protocol: PhantomData,
+        }
+    }
+
+    /// Sets how query results are read into arrow.
+    pub fn set_reader_options(&mut self, options: ReaderOptions) {
+        self.reader_options = options;
     }
 
     pub fn unwrap(self) -> C {
         self.conn
     }
+}
 
-    pub fn ping(&self) -> Result<(),Error> {
-        self.conn.ping()
+impl<P> MySQLConnection<mysql::Conn, P> {
+    pub fn ping(&mut self) -> Result<(), ConnectorError> {
+        Ok(self.conn.ping()?)
+    }
+
+    /// Selects how appenders of this connection write data into tables.
+    ///
+    /// [AppendMode::LoadDataLocalInfile] installs a local infile handler on the
+    /// underlying connection, which is reset when switching back to [AppendMode::Insert].
+    pub fn set_append_mode(&mut self, mode: AppendMode) {
+        match mode {
+            AppendMode::Insert => {
+                self.conn.set_local_infile_handler(None);
+                self.infile = None;
+            }
+            AppendMode::LoadDataLocalInfile => {
+                let infile = append::InfileBuffer::default();
+                self.conn
+                    .set_local_infile_handler(Some(infile.local_infile_handler()));
+                self.infile = Some(infile);
+            }
+        }
     }
 }
 
-impl<C: Queryable> Connector for MySQLConnection<C> {
-    type Stmt<'conn> = query::MySQLStatement<'conn, C> where Self: 'conn;
+impl<C: Queryable, P: MySQLProtocol> Connector for MySQLConnection<C, P> {
+    type Stmt<'conn> = query::MySQLStatement<'conn, C, P> where Self: 'conn;
 
     type Append<'conn> = append::MySQLAppender<'conn, C> where Self: 'conn;
 
     fn query<'a>(&'a mut self, query: &str) -> Result<Self::Stmt<'a>, ConnectorError> {
-        let stmt = self.conn.prep(query)?;
+        let stmt = P::prepare(&mut self.conn, query)?;
         Ok(query::MySQLStatement {
             conn: &mut self.conn,
             stmt,
+            reader_options: &self.reader_options,
         })
     }
 
     fn append<'a>(&'a mut self, table_name: &str) -> Result<Self::Append<'a>, ConnectorError> {
-        append::MySQLAppender::new(&mut self.conn, table_name)
+        append::MySQLAppender::new(&mut self.conn, self.infile.clone(), table_name)
     }
 
     fn type_db_into_arrow(ty: &str) -> Option<DataType> {
-        dbg!(ty);
-
         let (ty, unsigned) = ty
             .strip_suffix(" unsigned")
             .map(|p| (p, true))
             .unwrap_or((ty, false));
 
         // strip size suffix and anything following it
-        let ty = if let Some(open_parent) = ty.find('(') {
-            &ty[0..open_parent]
+        let (ty, params) = if let Some(open_parent) = ty.find('(') {
+            (&ty[0..open_parent], Some(&ty[open_parent..]))
         } else {
-            ty
+            (ty, None)
         };
 
         Some(match (ty, unsigned) {
             ("null", _) => DataType::Null,
 
             ("tinyint" | "bool" | "boolean", false) => DataType::Int8,
-            ("smallint", false) => DataType::Int16,
-            ("integer" | "int", false) => DataType::Int32,
+            ("smallint" | "year", false) => DataType::Int16,
+            ("integer" | "int" | "mediumint", false) => DataType::Int32,
             ("bigint", false) => DataType::Int64,
 
             ("tinyint", true) => DataType::UInt8,
             ("smallint", true) => DataType::UInt16,
-            ("integer" | "int", true) => DataType::UInt32,
+            ("integer" | "int" | "mediumint", true) => DataType::UInt32,
             ("bigint", true) => DataType::UInt64,
 
             ("real" | "float" | "float4", _) => DataType::Float32,
             ("double" | "float8", _) => DataType::Float64,
 
-            ("bit" | "tinyblob" | "mediumblob" | "longblob" | "blob" | "binary", _) => {
-                DataType::Binary
+            ("decimal" | "newdecimal" | "numeric", _) => {
+                let (precision, scale) = types::parse_decimal_params(params)?;
+                if precision <= Decimal128Type::MAX_PRECISION {
+                    DataType::Decimal128(precision, scale)
+                } else {
+                    DataType::Decimal256(precision, scale)
+                }
+            }
+
+            ("timestamp" | "datetime", _) => DataType::Timestamp(TimeUnit::Microsecond, None),
+            ("date", _) => DataType::Date32,
+            ("time", _) => DataType::Time64(TimeUnit::Microsecond),
+
+            (
+                "bit" | "tinyblob" | "mediumblob" | "longblob" | "blob" | "binary" | "varbinary",
+                _,
+            ) => DataType::Binary,
+
+            ("tinytext" | "mediumtext" | "longtext" | "text" | "varchar" | "char", _) => {
+                DataType::Utf8
             }
 
-            ("tinytext" | "mediumtext" | "longtext" | "text" | "varchar", _) => DataType::Utf8,
+            // JSON is returned as text, [types::create_field] marks it with an extension type
+            ("json", _) => DataType::Utf8,
+
+            ("enum" | "set", _) => {
+                DataType::Dictionary(Box::new(DataType::UInt16), Box::new(DataType::Utf8))
+            }
 
             _ => return None,
         })
@@ -102,7 +172,24 @@ impl<C: Queryable> Connector for MySQLConnection<C> {
                 DataType::Float32 => "float",
                 DataType::Float64 => "double",
 
-                DataType::Binary => "longblob",
+                // MySQL stores up to microseconds and years from 1000 to 9999,
+                // appending values out of this range fails
+                DataType::Timestamp(_, _) => "datetime(6)",
+                DataType::Date32 | DataType::Date64 => "date",
+                DataType::Time32(_) | DataType::Time64(_) => "time(6)",
+                DataType::Duration(_) => "bigint",
+                DataType::Interval(_) => return None,
+
+                // MySQL DECIMAL supports precision of up to 65 and scale of up to 30,
+                // larger decimals are stored as text.
+                DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale)
+                    if *precision <= 65 && (0..=30).contains(scale) =>
+                {
+                    return Some(format!("decimal({precision}, {scale})"))
+                }
+                DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => "longtext",
+
+                DataType::Binary | DataType::BinaryView => "longblob",
                 DataType::FixedSizeBinary(1) => "binary",
                 DataType::FixedSizeBinary(2) => "blob",
                 DataType::FixedSizeBinary(3) => "mediumblob",
@@ -110,8 +197,14 @@ impl<C: Queryable> Connector for MySQLConnection<C> {
                 DataType::FixedSizeBinary(_) => return None,
                 DataType::LargeBinary => return None,
 
-                DataType::Utf8 => "longtext",
+                DataType::Utf8 | DataType::Utf8View => "longtext",
                 DataType::LargeUtf8 => return None,
+                // see crate::util::dictionary_encode_schema
+                DataType::Dictionary(_, value)
+                    if matches!(**value, DataType::Utf8 | DataType::LargeUtf8) =>
+                {
+                    return Self::type_arrow_into_db(value)
+                }
 
                 _ => return None,
             }
@@ -1,56 +1,127 @@
+use arrow::temporal_conversions::EPOCH_DAYS_FROM_CE;
 use arrow::{datatypes::*, record_batch::RecordBatch};
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
 use mysql::prelude::*;
 use pac_cell::PacCell;
 
 use crate::api::{ResultReader, Statement};
 use crate::impl_produce_unsupported;
-use crate::types::{ArrowType, FixedSizeBinaryType, NullType};
+use crate::types::{
+    ArrowType, FixedSizeBinaryType, FixedSizeListType, LargeListType, ListType, MapType, NullType,
+    StructType,
+};
 use crate::util::transport::ProduceTy;
-use crate::util::{self, transport::Produce};
+use crate::util::{self, transport::Produce, ReaderOptions};
 use crate::ConnectorError;
 
-pub struct MySQLStatement<'conn, C: Queryable> {
-    pub(super) stmt: mysql::Statement,
+/// MySQL protocol that is used to execute queries and transfer results.
+///
+/// Implemented for:
+/// - [mysql::Binary], which prepares statements and receives values in binary encoding,
+/// - [mysql::Text], which sends the query as-is and receives values as text. This can also run
+///   statements that cannot be prepared (`SHOW`, some `CALL`s or multiple statements), but only the
+///   first result set is returned and query parameters are not supported.
+pub trait MySQLProtocol: Protocol + Sized {
+    /// Query in the form that is needed for execution.
+    type Prepared;
+
+    #[doc(hidden)]
+    fn prepare<C: Queryable>(conn: &mut C, query: &str) -> Result<Self::Prepared, ConnectorError>;
+
+    #[doc(hidden)]
+    fn execute<'c, C: Queryable>(
+        conn: &'c mut C,
+        prepared: &Self::Prepared,
+        has_params: bool,
+    ) -> Result<mysql::QueryResult<'c, 'c, 'c, Self>, ConnectorError>;
+}
+
+impl MySQLProtocol for mysql::Binary {
+    type Prepared = mysql::Statement;
+
+    fn prepare<C: Queryable>(conn: &mut C, query: &str) -> Result<Self::Prepared, ConnectorError> {
+        Ok(conn.prep(query)?)
+    }
+
+    fn execute<'c, C: Queryable>(
+        conn: &'c mut C,
+        prepared: &Self::Prepared,
+        _has_params: bool,
+    ) -> Result<mysql::QueryResult<'c, 'c, 'c, Self>, ConnectorError> {
+        // TODO: params
+
+        Ok(conn.exec_iter(prepared, ())?)
+    }
+}
+
+impl MySQLProtocol for mysql::Text {
+    type Prepared = String;
+
+    fn prepare<C: Queryable>(_conn: &mut C, query: &str) -> Result<Self::Prepared, ConnectorError> {
+        Ok(query.to_string())
+    }
+
+    fn execute<'c, C: Queryable>(
+        conn: &'c mut C,
+        prepared: &Self::Prepared,
+        has_params: bool,
+    ) -> Result<mysql::QueryResult<'c, 'c, 'c, Self>, ConnectorError> {
+        if has_params {
+            return Err(ConnectorError::NotSupported {
+                connector_name: "connector_arrow::mysql text protocol",
+                feature: "query params".into(),
+            });
+        }
+
+        Ok(conn.query_iter(prepared)?)
+    }
+}
+
+pub struct MySQLStatement<'conn, C: Queryable, P: MySQLProtocol> {
+    pub(super) stmt: P::Prepared,
     pub(super) conn: &'conn mut C,
+    pub(super) reader_options: &'conn ReaderOptions,
 }
 
-impl<'conn, C: Queryable> Statement<'conn> for MySQLStatement<'conn, C> {
-    type Reader<'stmt> = MySQLQueryResult<'stmt>
+impl<'conn, C: Queryable, P: MySQLProtocol> Statement<'conn> for MySQLStatement<'conn, C, P> {
+    type Reader<'stmt>
+        = MySQLQueryResult<'stmt, P>
     where
         Self: 'stmt;
 
-    fn start<'p, I>(&mut self, _params: I) -> Result<Self::Reader<'_>, ConnectorError>
+    fn start<'p, I>(&mut self, params: I) -> Result<Self::Reader<'_>, ConnectorError>
     where
         I: IntoIterator<Item = &'p dyn crate::api::ArrowValue>,
     {
-        // TODO: params
-
-        let query_result = self.conn.exec_iter(&self.stmt, ())?;
+        let has_params = params.into_iter().next().is_some();
+        let reader_options = self.reader_options;
+        let query_result = P::execute(self.conn, &self.stmt, has_params)?;
 
         // PacCell is needed so we can return query_result and result_set that mutably borrows query result.
         let pac = PacCell::try_new(query_result, |qr| -> Result<_, ConnectorError> {
             let result_set = qr.iter().ok_or(ConnectorError::NoResultSets)?;
             let schema = super::types::get_result_schema(&result_set)?;
+            let schema = reader_options.apply(schema);
             Ok(MySQLResultReader { result_set, schema })
         })?;
         Ok(MySQLQueryResult(pac))
     }
 }
 
-pub struct MySQLQueryResult<'stmt>(
+pub struct MySQLQueryResult<'stmt, P: Protocol>(
     PacCell<
-        mysql::QueryResult<'stmt, 'stmt, 'stmt, mysql::Binary>, // parent
-        MySQLResultReader<'stmt>,                               // child
+        mysql::QueryResult<'stmt, 'stmt, 'stmt, P>, // parent
+        MySQLResultReader<'stmt, P>,                // child
     >,
 );
 
-impl<'stmt> ResultReader<'stmt> for MySQLQueryResult<'stmt> {
+impl<'stmt, P: Protocol> ResultReader<'stmt> for MySQLQueryResult<'stmt, P> {
     fn get_schema(&mut self) -> Result<arrow::datatypes::SchemaRef, ConnectorError> {
         Ok(self.0.with_mut(|x| x.schema.clone()))
     }
 }
 
-impl<'stmt> Iterator for MySQLQueryResult<'stmt> {
+impl<'stmt, P: Protocol> Iterator for MySQLQueryResult<'stmt, P> {
     type Item = Result<RecordBatch, ConnectorError>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -61,13 +132,14 @@ impl<'stmt> Iterator for MySQLQueryResult<'stmt> {
     }
 }
 
-struct MySQLResultReader<'stmt> {
-    result_set: mysql::ResultSet<'stmt, 'stmt, 'stmt, 'stmt, mysql::Binary>,
+struct MySQLResultReader<'stmt, P: Protocol> {
+    result_set: mysql::ResultSet<'stmt, 'stmt, 'stmt, 'stmt, P>,
     schema: SchemaRef,
 }
 
-impl<'s> util::RowsReader<'s> for MySQLResultReader<'s> {
-    type CellReader<'row> = MySQLCellReader
+impl<'s, P: Protocol> util::RowsReader<'s> for MySQLResultReader<'s, P> {
+    type CellReader<'row>
+        = MySQLCellReader
     where
         Self: 'row;
 
@@ -83,7 +155,8 @@ struct MySQLCellReader {
 }
 
 impl<'a> util::CellReader<'a> for MySQLCellReader {
-    type CellRef<'cell> = MySQLCellRef<'cell>
+    type CellRef<'cell>
+        = MySQLCellRef<'cell>
     where
         Self: 'cell;
 
@@ -145,6 +218,153 @@ impl_produce_ty!(
     )
 );
 
+macro_rules! impl_produce_value {
+    ($p: ty, ($({ $t: ty => $conversion_fn: expr },)+)) => {
+        $(
+            impl<'r> ProduceTy<'r, $t> for $p {
+                fn produce(self) -> Result<<$t as ArrowType>::Native, ConnectorError> {
+                    ProduceTy::<$t>::produce_opt(self)?.ok_or_else(err_null)
+                }
+                fn produce_opt(self) -> Result<Option<<$t as ArrowType>::Native>, ConnectorError> {
+                    match self.row.take::<mysql::Value, _>(self.cell).unwrap() {
+                        mysql::Value::NULL => Ok(None),
+                        value => $conversion_fn(value).map(Some),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_produce_value!(
+    MySQLCellRef<'r>,
+    (
+        { TimestampMicrosecondType => value_to_timestamp_micros },
+        { Date32Type => value_to_date32 },
+        { Time64MicrosecondType => value_to_time_micros },
+        { Decimal128Type => value_to_decimal128 },
+        { Decimal256Type => value_to_decimal256 },
+    )
+);
+
+fn err_null() -> ConnectorError {
+    ConnectorError::DataSchemaMismatch("NULL in non-nullable column".into())
+}
+
+fn err_unexpected(value: mysql::Value) -> ConnectorError {
+    ConnectorError::from(mysql::Error::FromValueError(value))
+}
+
+fn value_to_date(value: &mysql::Value) -> Option<NaiveDateTime> {
+    match *value {
+        mysql::Value::Date(year, month, day, hour, minute, second, micros) => {
+            NaiveDate::from_ymd_opt(year as i32, month as u32, day as u32)?.and_hms_micro_opt(
+                hour as u32,
+                minute as u32,
+                second as u32,
+                micros,
+            )
+        }
+
+        // text protocol
+        mysql::Value::Bytes(ref bytes) => {
+            let text = std::str::from_utf8(bytes).ok()?;
+            NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f")
+                .or_else(|_| NaiveDate::parse_from_str(text, "%Y-%m-%d").map(|d| d.into()))
+                .ok()
+        }
+        _ => None,
+    }
+}
+
+fn value_to_timestamp_micros(value: mysql::Value) -> Result<i64, ConnectorError> {
+    match value_to_date(&value) {
+        Some(date_time) => Ok(date_time.and_utc().timestamp_micros()),
+        None => Err(err_unexpected(value)),
+    }
+}
+
+fn value_to_date32(value: mysql::Value) -> Result<i32, ConnectorError> {
+    match value_to_date(&value) {
+        Some(date_time) => Ok(date_time.num_days_from_ce() - EPOCH_DAYS_FROM_CE),
+        None => Err(err_unexpected(value)),
+    }
+}
+
+fn value_to_time_micros(value: mysql::Value) -> Result<i64, ConnectorError> {
+    // MySQL TIME can be negative and larger than 24 hours, since it is used to represent
+    // both time of day and elapsed time.
+    let (is_negative, hours, minutes, seconds, micros) = match value {
+        mysql::Value::Time(is_negative, days, hours, minutes, seconds, micros) => (
+            is_negative,
+            days as i64 * 24 + hours as i64,
+            minutes as i64,
+            seconds as i64,
+            micros as i64,
+        ),
+
+        // text protocol
+        mysql::Value::Bytes(ref bytes) => match parse_time(bytes) {
+            Some(parts) => parts,
+            None => return Err(err_unexpected(value)),
+        },
+        _ => return Err(err_unexpected(value)),
+    };
+    let seconds = (hours * 60 + minutes) * 60 + seconds;
+    let micros = seconds * 1_000_000 + micros;
+    Ok(if is_negative { -micros } else { micros })
+}
+
+/// Parses textual TIME, formatted as `[-]H:MM:SS[.ffffff]`, with any number of hour digits.
+fn parse_time(bytes: &[u8]) -> Option<(bool, i64, i64, i64, i64)> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    let (is_negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+
+    let (text, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let mut parts = text.splitn(3, ':');
+    let hours = parts.next()?.parse().ok()?;
+    let minutes = parts.next()?.parse().ok()?;
+    let seconds = parts.next()?.parse().ok()?;
+
+    if fraction.len() > 6 {
+        return None;
+    }
+    let micros = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<i64>().ok()? * 10_i64.pow(6 - fraction.len() as u32)
+    };
+    Some((is_negative, hours, minutes, seconds, micros))
+}
+
+/// MySQL always formats decimals with all of the digits of the declared scale,
+/// so we can obtain the underlying integer by removing the decimal point.
+fn decimal_digits(value: mysql::Value) -> Result<String, ConnectorError> {
+    let mysql::Value::Bytes(bytes) = value else {
+        return Err(err_unexpected(value));
+    };
+    let mut digits = String::from_utf8(bytes)?;
+    digits.retain(|c| c != '.');
+    Ok(digits)
+}
+
+fn value_to_decimal128(value: mysql::Value) -> Result<i128, ConnectorError> {
+    let digits = decimal_digits(value)?;
+    digits
+        .parse()
+        .map_err(|_| ConnectorError::DataSchemaMismatch(format!("bad decimal encoding: {digits}")))
+}
+
+fn value_to_decimal256(value: mysql::Value) -> Result<i256, ConnectorError> {
+    let digits = decimal_digits(value)?;
+    i256::from_string(&digits).ok_or_else(|| {
+        ConnectorError::DataSchemaMismatch(format!("bad decimal encoding: {digits}"))
+    })
+}
+
 impl_produce_unsupported!(
     MySQLCellRef<'r>,
     (
@@ -152,13 +372,10 @@ impl_produce_unsupported!(
         Float16Type,
         TimestampSecondType,
         TimestampMillisecondType,
-        TimestampMicrosecondType,
         TimestampNanosecondType,
-        Date32Type,
         Date64Type,
         Time32SecondType,
         Time32MillisecondType,
-        Time64MicrosecondType,
         Time64NanosecondType,
         IntervalYearMonthType,
         IntervalDayTimeType,
@@ -170,7 +387,10 @@ impl_produce_unsupported!(
         LargeUtf8Type,
         LargeBinaryType,
         FixedSizeBinaryType,
-        Decimal128Type,
-        Decimal256Type,
+        ListType,
+        LargeListType,
+        FixedSizeListType,
+        StructType,
+        MapType,
     )
 );
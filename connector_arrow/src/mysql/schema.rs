@@ -1,17 +1,16 @@
 use std::sync::Arc;
 
 use arrow::datatypes::{DataType, Schema};
-use itertools::Itertools;
 use mysql::prelude::Queryable;
 
 use crate::{
     api::{Connector, SchemaEdit, SchemaGet},
-    mysql::MySQLConnection,
+    mysql::{MySQLConnection, MySQLProtocol},
     util::escape::escaped_ident_bt,
     ConnectorError, TableCreateError, TableDropError,
 };
 
-impl<C: Queryable> SchemaGet for super::MySQLConnection<C> {
+impl<C: Queryable, P: MySQLProtocol> SchemaGet for super::MySQLConnection<C, P> {
     fn table_list(&mut self) -> Result<Vec<String>, crate::ConnectorError> {
         let mut results = self.conn.exec_iter("SHOW TABLES;", ())?;
         let result = results.iter().ok_or(crate::ConnectorError::NoResultSets)?;
@@ -36,21 +35,20 @@ impl<C: Queryable> SchemaGet for super::MySQLConnection<C> {
         let fields = result
             .into_iter()
             .map(|r_row| {
-                r_row.map(|row| {
-                    let name = row.get::<String, _>(0).unwrap();
-                    let ty = row.get::<String, _>(1).unwrap();
-                    let nullable = row.get::<String, _>(2).unwrap() == "YES";
+                let row = r_row?;
+                let column = row.get::<String, _>(0).unwrap();
+                let ty = row.get::<String, _>(1).unwrap();
+                let nullable = row.get::<String, _>(2).unwrap() == "YES";
 
-                    super::types::create_field(name, &ty, nullable)
-                })
+                super::types::create_field(name, column, &ty, nullable)
             })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect::<Result<Vec<_>, ConnectorError>>()?;
 
         Ok(Arc::new(Schema::new(fields)))
     }
 }
 
-impl<C: Queryable> SchemaEdit for super::MySQLConnection<C> {
+impl<C: Queryable, P: MySQLProtocol> SchemaEdit for super::MySQLConnection<C, P> {
     fn table_create(
         &mut self,
         name: &str,
@@ -61,17 +59,24 @@ impl<C: Queryable> SchemaEdit for super::MySQLConnection<C> {
             .iter()
             .map(|field| {
                 let ty = MySQLConnection::<mysql::Conn>::type_arrow_into_db(field.data_type())
-                    .unwrap_or_else(|| {
-                        unimplemented!("cannot store arrow type {} in MySQL", field.data_type());
-                    });
+                    .ok_or_else(|| ConnectorError::IncompatibleSchema {
+                        table_name: name.to_string(),
+                        message: format!(
+                            "cannot store arrow type {} of column {} in MySQL",
+                            field.data_type(),
+                            field.name()
+                        ),
+                        hint: None,
+                    })?;
 
                 let is_nullable =
                     field.is_nullable() || matches!(field.data_type(), DataType::Null);
                 let not_null = if is_nullable { "" } else { " NOT NULL" };
 
                 let name = escaped_ident_bt(field.name());
-                format!("{name} {ty}{not_null}",)
+                Ok(format!("{name} {ty}{not_null}"))
             })
+            .collect::<Result<Vec<_>, ConnectorError>>()?
             .join(",");
 
         let ddl = format!("CREATE TABLE {} ({column_defs});", escaped_ident_bt(name));
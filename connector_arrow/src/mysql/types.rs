@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use arrow::datatypes::*;
@@ -10,36 +11,91 @@ use crate::ConnectorError;
 pub fn get_result_schema<'a, P: Protocol>(
     result: &mysql::ResultSet<'a, 'a, 'a, 'a, P>,
 ) -> Result<SchemaRef, ConnectorError> {
-    dbg!("get_result_schema");
-
     let mut fields = Vec::new();
     for column in result.columns().as_ref() {
         let is_unsigned = !(column.flags() & ColumnFlags::UNSIGNED_FLAG).is_empty();
         let is_not_null = !(column.flags() & ColumnFlags::NOT_NULL_FLAG).is_empty();
-        let is_blob = !(column.flags() & ColumnFlags::BLOB_FLAG).is_empty();
         let is_binary = !(column.flags() & ColumnFlags::BINARY_FLAG).is_empty();
 
-        dbg!(column.name_str());
-        dbg!(is_blob);
-        dbg!(is_binary);
+        let is_enum = !(column.flags() & ColumnFlags::ENUM_FLAG).is_empty();
+        let is_set = !(column.flags() & ColumnFlags::SET_FLAG).is_empty();
+
+        let db_ty = if is_enum {
+            "enum"
+        } else if is_set {
+            "set"
+        } else {
+            get_name_of_column_type(&column.column_type(), is_unsigned, is_binary)
+        };
+
+        let db_ty = if db_ty == "newdecimal" || db_ty == "decimal" {
+            // column length includes the sign and the decimal point
+            let scale = column.decimals() as u32;
+            let precision = column.column_length()
+                - if scale > 0 { 1 } else { 0 }
+                - if is_unsigned { 0 } else { 1 };
+            format!("decimal({precision},{scale})")
+        } else {
+            db_ty.to_string()
+        };
 
-        let db_ty = get_name_of_column_type(&column.column_type(), is_unsigned, is_binary);
-        dbg!(db_ty);
         fields.push(create_field(
+            &column.table_str(),
             column.name_str().to_string(),
-            db_ty,
+            &db_ty,
             !is_not_null,
-        ));
+        )?);
     }
 
     Ok(Arc::new(Schema::new(fields)))
 }
 
-pub fn create_field(name: String, db_ty: &str, nullable: bool) -> Field {
+pub fn create_field(
+    table_name: &str,
+    name: String,
+    db_ty: &str,
+    nullable: bool,
+) -> Result<Field, ConnectorError> {
     let data_type = super::MySQLConnection::<mysql::Conn>::type_db_into_arrow(db_ty);
-    let data_type = data_type.unwrap_or_else(|| todo!("db type: {db_ty}"));
+    let Some(data_type) = data_type else {
+        return Err(ConnectorError::IncompatibleSchema {
+            table_name: table_name.to_string(),
+            message: format!("cannot read MySQL type {db_ty} of column {name}"),
+            hint: None,
+        });
+    };
+
+    let mut metadata = HashMap::new();
+    if db_ty == "json" {
+        metadata.insert(
+            METADATA_EXTENSION_NAME.to_string(),
+            EXTENSION_JSON.to_string(),
+        );
+    }
+
+    Ok(Field::new(name, data_type, nullable).with_metadata(metadata))
+}
 
-    Field::new(name, data_type, nullable)
+/// Key of the metadata on [Field] that stores the name of Arrow extension type.
+const METADATA_EXTENSION_NAME: &str = "ARROW:extension:name";
+
+/// Canonical Arrow extension type for JSON-encoded strings.
+const EXTENSION_JSON: &str = "arrow.json";
+
+/// Parses `(precision,scale)` suffix of DECIMAL type.
+/// When omitted, MySQL defaults to precision of 10 and scale of 0.
+pub fn parse_decimal_params(params: Option<&str>) -> Option<(u8, i8)> {
+    let Some(params) = params else {
+        return Some((10, 0));
+    };
+    let params = params.strip_prefix('(')?;
+    let params = &params[0..params.find(')')?];
+
+    let (precision, scale) = match params.split_once(',') {
+        Some((p, s)) => (p.trim().parse().ok()?, s.trim().parse().ok()?),
+        None => (params.trim().parse().ok()?, 0),
+    };
+    Some((precision, scale))
 }
 
 fn get_name_of_column_type(col_ty: &ColumnType, unsigned: bool, binary: bool) -> &'static str {
@@ -1,8 +1,12 @@
+#[cfg(any(feature = "adbc", feature = "flight_sql_server"))]
+use arrow::array::{Array, AsArray, RecordBatch};
 use arrow::datatypes::*;
 use std::any::Any;
 
 use crate::api::ArrowValue;
-use crate::types::{FixedSizeBinaryType, NullType};
+use crate::types::{
+    FixedSizeBinaryType, FixedSizeListType, LargeListType, ListType, MapType, NullType, StructType,
+};
 use crate::util::transport::{Produce, ProduceTy};
 use crate::{impl_produce_unsupported, ConnectorError};
 
@@ -124,4 +128,85 @@ impl_arrow_value_tuple!(String, (LargeUtf8Type,));
 
 impl_arrow_value_tuple!(Vec<u8>, (LargeBinaryType, FixedSizeBinaryType,));
 
-impl_produce_unsupported!(&'r dyn ArrowValue, (NullType, Float16Type,));
+impl_produce_unsupported!(
+    &'r dyn ArrowValue,
+    (
+        NullType,
+        Float16Type,
+        ListType,
+        LargeListType,
+        FixedSizeListType,
+        StructType,
+        MapType,
+    )
+);
+
+/// Values of a row of a batch of query parameters.
+#[cfg(any(feature = "adbc", feature = "flight_sql_server"))]
+pub(crate) fn row_values(
+    batch: &RecordBatch,
+    row: usize,
+) -> Result<Vec<Box<dyn ArrowValue>>, ConnectorError> {
+    batch
+        .columns()
+        .iter()
+        .map(|array| value(array.as_ref(), row))
+        .collect()
+}
+
+#[cfg(any(feature = "adbc", feature = "flight_sql_server"))]
+fn value(array: &dyn Array, row: usize) -> Result<Box<dyn ArrowValue>, ConnectorError> {
+    if array.is_null(row) {
+        return Err(params_not_supported("null query parameters".into()));
+    }
+
+    let ty = array.data_type().clone();
+    Ok(match &ty {
+        DataType::Boolean => Box::new(array.as_boolean().value(row)),
+        DataType::Int8 => Box::new(array.as_primitive::<Int8Type>().value(row)),
+        DataType::Int16 => Box::new(array.as_primitive::<Int16Type>().value(row)),
+        DataType::Int32 => Box::new(array.as_primitive::<Int32Type>().value(row)),
+        DataType::Int64 => Box::new(array.as_primitive::<Int64Type>().value(row)),
+        DataType::UInt8 => Box::new(array.as_primitive::<UInt8Type>().value(row)),
+        DataType::UInt16 => Box::new(array.as_primitive::<UInt16Type>().value(row)),
+        DataType::UInt32 => Box::new(array.as_primitive::<UInt32Type>().value(row)),
+        DataType::UInt64 => Box::new(array.as_primitive::<UInt64Type>().value(row)),
+        DataType::Float32 => Box::new(array.as_primitive::<Float32Type>().value(row)),
+        DataType::Float64 => Box::new(array.as_primitive::<Float64Type>().value(row)),
+        DataType::Utf8 => Box::new(array.as_string::<i32>().value(row).to_string()),
+        DataType::LargeUtf8 => Box::new((ty, array.as_string::<i64>().value(row).to_string())),
+        DataType::Binary => Box::new(array.as_binary::<i32>().value(row).to_vec()),
+        DataType::LargeBinary => Box::new((ty, array.as_binary::<i64>().value(row).to_vec())),
+        DataType::Date32 => Box::new((ty, array.as_primitive::<Date32Type>().value(row))),
+        DataType::Date64 => Box::new((ty, array.as_primitive::<Date64Type>().value(row))),
+        DataType::Timestamp(unit, _) => {
+            let value = match unit {
+                TimeUnit::Second => array.as_primitive::<TimestampSecondType>().value(row),
+                TimeUnit::Millisecond => {
+                    array.as_primitive::<TimestampMillisecondType>().value(row)
+                }
+                TimeUnit::Microsecond => {
+                    array.as_primitive::<TimestampMicrosecondType>().value(row)
+                }
+                TimeUnit::Nanosecond => array.as_primitive::<TimestampNanosecondType>().value(row),
+            };
+            Box::new((ty, value))
+        }
+        DataType::Decimal128(_, _) => {
+            Box::new((ty, array.as_primitive::<Decimal128Type>().value(row)))
+        }
+        _ => {
+            return Err(params_not_supported(
+                format!("query parameters of type {ty}").into(),
+            ))
+        }
+    })
+}
+
+#[cfg(any(feature = "adbc", feature = "flight_sql_server"))]
+fn params_not_supported(feature: std::borrow::Cow<'static, str>) -> ConnectorError {
+    ConnectorError::NotSupported {
+        connector_name: "connector_arrow",
+        feature,
+    }
+}
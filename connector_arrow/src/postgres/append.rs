@@ -1,129 +1,110 @@
-use std::sync::Arc;
+use std::convert::TryFrom;
+use std::ops::Range;
 
+use arrow::array::{Array, ArrayRef, AsArray, StructArray};
 use arrow::datatypes::*;
 use arrow::record_batch::RecordBatch;
-use bytes::BytesMut;
+use bytes::{BufMut, BytesMut};
 use itertools::{zip_eq, Itertools};
 use postgres::binary_copy::BinaryCopyInWriter;
-use postgres::types::{to_sql_checked, IsNull, ToSql};
-use postgres::{Client, CopyInWriter};
+use postgres::types::{to_sql_checked, IsNull, Kind, ToSql, Type};
+use postgres::Client;
 use postgres_protocol::types as postgres_proto;
+use postgres_protocol::Oid;
 
 use crate::api::Append;
-use crate::types::{FixedSizeBinaryType, NullType};
+use crate::types::{
+    FixedSizeBinaryType, FixedSizeListType, LargeListType, ListType, MapType, NullType, StructType,
+};
 use crate::util::escape::escaped_ident;
 use crate::util::transport::{Consume, ConsumeTy};
-use crate::util::ArrayCellRef;
+use crate::util::{ArrayCellRef, CellTransporter};
 use crate::{impl_consume_unsupported, ConnectorError};
 
 use super::PostgresError;
 
 pub struct PostgresAppender<'c> {
-    writer: Writer<'c>,
+    writer: BinaryCopyInWriter<'c>,
+
+    /// Types of the columns of the table, which are needed to encode arrays and composite types.
+    types: Vec<Type>,
 }
 
 impl<'conn> PostgresAppender<'conn> {
     pub fn new(client: &'conn mut Client, table_name: &str) -> Result<Self, ConnectorError> {
+        let query = format!("SELECT * FROM {}", escaped_ident(table_name));
+        let stmt = client.prepare(&query).map_err(PostgresError::Postgres)?;
+        let types = stmt
+            .columns()
+            .iter()
+            .map(|c| c.type_().clone())
+            .collect_vec();
+
         let query = format!("COPY BINARY {} FROM stdin", escaped_ident(table_name));
         let writer = client.copy_in(&query).map_err(PostgresError::Postgres)?;
-        let writer = Writer::Uninitialized(writer);
-        Ok(Self { writer })
-    }
-}
-
-enum Writer<'c> {
-    Uninitialized(CopyInWriter<'c>),
-    Invalid,
-    Initialized { writer: BinaryCopyInWriter<'c> },
-}
-
-impl<'c> Writer<'c> {
-    fn as_binary(
-        &mut self,
-        schema: SchemaRef,
-    ) -> Result<&mut BinaryCopyInWriter<'c>, ConnectorError> {
-        if let Writer::Uninitialized(_) = self {
-            // replace plain writer with a new binary one
-            let Writer::Uninitialized(w) = std::mem::replace(self, Writer::Invalid) else {
-                unreachable!();
-            };
-
-            // types don't really matter
-            // they are used only for client-side checking of match between the
-            // declared type and passed value.
-            // Because our ToSql::accepts returns true
-            let types = vec![postgres::types::Type::VOID; schema.fields().len()];
-
-            *self = Writer::Initialized {
-                writer: BinaryCopyInWriter::new(w, &types),
-            }
-        }
-
-        // return binary writer
-        let Writer::Initialized { writer } = self else {
-            unreachable!();
-        };
-        Ok(writer)
-    }
-
-    fn finish(mut self) -> Result<u64, ConnectorError> {
-        let schema = Arc::new(Schema::new(vec![] as Vec<Field>));
-        self.as_binary(schema)?;
-        match self {
-            Writer::Initialized { writer: w, .. } => {
-                Ok(w.finish().map_err(PostgresError::Postgres)?)
-            }
-            Writer::Uninitialized(_) | Writer::Invalid => unreachable!(),
-        }
+        let writer = BinaryCopyInWriter::new(writer, &types);
+        Ok(Self { writer, types })
     }
 }
 
 impl<'conn> Append<'conn> for PostgresAppender<'conn> {
     fn append(&mut self, batch: RecordBatch) -> Result<(), ConnectorError> {
-        let writer = self.writer.as_binary(batch.schema())?;
+        if batch.num_columns() != self.types.len() {
+            return Err(ConnectorError::DataSchemaMismatch(format!(
+                "cannot append {} columns into a table of {} columns",
+                batch.num_columns(),
+                self.types.len()
+            )));
+        }
 
         let schema = batch.schema();
-        let mut row = zip_eq(batch.columns(), schema.fields())
-            .map(|(array, field)| ArrayCellRef {
-                array,
-                field,
-                row_number: 0,
-            })
-            .collect_vec();
+        let encoders: Vec<_> = zip_eq(schema.fields(), batch.columns())
+            .zip(&self.types)
+            .map(|((field, array), ty)| Encoder::new(field, array, ty))
+            .try_collect()?;
 
         for row_number in 0..batch.num_rows() {
-            for cell in &mut row {
-                cell.row_number = row_number;
-            }
-
-            writer.write_raw(&row).map_err(PostgresError::Postgres)?;
+            let row = encoders.iter().map(|encoder| CopyCell {
+                encoder,
+                row_number,
+            });
+            self.writer
+                .write_raw(row)
+                .map_err(PostgresError::Postgres)?;
         }
         Ok(())
     }
 
     fn finish(self) -> Result<(), ConnectorError> {
-        self.writer.finish()?;
+        self.writer.finish().map_err(PostgresError::Postgres)?;
         Ok(())
     }
 }
 
-impl<'a> ToSql for ArrayCellRef<'a> {
+/// A cell of the appended batch.
+#[derive(Debug)]
+struct CopyCell<'a> {
+    encoder: &'a Encoder<'a>,
+    row_number: usize,
+}
+
+impl<'a> ToSql for CopyCell<'a> {
     fn to_sql(
         &self,
-        _ty: &postgres::types::Type,
+        _ty: &Type,
         out: &mut BytesMut,
     ) -> Result<IsNull, Box<dyn std::error::Error + Sync + Send>>
     where
         Self: Sized,
     {
-        if self.array.is_null(self.row_number) || matches!(self.field.data_type(), DataType::Null) {
+        if self.encoder.is_null(self.row_number) {
             return Ok(IsNull::Yes);
         }
-        crate::util::transport::transport(self.field, self, out)?;
+        self.encoder.encode(self.row_number, out)?;
         Ok(IsNull::No)
     }
 
-    fn accepts(_: &postgres::types::Type) -> bool
+    fn accepts(_: &Type) -> bool
     where
         Self: Sized,
     {
@@ -134,6 +115,200 @@ impl<'a> ToSql for ArrayCellRef<'a> {
     to_sql_checked!();
 }
 
+/// Encodes values of an array into the binary format of a PostgreSQL type.
+/// Resolved once per column of each appended batch.
+#[derive(Debug)]
+enum Encoder<'a> {
+    /// Values that are transported into the buffer with the transport function of their field.
+    Scalar {
+        array: &'a ArrayRef,
+        field: &'a Field,
+        transport: CellTransporter<BytesMut>,
+    },
+    /// Lists and maps, which are encoded as arrays of their elements.
+    Array {
+        array: &'a dyn Array,
+        element_oid: Oid,
+        element: Box<Encoder<'a>>,
+    },
+    /// Structs and entries of maps, which are encoded as composite values.
+    Composite {
+        array: &'a dyn Array,
+        attributes: Vec<(Oid, Encoder<'a>)>,
+    },
+}
+
+impl<'a> Encoder<'a> {
+    fn new(field: &'a Field, array: &'a ArrayRef, ty: &Type) -> Result<Self, ConnectorError> {
+        let element = match ty.kind() {
+            Kind::Array(element) => Some(element),
+            _ => None,
+        };
+        Ok(match (field.data_type(), element) {
+            (DataType::List(item), Some(element)) => {
+                let values = array.as_list::<i32>().values();
+                Self::array(array, Self::new(item, values, element)?, element)
+            }
+            (DataType::LargeList(item), Some(element)) => {
+                let values = array.as_list::<i64>().values();
+                Self::array(array, Self::new(item, values, element)?, element)
+            }
+            (DataType::FixedSizeList(item, _), Some(element)) => {
+                let values = array.as_fixed_size_list().values();
+                Self::array(array, Self::new(item, values, element)?, element)
+            }
+            (DataType::Map(_, _), Some(element)) => {
+                let entries = array.as_map().entries();
+                Self::array(array, Self::composite(entries, element)?, element)
+            }
+            (DataType::Struct(_), _) => Self::composite(array.as_struct(), ty)?,
+            (
+                DataType::List(_)
+                | DataType::LargeList(_)
+                | DataType::FixedSizeList(_, _)
+                | DataType::Map(_, _),
+                None,
+            ) => return Err(mismatch(field.data_type(), ty)),
+            _ => Encoder::Scalar {
+                array,
+                field,
+                transport: ArrayCellRef::transporter(field)?,
+            },
+        })
+    }
+
+    fn array(array: &'a ArrayRef, element: Encoder<'a>, element_ty: &Type) -> Self {
+        Encoder::Array {
+            array: array.as_ref(),
+            element_oid: element_ty.oid(),
+            element: Box::new(element),
+        }
+    }
+
+    fn composite(array: &'a StructArray, ty: &Type) -> Result<Self, ConnectorError> {
+        let attributes = match ty.kind() {
+            Kind::Composite(attributes) if attributes.len() == array.num_columns() => attributes,
+            _ => return Err(mismatch(array.data_type(), ty)),
+        };
+        let attributes = zip_eq(array.fields(), array.columns())
+            .zip(attributes)
+            .map(|((field, column), attribute)| {
+                let encoder = Self::new(field, column, attribute.type_())?;
+                Ok((attribute.type_().oid(), encoder))
+            })
+            .collect::<Result<_, ConnectorError>>()?;
+        Ok(Encoder::Composite { array, attributes })
+    }
+
+    fn is_null(&self, row_number: usize) -> bool {
+        match self {
+            Encoder::Scalar { array, field, .. } => {
+                array.is_null(row_number) || matches!(field.data_type(), DataType::Null)
+            }
+            Encoder::Array { array, .. } | Encoder::Composite { array, .. } => {
+                array.is_null(row_number)
+            }
+        }
+    }
+
+    /// Encodes a value that is not null.
+    fn encode(
+        &self,
+        row_number: usize,
+        out: &mut BytesMut,
+    ) -> Result<(), Box<dyn std::error::Error + Sync + Send>> {
+        match self {
+            Encoder::Scalar {
+                array,
+                field,
+                transport,
+            } => {
+                let cell = ArrayCellRef {
+                    array,
+                    field,
+                    row_number,
+                };
+                transport(&cell, out)?;
+            }
+            Encoder::Array {
+                array,
+                element_oid,
+                element,
+            } => {
+                let elements = element_range(*array, row_number);
+
+                // empty arrays have no dimensions
+                let dimension = postgres_proto::ArrayDimension {
+                    len: i32::try_from(elements.len())?,
+                    lower_bound: 1,
+                };
+                let dimensions = Some(dimension).filter(|_| !elements.is_empty());
+
+                postgres_proto::array_to_sql(
+                    dimensions,
+                    *element_oid,
+                    elements,
+                    |i, out| {
+                        if element.is_null(i) {
+                            return Ok(postgres_protocol::IsNull::Yes);
+                        }
+                        element.encode(i, out)?;
+                        Ok(postgres_protocol::IsNull::No)
+                    },
+                    out,
+                )?;
+            }
+            Encoder::Composite { attributes, .. } => {
+                out.put_i32(i32::try_from(attributes.len())?);
+                for (oid, attribute) in attributes {
+                    out.put_u32(*oid);
+                    if attribute.is_null(row_number) {
+                        out.put_i32(-1);
+                        continue;
+                    }
+
+                    // length is written after the value
+                    let len_idx = out.len();
+                    out.put_i32(0);
+                    attribute.encode(row_number, out)?;
+                    let len = i32::try_from(out.len() - len_idx - 4)?;
+                    out[len_idx..len_idx + 4].copy_from_slice(&len.to_be_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Range of the elements of a list or a map within its values.
+fn element_range(array: &dyn Array, row_number: usize) -> Range<usize> {
+    match array.data_type() {
+        DataType::List(_) => {
+            let offsets = array.as_list::<i32>().value_offsets();
+            offsets[row_number] as usize..offsets[row_number + 1] as usize
+        }
+        DataType::LargeList(_) => {
+            let offsets = array.as_list::<i64>().value_offsets();
+            offsets[row_number] as usize..offsets[row_number + 1] as usize
+        }
+        DataType::FixedSizeList(_, size) => {
+            let start = array.as_fixed_size_list().value_offset(row_number) as usize;
+            start..start + *size as usize
+        }
+        DataType::Map(_, _) => {
+            let offsets = array.as_map().value_offsets();
+            offsets[row_number] as usize..offsets[row_number + 1] as usize
+        }
+        _ => unreachable!("not a list: {}", array.data_type()),
+    }
+}
+
+fn mismatch(data_type: &DataType, ty: &Type) -> ConnectorError {
+    ConnectorError::DataSchemaMismatch(format!(
+        "cannot append {data_type} into a column of type {ty}"
+    ))
+}
+
 impl Consume for BytesMut {}
 
 macro_rules! impl_consume_ty {
@@ -147,11 +322,14 @@ macro_rules! impl_consume_ty {
                 &mut self,
                 _ty: &DataType,
                 value: <$ArrTy as crate::types::ArrowType>::Native,
-            ) {
+            ) -> Result<(), ConnectorError> {
                 $to_sql(($conversion)(value), self);
+                Ok(())
             }
 
-            fn consume_null(&mut self) {}
+            fn consume_null(&mut self) -> Result<(), ConnectorError> {
+                Ok(())
+            }
         }
     };
 }
@@ -163,19 +341,26 @@ macro_rules! impl_consume_ref_ty {
                 &mut self,
                 _ty: &DataType,
                 value: <$ArrTy as crate::types::ArrowType>::Native,
-            ) {
+            ) -> Result<(), ConnectorError> {
                 $to_sql(&value, self);
+                Ok(())
             }
 
-            fn consume_null(&mut self) {}
+            fn consume_null(&mut self) -> Result<(), ConnectorError> {
+                Ok(())
+            }
         }
     };
 }
 
 impl ConsumeTy<NullType> for BytesMut {
-    fn consume(&mut self, _ty: &DataType, _: ()) {}
+    fn consume(&mut self, _ty: &DataType, _: ()) -> Result<(), ConnectorError> {
+        Ok(())
+    }
 
-    fn consume_null(&mut self) {}
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
 }
 
 impl_consume_ty!(BooleanType, postgres_proto::bool_to_sql);
@@ -215,36 +400,45 @@ impl_consume_ref_ty!(Utf8Type, postgres_proto::text_to_sql);
 impl_consume_ref_ty!(LargeUtf8Type, postgres_proto::text_to_sql);
 
 impl ConsumeTy<UInt64Type> for BytesMut {
-    fn consume(&mut self, _ty: &DataType, value: u64) {
+    fn consume(&mut self, _ty: &DataType, value: u64) -> Result<(), ConnectorError> {
         // this is inefficient, we'd need a special u64_to_sql function
-        super::decimal::i128_to_sql(value as i128, 0, self)
+        super::decimal::i128_to_sql(value as i128, 0, self);
+        Ok(())
     }
 
-    fn consume_null(&mut self) {}
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
 }
 
 impl ConsumeTy<Decimal128Type> for BytesMut {
-    fn consume(&mut self, ty: &DataType, value: i128) {
+    fn consume(&mut self, ty: &DataType, value: i128) -> Result<(), ConnectorError> {
         let DataType::Decimal128(_, scale) = ty else {
             unreachable!()
         };
 
-        super::decimal::i128_to_sql(value, *scale, self)
+        super::decimal::i128_to_sql(value, *scale, self);
+        Ok(())
     }
 
-    fn consume_null(&mut self) {}
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
 }
 
 impl ConsumeTy<Decimal256Type> for BytesMut {
-    fn consume(&mut self, ty: &DataType, value: i256) {
+    fn consume(&mut self, ty: &DataType, value: i256) -> Result<(), ConnectorError> {
         let DataType::Decimal256(_, scale) = ty else {
             unreachable!()
         };
 
-        super::decimal::i256_to_sql(value, *scale, self)
+        super::decimal::i256_to_sql(value, *scale, self);
+        Ok(())
     }
 
-    fn consume_null(&mut self) {}
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        Ok(())
+    }
 }
 
 impl_consume_unsupported!(
@@ -253,5 +447,10 @@ impl_consume_unsupported!(
         IntervalYearMonthType,
         IntervalDayTimeType,
         IntervalMonthDayNanoType,
+        ListType,
+        LargeListType,
+        FixedSizeListType,
+        StructType,
+        MapType,
     )
 );
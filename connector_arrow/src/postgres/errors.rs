@@ -0,0 +1,54 @@
+use postgres::error::{ErrorPosition, SqlState};
+
+use super::PostgresError;
+use crate::{DbErrorDetails, DbErrorKind};
+
+pub(crate) fn error_details(error: &PostgresError) -> Option<DbErrorDetails> {
+    let error = match error {
+        PostgresError::Postgres(e) => e,
+        PostgresError::IO(_) => {
+            return Some(DbErrorDetails::new(DbErrorKind::ConnectionLost, None));
+        }
+        _ => return None,
+    };
+
+    if error.is_closed() {
+        return Some(DbErrorDetails::new(DbErrorKind::ConnectionLost, None));
+    }
+
+    let db_error = error.as_db_error()?;
+    let code = db_error.code();
+    let mut details = DbErrorDetails::new(classify(code), Some(code.code().to_string()));
+    details.constraint = db_error.constraint().map(str::to_string);
+    details.column = db_error.column().map(str::to_string);
+    details.position = match db_error.position() {
+        Some(ErrorPosition::Original(position)) => Some(*position),
+        // position within a query that was generated internally, by a function
+        Some(ErrorPosition::Internal { .. }) | None => None,
+    };
+    Some(details)
+}
+
+/// See: https://www.postgresql.org/docs/current/errcodes-appendix.html
+fn classify(code: &SqlState) -> DbErrorKind {
+    if *code == SqlState::T_R_SERIALIZATION_FAILURE || *code == SqlState::T_R_DEADLOCK_DETECTED {
+        return DbErrorKind::SerializationFailure;
+    }
+    if *code == SqlState::QUERY_CANCELED || *code == SqlState::LOCK_NOT_AVAILABLE {
+        return DbErrorKind::Timeout;
+    }
+    if *code == SqlState::ADMIN_SHUTDOWN
+        || *code == SqlState::CRASH_SHUTDOWN
+        || *code == SqlState::CANNOT_CONNECT_NOW
+    {
+        return DbErrorKind::ConnectionLost;
+    }
+
+    // classes of errors are determined by the first two characters
+    match &code.code()[0..2] {
+        "08" => DbErrorKind::ConnectionLost,
+        "23" => DbErrorKind::ConstraintViolation,
+        "42" => DbErrorKind::Syntax,
+        _ => DbErrorKind::Other,
+    }
+}
@@ -14,18 +14,23 @@
 
 mod append;
 mod decimal;
+mod errors;
 mod protocol_extended;
 mod protocol_simple;
 mod schema;
 mod types;
 
-use arrow::datatypes::{DataType, IntervalUnit, TimeUnit};
+pub(crate) use errors::error_details;
+
+use arrow::datatypes::{DataType, Field, IntervalUnit, TimeUnit};
 use postgres::Client;
 use std::marker::PhantomData;
+use std::sync::Arc;
 use thiserror::Error;
 
 use crate::api::{Connector, Statement};
 use crate::errors::ConnectorError;
+use crate::util::ReaderOptions;
 
 /// Connection to PostgreSQL that implements [Connection], [crate::api::SchemaGet] and [crate::api::SchemaEdit].
 ///
@@ -34,6 +39,7 @@ use crate::errors::ConnectorError;
 /// - [ProtocolSimple]
 pub struct PostgresConnection<Protocol> {
     client: Client,
+    reader_options: ReaderOptions,
     _protocol: PhantomData<Protocol>,
 }
 
@@ -41,10 +47,16 @@ impl<Protocol> PostgresConnection<Protocol> {
     pub fn new(client: Client) -> Self {
         PostgresConnection {
             client,
+            reader_options: ReaderOptions::default(),
             _protocol: PhantomData,
         }
     }
 
+    /// Sets how query results are read into arrow.
+    pub fn set_reader_options(&mut self, options: ReaderOptions) {
+        self.reader_options = options;
+    }
+
     pub fn unwrap(self) -> Client {
         self.client
     }
@@ -99,6 +111,7 @@ where
             client: &mut self.client,
             query: query.to_string(),
             stmt,
+            reader_options: &self.reader_options,
             _protocol: &PhantomData,
         })
     }
@@ -132,6 +145,10 @@ where
 
             "text" | "varchar" | "char" | "bpchar" => DataType::Utf8,
 
+            _ if ty.ends_with("[]") => {
+                let element = Self::type_db_into_arrow(ty.trim_end_matches("[]"))?;
+                DataType::List(Arc::new(Field::new("item", element, true)))
+            }
             _ if ty.starts_with("bit") => DataType::Binary,
             _ if ty.starts_with("varchar") | ty.starts_with("char") | ty.starts_with("bpchar") => {
                 DataType::Utf8
@@ -176,24 +193,37 @@ where
                 DataType::Duration(_) => "bigint",
                 DataType::Interval(_) => return None,
 
-                DataType::Utf8 | DataType::LargeUtf8 => "text",
+                DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => "text",
+                // see crate::util::dictionary_encode_schema
+                DataType::Dictionary(_, value)
+                    if matches!(**value, DataType::Utf8 | DataType::LargeUtf8) =>
+                {
+                    return Self::type_arrow_into_db(value)
+                }
 
-                DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => "bytea",
+                DataType::Binary
+                | DataType::LargeBinary
+                | DataType::BinaryView
+                | DataType::FixedSizeBinary(_) => "bytea",
 
                 DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
                     return Some(format!("decimal({precision}, {scale})"))
                 }
 
-                DataType::List(_)
-                | DataType::FixedSizeList(_, _)
-                | DataType::LargeList(_)
-                | DataType::Struct(_)
+                // arrays cannot contain arrays
+                DataType::List(field)
+                | DataType::LargeList(field)
+                | DataType::FixedSizeList(field, _) => {
+                    let element = Self::type_arrow_into_db(field.data_type())?;
+                    return (!element.ends_with("[]")).then(|| format!("{element}[]"));
+                }
+
+                // composite types are created by table_create
+                DataType::Struct(_)
                 | DataType::Union(_, _)
                 | DataType::Dictionary(_, _)
                 | DataType::Map(_, _)
                 | DataType::RunEndEncoded(_, _)
-                | DataType::BinaryView
-                | DataType::Utf8View
                 | DataType::ListView(_)
                 | DataType::LargeListView(_) => return None,
             }
@@ -206,5 +236,6 @@ pub struct PostgresStatement<'conn, P> {
     client: &'conn mut Client,
     query: String,
     stmt: postgres::Statement,
+    reader_options: &'conn ReaderOptions,
     _protocol: &'conn PhantomData<P>,
 }
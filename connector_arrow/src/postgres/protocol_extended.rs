@@ -1,15 +1,21 @@
+use std::convert::TryInto;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, StructArray};
 use arrow::datatypes::*;
 use arrow::record_batch::RecordBatch;
 use bytes::BytesMut;
 use itertools::Itertools;
 use postgres::fallible_iterator::FallibleIterator;
-use postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+use postgres::types::{to_sql_checked, FromSql, IsNull, Kind, ToSql, Type};
 use postgres::{Row, RowIter};
 
 use crate::api::{ArrowValue, ResultReader, Statement};
-use crate::types::{ArrowType, FixedSizeBinaryType};
-use crate::util::transport;
+use crate::types::{
+    ArrowType, FixedSizeBinaryType, FixedSizeListType, LargeListType, ListType, MapType, StructType,
+};
 use crate::util::CellReader;
+use crate::util::transport;
 use crate::{errors::ConnectorError, util::RowsReader};
 
 use super::{types, PostgresError, PostgresStatement, ProtocolExtended};
@@ -23,6 +29,7 @@ impl<'conn> Statement<'conn> for PostgresStatement<'conn, ProtocolExtended> {
     {
         let stmt = &self.stmt;
         let schema = types::pg_stmt_to_arrow(stmt)?;
+        let schema = self.reader_options.apply(schema);
 
         // prepare params
         let params = params
@@ -90,7 +97,7 @@ struct PostgresCellReader {
 }
 
 impl<'row> CellReader<'row> for PostgresCellReader {
-    type CellRef<'cell> = CellRef<'cell> where Self: 'cell;
+    type CellRef<'cell> = RawCell<'cell> where Self: 'cell;
 
     fn next_cell(&mut self) -> Option<Self::CellRef<'_>> {
         if self.next_col >= self.row.columns().len() {
@@ -98,24 +105,46 @@ impl<'row> CellReader<'row> for PostgresCellReader {
         }
         let col = self.next_col;
         self.next_col += 1;
-        Some((&self.row, col))
+        Some(RawCell {
+            ty: self.row.columns()[col].type_(),
+            raw: self.row.get::<_, Option<Raw>>(col).map(|r| r.0),
+        })
     }
 }
 
-type CellRef<'a> = (&'a Row, usize);
+/// A value in the binary format, along with its type.
+#[derive(Debug, Clone, Copy)]
+struct RawCell<'a> {
+    ty: &'a Type,
+    raw: Option<&'a [u8]>,
+}
 
-impl<'c> transport::Produce<'c> for CellRef<'c> {}
+impl<'a> RawCell<'a> {
+    fn get<T: FromSql<'a>>(self) -> Result<T, ConnectorError> {
+        if !T::accepts(self.ty) {
+            return Err(ConnectorError::DataSchemaMismatch(format!(
+                "cannot read {} as {}",
+                self.ty,
+                std::any::type_name::<T>()
+            )));
+        }
+        T::from_sql_nullable(self.ty, self.raw)
+            .map_err(|e| ConnectorError::DataSchemaMismatch(e.to_string()))
+    }
+}
+
+impl<'c> transport::Produce<'c> for RawCell<'c> {}
 
 macro_rules! impl_produce {
     ($t: ty, $native: ty, $conversion_fn: expr) => {
-        impl<'c> transport::ProduceTy<'c, $t> for CellRef<'c> {
+        impl<'c> transport::ProduceTy<'c, $t> for RawCell<'c> {
             fn produce(self) -> Result<<$t as ArrowType>::Native, ConnectorError> {
-                let value = self.0.get::<_, $native>(self.1);
+                let value = self.get::<$native>()?;
                 $conversion_fn(value)
             }
 
             fn produce_opt(self) -> Result<Option<<$t as ArrowType>::Native>, ConnectorError> {
-                let value = self.0.get::<_, Option<$native>>(self.1);
+                let value = self.get::<Option<$native>>()?;
                 value.map($conversion_fn).transpose()
             }
         }
@@ -146,8 +175,27 @@ impl_produce!(
     IntervalMonthDayMicros::into_arrow
 );
 
+macro_rules! impl_produce_nested {
+    ($($t: ty,)+) => {
+        $(
+            impl<'c> transport::ProduceTy<'c, $t> for RawCell<'c> {
+                fn produce(self) -> Result<ArrayRef, ConnectorError> {
+                    decode_nested(self.ty, self.get::<Raw>()?.0)
+                }
+
+                fn produce_opt(self) -> Result<Option<ArrayRef>, ConnectorError> {
+                    let raw = self.get::<Option<Raw>>()?;
+                    raw.map(|r| decode_nested(self.ty, r.0)).transpose()
+                }
+            }
+        )+
+    };
+}
+
+impl_produce_nested!(ListType, StructType, MapType,);
+
 crate::impl_produce_unsupported!(
-    CellRef<'r>,
+    RawCell<'r>,
     (
         UInt8Type,
         UInt16Type,
@@ -170,9 +218,154 @@ crate::impl_produce_unsupported!(
         FixedSizeBinaryType,
         Decimal128Type,
         Decimal256Type,
+        LargeListType,
+        FixedSizeListType,
     )
 );
 
+/// Decodes an array or a composite value into an arrow array, as described by [crate::types::ListType],
+/// [crate::types::StructType] and [crate::types::MapType].
+fn decode_nested(ty: &Type, raw: &[u8]) -> Result<ArrayRef, ConnectorError> {
+    let field = types::pg_field_to_arrow(String::new(), ty, true);
+    match (ty.kind(), field.data_type()) {
+        (Kind::Array(element), DataType::List(item)) => {
+            let rows = array_elements(raw)?
+                .into_iter()
+                .map(|raw| vec![RawCell { ty: element, raw }])
+                .collect();
+            let batch = collect_cells(Fields::from(vec![item.clone()]), rows)?;
+            Ok(batch.column(0).clone())
+        }
+        (Kind::Array(element), DataType::Map(entries, _)) => {
+            let (Kind::Composite(attrs), DataType::Struct(fields)) =
+                (element.kind(), entries.data_type())
+            else {
+                unreachable!()
+            };
+            let rows = array_elements(raw)?
+                .into_iter()
+                .map(|raw| {
+                    let raw = raw.ok_or_else(|| {
+                        ConnectorError::DataSchemaMismatch("map entries cannot be null".into())
+                    })?;
+                    composite_attributes(attrs, raw)
+                })
+                .try_collect()?;
+            let batch = collect_cells(fields.clone(), rows)?;
+            Ok(Arc::new(StructArray::from(batch)))
+        }
+        (Kind::Composite(attrs), DataType::Struct(fields)) => {
+            let rows = vec![composite_attributes(attrs, raw)?];
+            let batch = collect_cells(fields.clone(), rows)?;
+            Ok(Arc::new(StructArray::from(batch)))
+        }
+        _ => Err(ConnectorError::DataSchemaMismatch(format!(
+            "cannot read {} as {}",
+            ty,
+            field.data_type()
+        ))),
+    }
+}
+
+/// Converts rows of cells into a batch.
+fn collect_cells(fields: Fields, rows: Vec<Vec<RawCell>>) -> Result<RecordBatch, ConnectorError> {
+    let schema = Arc::new(Schema::new(fields));
+    let row_count = rows.len();
+    let mut reader = RawCellRows(rows.into_iter());
+    let batch = crate::util::next_batch_from_rows(&schema, &mut reader, row_count)?;
+    Ok(batch.unwrap_or_else(|| RecordBatch::new_empty(schema)))
+}
+
+struct RawCellRows<'a>(std::vec::IntoIter<Vec<RawCell<'a>>>);
+
+impl<'a> RowsReader<'a> for RawCellRows<'a> {
+    type CellReader<'row> = std::vec::IntoIter<RawCell<'a>> where Self: 'row;
+
+    fn next_row(&mut self) -> Result<Option<Self::CellReader<'_>>, ConnectorError> {
+        Ok(self.0.next().map(Vec::into_iter))
+    }
+}
+
+impl<'row, 'a> CellReader<'row> for std::vec::IntoIter<RawCell<'a>> {
+    type CellRef<'cell> = RawCell<'cell> where Self: 'cell;
+
+    fn next_cell(&mut self) -> Option<Self::CellRef<'_>> {
+        self.next()
+    }
+}
+
+fn array_elements(raw: &[u8]) -> Result<Vec<Option<&[u8]>>, ConnectorError> {
+    let mismatch = |e: Box<dyn std::error::Error + Sync + Send>| {
+        ConnectorError::DataSchemaMismatch(e.to_string())
+    };
+    let array = postgres_protocol::types::array_from_sql(raw).map_err(mismatch)?;
+    if array.dimensions().count().map_err(mismatch)? > 1 {
+        return Err(ConnectorError::NotSupported {
+            connector_name: "connector_arrow::postgres",
+            feature: "multi-dimensional arrays".into(),
+        });
+    }
+    array.values().collect().map_err(mismatch)
+}
+
+/// Splits a composite value into the values of its attributes.
+fn composite_attributes<'a>(
+    attrs: &'a [postgres::types::Field],
+    mut raw: &'a [u8],
+) -> Result<Vec<RawCell<'a>>, ConnectorError> {
+    if take_i32(&mut raw)? as usize != attrs.len() {
+        return Err(ConnectorError::DataSchemaMismatch(
+            "invalid composite value".into(),
+        ));
+    }
+    (attrs.iter())
+        .map(|attr| {
+            let _oid = take_i32(&mut raw)?;
+            let len = take_i32(&mut raw)?;
+            let value = if len < 0 {
+                None
+            } else {
+                Some(take(&mut raw, len as usize)?)
+            };
+            Ok(RawCell {
+                ty: attr.type_(),
+                raw: value,
+            })
+        })
+        .collect()
+}
+
+fn take<'a>(raw: &mut &'a [u8], len: usize) -> Result<&'a [u8], ConnectorError> {
+    if raw.len() < len {
+        return Err(ConnectorError::DataSchemaMismatch(
+            "invalid composite value".into(),
+        ));
+    }
+    let (value, rest) = raw.split_at(len);
+    *raw = rest;
+    Ok(value)
+}
+
+fn take_i32(raw: &mut &[u8]) -> Result<i32, ConnectorError> {
+    Ok(i32::from_be_bytes(take(raw, 4)?.try_into().unwrap()))
+}
+
+/// Borrows the value in the binary format.
+struct Raw<'a>(&'a [u8]);
+
+impl<'a> FromSql<'a> for Raw<'a> {
+    fn from_sql(
+        _ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(Raw(raw))
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+}
+
 struct StrOrNum(String);
 
 impl StrOrNum {
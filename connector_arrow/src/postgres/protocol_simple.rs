@@ -3,14 +3,19 @@ use hex::decode;
 use postgres::{SimpleQueryMessage, SimpleQueryRow};
 
 use crate::api::{ArrowValue, Statement};
-use crate::types::{ArrowType, FixedSizeBinaryType};
+use crate::types::{
+    ArrowType, FixedSizeBinaryType, FixedSizeListType, LargeListType, ListType, MapType, StructType,
+};
 use crate::util::{collect_rows_to_arrow, transport, ArrowReader, CellReader};
 use crate::{errors::ConnectorError, util::RowsReader};
 
 use super::{types, PostgresError, PostgresStatement, ProtocolSimple};
 
 impl<'conn> Statement<'conn> for PostgresStatement<'conn, ProtocolSimple> {
-    type Reader<'stmt> = ArrowReader where Self: 'stmt;
+    type Reader<'stmt>
+        = ArrowReader
+    where
+        Self: 'stmt;
 
     fn start<'p, I>(&mut self, params: I) -> Result<Self::Reader<'_>, ConnectorError>
     where
@@ -19,12 +24,13 @@ impl<'conn> Statement<'conn> for PostgresStatement<'conn, ProtocolSimple> {
         if params.into_iter().count() > 0 {
             return Err(ConnectorError::NotSupported {
                 connector_name: "connector_arrow::postgres simple protocol",
-                feature: "query params",
+                feature: "query params".into(),
             });
         }
 
         let stmt = &self.stmt;
         let schema = types::pg_stmt_to_arrow(stmt)?;
+        let schema = self.reader_options.apply(schema);
 
         let rows = self
             .client
@@ -47,7 +53,10 @@ struct PostgresRowsReader {
 }
 
 impl<'stmt> RowsReader<'stmt> for PostgresRowsReader {
-    type CellReader<'row> = PostgresCellReader where Self: 'row;
+    type CellReader<'row>
+        = PostgresCellReader
+    where
+        Self: 'row;
 
     fn next_row(&mut self) -> Result<Option<Self::CellReader<'_>>, ConnectorError> {
         Ok(self.rows.next().and_then(|message| match message {
@@ -63,7 +72,10 @@ struct PostgresCellReader {
 }
 
 impl<'row> CellReader<'row> for PostgresCellReader {
-    type CellRef<'cell> = CellRef<'cell> where Self: 'cell;
+    type CellRef<'cell>
+        = CellRef<'cell>
+    where
+        Self: 'cell;
 
     fn next_cell(&mut self) -> Option<Self::CellRef<'_>> {
         if self.next_col >= self.row.columns().len() {
@@ -166,6 +178,11 @@ crate::impl_produce_unsupported!(
         FixedSizeBinaryType,
         Decimal128Type,
         Decimal256Type,
+        ListType,
+        LargeListType,
+        FixedSizeListType,
+        StructType,
+        MapType,
     )
 );
 
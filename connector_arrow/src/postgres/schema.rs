@@ -50,7 +50,7 @@ impl<P> SchemaGet for super::PostgresConnection<P> {
 
                 let ty = Type::from_oid(typid).ok_or(ConnectorError::NotSupported {
                     connector_name: "connector_arrow::postgres table_get",
-                    feature: "custom types",
+                    feature: "custom types".into(),
                 })?;
 
                 Ok(super::types::pg_field_to_arrow(name, &ty, !not_null))
@@ -62,32 +62,53 @@ impl<P> SchemaGet for super::PostgresConnection<P> {
 }
 
 impl<P> SchemaEdit for super::PostgresConnection<P> {
+    /// Struct columns are stored as composite types and map columns as arrays of composite types
+    /// with attributes `key` and `value`. These types are named `{table}.{column}` and are dropped
+    /// along with the table.
     fn table_create(&mut self, name: &str, schema: SchemaRef) -> Result<(), TableCreateError> {
+        let mut ddl = Vec::new();
         let column_defs = schema
             .fields()
             .iter()
             .map(|field| {
-                let ty =
-                    PostgresConnection::<ProtocolExtended>::type_arrow_into_db(field.data_type())
-                        .unwrap_or_else(|| {
-                            unimplemented!("cannot store type {} in PostgreSQL", field.data_type());
-                        });
+                let path = format!("{name}.{}", field.name());
+                let ty = column_type(&path, field.data_type(), &mut ddl).ok_or_else(|| {
+                    ConnectorError::IncompatibleSchema {
+                        table_name: name.to_string(),
+                        message: format!(
+                            "cannot store arrow type {} of column {} in PostgreSQL",
+                            field.data_type(),
+                            field.name()
+                        ),
+                        hint: None,
+                    }
+                })?;
 
                 let is_nullable =
                     field.is_nullable() || matches!(field.data_type(), DataType::Null);
                 let not_null = if is_nullable { "" } else { " NOT NULL" };
 
                 let name = escaped_ident(field.name());
-                format!("{name} {ty}{not_null}",)
+                Ok(format!("{name} {ty}{not_null}"))
             })
+            .collect::<Result<Vec<_>, ConnectorError>>()?
             .join(",");
 
-        let ddl = format!("CREATE TABLE {} ({column_defs});", escaped_ident(name));
+        ddl.push(format!(
+            "CREATE TABLE {} ({column_defs});",
+            escaped_ident(name)
+        ));
 
-        let res = self.client.execute(&ddl, &[]);
+        // statements of a batch are executed in a single transaction
+        let res = self.client.batch_execute(&ddl.join("\n"));
         match res {
             Ok(_) => Ok(()),
-            Err(e) if matches!(e.code(), Some(&SqlState::DUPLICATE_TABLE)) => {
+            Err(e)
+                if matches!(
+                    e.code(),
+                    Some(&SqlState::DUPLICATE_TABLE | &SqlState::DUPLICATE_OBJECT)
+                ) =>
+            {
                 Err(TableCreateError::TableExists)
             }
             Err(e) => Err(TableCreateError::Connector(ConnectorError::Postgres(
@@ -97,8 +118,22 @@ impl<P> SchemaEdit for super::PostgresConnection<P> {
     }
 
     fn table_drop(&mut self, name: &str) -> Result<(), TableDropError> {
-        let ddl = format!("DROP TABLE {}", escaped_ident(name));
-        let res = self.client.execute(&ddl, &[]);
+        let query = "
+            SELECT typname
+            FROM pg_type
+            JOIN pg_namespace ON (typnamespace = pg_namespace.oid)
+            JOIN pg_class ON (typrelid = pg_class.oid)
+            WHERE nspname = current_schema AND relkind = 'c' AND starts_with(typname, $1 || '.')
+        ";
+        let rows = (self.client.query(query, &[&name]))
+            .map_err(|e| TableDropError::Connector(PostgresError::Postgres(e).into()))?;
+        let types = rows.iter().map(|r| escaped_ident(r.get(0))).join(", ");
+
+        let mut ddl = format!("DROP TABLE {};", escaped_ident(name));
+        if !types.is_empty() {
+            ddl += &format!(" DROP TYPE {types};");
+        }
+        let res = self.client.batch_execute(&ddl);
 
         match res {
             Ok(_) => Ok(()),
@@ -111,3 +146,47 @@ impl<P> SchemaEdit for super::PostgresConnection<P> {
         }
     }
 }
+
+/// Converts an arrow type into a PostgreSQL type of a column, or of an attribute of a composite type.
+/// Appends DDL of the composite types that are needed to `ddl`.
+fn column_type(path: &str, data_type: &DataType, ddl: &mut Vec<String>) -> Option<String> {
+    match data_type {
+        DataType::Struct(fields) => {
+            let attributes = (fields.iter())
+                .map(|f| {
+                    let ty = column_type(&format!("{path}.{}", f.name()), f.data_type(), ddl)?;
+                    Some(format!("{} {ty}", escaped_ident(f.name())))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(create_type(path, attributes, ddl))
+        }
+        DataType::Map(entries, _) => {
+            let DataType::Struct(fields) = entries.data_type() else {
+                return None;
+            };
+            let [key, value] = &fields[..] else {
+                return None;
+            };
+            let key = column_type(&format!("{path}.key"), key.data_type(), ddl)?;
+            let value = column_type(&format!("{path}.value"), value.data_type(), ddl)?;
+            let attributes = vec![format!("key {key}"), format!("value {value}")];
+            Some(format!("{}[]", create_type(path, attributes, ddl)))
+        }
+        DataType::List(field) | DataType::LargeList(field) | DataType::FixedSizeList(field, _) => {
+            let element = column_type(path, field.data_type(), ddl)?;
+
+            // arrays cannot contain arrays
+            (!element.ends_with("[]")).then(|| format!("{element}[]"))
+        }
+        _ => PostgresConnection::<ProtocolExtended>::type_arrow_into_db(data_type),
+    }
+}
+
+fn create_type(name: &str, attributes: Vec<String>, ddl: &mut Vec<String>) -> String {
+    let name = escaped_ident(name).to_string();
+    ddl.push(format!(
+        "CREATE TYPE {name} AS ({});",
+        attributes.join(", ")
+    ));
+    name
+}
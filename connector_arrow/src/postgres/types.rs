@@ -1,7 +1,7 @@
 use std::{collections::HashMap, sync::Arc};
 
-use arrow::datatypes::{DataType, Field, Schema};
-use postgres::types::Type;
+use arrow::datatypes::{DataType, Field, Fields, Schema};
+use postgres::types::{Kind, Type};
 
 use crate::{api::Connector, errors::ConnectorError};
 
@@ -18,10 +18,28 @@ pub fn pg_stmt_to_arrow(
     Ok(Arc::new(Schema::new(fields)))
 }
 
+/// Arrays are converted into lists and composite types into structs.
+/// Arrays of composite types with attributes `key` and `value` are converted into maps.
 pub fn pg_field_to_arrow(name: String, db_ty: &Type, nullable: bool) -> Field {
     let mut metadata = HashMap::new();
 
-    let data_type = PostgresConnection::<ProtocolExtended>::type_db_into_arrow(db_ty.name());
+    let data_type = match db_ty.kind() {
+        Kind::Array(element) => Some(match element.kind() {
+            Kind::Composite(attrs) if is_map_entry(attrs) => {
+                let key = pg_field_to_arrow(attrs[0].name().to_string(), attrs[0].type_(), false);
+                let value = pg_field_to_arrow(attrs[1].name().to_string(), attrs[1].type_(), true);
+                let entries = DataType::Struct(Fields::from(vec![key, value]));
+                DataType::Map(Arc::new(Field::new("entries", entries, false)), false)
+            }
+            _ => DataType::List(Arc::new(pg_field_to_arrow("item".into(), element, true))),
+        }),
+        Kind::Composite(attrs) => Some(DataType::Struct(
+            (attrs.iter())
+                .map(|a| pg_field_to_arrow(a.name().to_string(), a.type_(), true))
+                .collect(),
+        )),
+        _ => PostgresConnection::<ProtocolExtended>::type_db_into_arrow(db_ty.name()),
+    };
 
     // if we cannot map to an arrow type, map into a binary
     let data_type = data_type.unwrap_or_else(|| {
@@ -35,3 +53,7 @@ pub fn pg_field_to_arrow(name: String, db_ty: &Type, nullable: bool) -> Field {
 
     Field::new(name, data_type, nullable).with_metadata(metadata)
 }
+
+fn is_map_entry(attrs: &[postgres::types::Field]) -> bool {
+    matches!(attrs, [key, value] if key.name() == "key" && value.name() == "value")
+}
@@ -1,26 +1,57 @@
-use arrow::datatypes::*;
-use arrow::record_batch::RecordBatch;
 use itertools::zip_eq;
 use itertools::Itertools;
-use rusqlite::types::Value;
-use rusqlite::{params_from_iter, Transaction};
+use rusqlite::limits::Limit;
+use rusqlite::Transaction;
 
-use crate::impl_consume_unsupported;
-use crate::types::{FixedSizeBinaryType, NullType};
+use super::bind::{Binder, StatementParams};
 use crate::util::escape::escaped_ident;
-use crate::util::transport;
-use crate::util::transport::{Consume, ConsumeTy};
-use crate::util::ArrayCellRef;
+use crate::util::{ArrayCellRef, CellTransporter};
 use crate::{api::Append, ConnectorError};
 
+/// Upper bound for number of rows inserted by a single statement.
+/// Larger statements don't improve throughput, but take longer to prepare.
+const MAX_ROWS_PER_STATEMENT: usize = 256;
+
 pub struct SQLiteAppender<'conn> {
     table: String,
     transaction: Transaction<'conn>,
+
+    /// Queries are the same for all batches, so they are built on first append.
+    queries: Option<InsertQueries>,
+}
+
+struct InsertQueries {
+    rows_per_statement: usize,
+
+    /// Inserts `rows_per_statement` rows.
+    multi_row: String,
+
+    /// Inserts a single row, used for the remainder of the batch.
+    single_row: String,
 }
 
 impl<'conn> SQLiteAppender<'conn> {
     pub fn new(table: String, transaction: Transaction<'conn>) -> Result<Self, ConnectorError> {
-        Ok(Self { table, transaction })
+        Ok(Self {
+            table,
+            transaction,
+            queries: None,
+        })
+    }
+}
+
+impl InsertQueries {
+    fn new(table: &str, transaction: &Transaction, cols: usize) -> Self {
+        // each row binds one variable per column
+        let max_variables = transaction.limit(Limit::SQLITE_LIMIT_VARIABLE_NUMBER);
+        let rows_per_statement =
+            (max_variables.max(1) as usize / cols.max(1)).clamp(1, MAX_ROWS_PER_STATEMENT);
+
+        InsertQueries {
+            rows_per_statement,
+            multi_row: insert_query(table, cols, rows_per_statement),
+            single_row: insert_query(table, cols, 1),
+        }
     }
 }
 
@@ -29,27 +60,48 @@ impl<'conn> Append<'conn> for SQLiteAppender<'conn> {
         &mut self,
         batch: arrow::record_batch::RecordBatch,
     ) -> Result<(), crate::ConnectorError> {
-        // TODO: 30 is a guess, we need benchmarking to find the optimum value
-        const BATCH_SIZE: usize = 30;
-
-        let last_batch_size = batch.num_rows() % BATCH_SIZE;
-
-        let batch_query = insert_query(&self.table, batch.num_columns(), BATCH_SIZE);
-        for batch_number in 0..(batch.num_rows() / BATCH_SIZE) {
-            let rows_range = (batch_number * BATCH_SIZE)..((batch_number + 1) * BATCH_SIZE);
-
-            let params: Vec<Value> = collect_args(&batch, rows_range);
-            self.transaction
-                .execute(&batch_query, params_from_iter(params))?;
+        let num_rows = batch.num_rows();
+        if num_rows == 0 {
+            return Ok(());
         }
 
-        if last_batch_size > 0 {
-            let rows_range = (batch.num_rows() - last_batch_size)..batch.num_rows();
+        let (table, transaction) = (&self.table, &self.transaction);
+        let queries = self
+            .queries
+            .get_or_insert_with(|| InsertQueries::new(table, transaction, batch.num_columns()));
+        let rows_per_statement = queries.rows_per_statement;
+
+        let schema = batch.schema();
+        let mut row = zip_eq(batch.columns(), schema.fields())
+            .map(|(array, field)| ArrayCellRef {
+                array,
+                field,
+                row_number: 0,
+            })
+            .collect_vec();
+
+        // statements are cached by the connection, so they are prepared only once per appender
+        let multi_row_count = num_rows / rows_per_statement;
+        if multi_row_count > 0 {
+            let mut statement = self.transaction.prepare_cached(&queries.multi_row)?;
+            let mut binder = Binder::new(StatementParams::new(&mut statement));
+            let transporters = ArrayCellRef::transporters(schema.fields())?;
+            for statement_number in 0..multi_row_count {
+                let start = statement_number * rows_per_statement;
+                let rows_range = start..(start + rows_per_statement);
+                execute_rows(&mut binder, &mut row, &transporters, rows_range)?;
+            }
+        }
 
-            let last_query = insert_query(&self.table, batch.num_columns(), last_batch_size);
-            let params: Vec<Value> = collect_args(&batch, rows_range);
-            self.transaction
-                .execute(&last_query, params_from_iter(params))?;
+        let remainder_start = multi_row_count * rows_per_statement;
+        if remainder_start < num_rows {
+            let mut statement = self.transaction.prepare_cached(&queries.single_row)?;
+            let mut binder = Binder::new(StatementParams::new(&mut statement));
+            let transporters = ArrayCellRef::transporters(schema.fields())?;
+            for row_number in remainder_start..num_rows {
+                let rows_range = row_number..(row_number + 1);
+                execute_rows(&mut binder, &mut row, &transporters, rows_range)?;
+            }
         }
 
         Ok(())
@@ -71,127 +123,19 @@ fn insert_query(table_name: &str, cols: usize, rows: usize) -> String {
     format!("INSERT INTO {} VALUES {values}", escaped_ident(table_name))
 }
 
-fn collect_args(batch: &RecordBatch, rows_range: std::ops::Range<usize>) -> Vec<Value> {
-    let mut res = Vec::with_capacity(rows_range.len() * batch.num_columns());
-
-    let schema = batch.schema();
-    let mut row = zip_eq(batch.columns(), schema.fields())
-        .map(|(array, field)| ArrayCellRef {
-            array,
-            field,
-            row_number: 0,
-        })
-        .collect_vec();
-
+/// Binds values of the rows directly from the arrays and executes the statement.
+fn execute_rows<'a, 'conn>(
+    binder: &mut Binder<StatementParams<'a, 'conn>>,
+    row: &mut [ArrayCellRef],
+    transporters: &[CellTransporter<Binder<StatementParams<'a, 'conn>>>],
+    rows_range: std::ops::Range<usize>,
+) -> Result<(), ConnectorError> {
     for row_number in rows_range {
-        for cell in &mut row {
+        for (cell, transport) in zip_eq(row.iter_mut(), transporters) {
             cell.row_number = row_number;
-            transport::transport(cell.field, cell as &_, &mut res).unwrap();
+            transport(cell, binder)?;
         }
     }
-    res
-}
-
-impl Consume for Vec<Value> {}
-
-macro_rules! impl_consume_ty {
-    ($ArrTy: ty, $value_kind: expr) => {
-        impl_consume_ty!($ArrTy, $value_kind, std::convert::identity);
-    };
-
-    ($ArrTy: ty, $value_kind: expr, $conversion: expr) => {
-        impl ConsumeTy<$ArrTy> for Vec<Value> {
-            fn consume(
-                &mut self,
-                _ty: &DataType,
-                value: <$ArrTy as crate::types::ArrowType>::Native,
-            ) {
-                let value: Value = $value_kind(($conversion)(value));
-                self.push(value);
-            }
-
-            fn consume_null(&mut self) {
-                self.push(Value::Null);
-            }
-        }
-    };
-}
-
-impl ConsumeTy<NullType> for Vec<Value> {
-    fn consume(&mut self, _ty: &DataType, _value: ()) {
-        self.push(Value::Null);
-    }
-
-    fn consume_null(&mut self) {
-        self.push(Value::Null);
-    }
-}
-
-impl ConsumeTy<Decimal128Type> for Vec<Value> {
-    fn consume(&mut self, ty: &DataType, value: i128) {
-        self.push(Value::Text(crate::util::decimal::decimal128_to_string(
-            ty, value,
-        )));
-    }
-
-    fn consume_null(&mut self) {
-        self.push(Value::Null);
-    }
-}
-
-impl ConsumeTy<Decimal256Type> for Vec<Value> {
-    fn consume(&mut self, ty: &DataType, value: i256) {
-        self.push(Value::Text(crate::util::decimal::decimal256_to_string(
-            ty, value,
-        )));
-    }
-
-    fn consume_null(&mut self) {
-        self.push(Value::Null);
-    }
-}
-
-impl_consume_ty!(BooleanType, Value::Integer, i64::from);
-impl_consume_ty!(Int8Type, Value::Integer, i64::from);
-impl_consume_ty!(Int16Type, Value::Integer, i64::from);
-impl_consume_ty!(Int32Type, Value::Integer, i64::from);
-impl_consume_ty!(Int64Type, Value::Integer);
-impl_consume_ty!(UInt8Type, Value::Integer, i64::from);
-impl_consume_ty!(UInt16Type, Value::Integer, i64::from);
-impl_consume_ty!(UInt32Type, Value::Integer, i64::from);
-impl_consume_ty!(UInt64Type, Value::Text, u64_to_string);
-impl_consume_ty!(Float16Type, Value::Real, f64::from);
-impl_consume_ty!(Float32Type, Value::Real, f64::from);
-impl_consume_ty!(TimestampSecondType, Value::Integer);
-impl_consume_ty!(TimestampMillisecondType, Value::Integer);
-impl_consume_ty!(TimestampMicrosecondType, Value::Integer);
-impl_consume_ty!(TimestampNanosecondType, Value::Integer);
-impl_consume_ty!(Date32Type, Value::Integer, i64::from);
-impl_consume_ty!(Date64Type, Value::Integer);
-impl_consume_ty!(Time32SecondType, Value::Integer, i64::from);
-impl_consume_ty!(Time32MillisecondType, Value::Integer, i64::from);
-impl_consume_ty!(Time64MicrosecondType, Value::Integer);
-impl_consume_ty!(Time64NanosecondType, Value::Integer);
-impl_consume_ty!(DurationSecondType, Value::Integer);
-impl_consume_ty!(DurationMillisecondType, Value::Integer);
-impl_consume_ty!(DurationMicrosecondType, Value::Integer);
-impl_consume_ty!(DurationNanosecondType, Value::Integer);
-impl_consume_ty!(Float64Type, Value::Real);
-impl_consume_ty!(BinaryType, Value::Blob);
-impl_consume_ty!(LargeBinaryType, Value::Blob);
-impl_consume_ty!(FixedSizeBinaryType, Value::Blob);
-impl_consume_ty!(Utf8Type, Value::Text);
-impl_consume_ty!(LargeUtf8Type, Value::Text);
-
-impl_consume_unsupported!(
-    Vec<Value>,
-    (
-        IntervalYearMonthType,
-        IntervalDayTimeType,
-        IntervalMonthDayNanoType,
-    )
-);
-
-fn u64_to_string(u: u64) -> String {
-    u64::to_string(&u)
+    binder.execute()?;
+    Ok(())
 }
@@ -0,0 +1,185 @@
+//! Conversion of arrow values into SQLite values.
+
+use arrow::datatypes::*;
+use rusqlite::types::Null;
+use rusqlite::vtab::Context;
+use rusqlite::{Statement, ToSql};
+
+use crate::types::{
+    FixedSizeBinaryType, FixedSizeListType, LargeListType, ListType, MapType, NullType, StructType,
+};
+use crate::util::transport::{Consume, ConsumeTy};
+use crate::{impl_consume_unsupported, ConnectorError};
+
+/// Destination of SQLite values.
+pub trait BindTarget {
+    fn bind<T: ToSql>(&mut self, value: T) -> rusqlite::Result<()>;
+}
+
+/// Binds values to consecutive parameters of a statement.
+pub struct StatementParams<'a, 'conn> {
+    statement: &'a mut Statement<'conn>,
+
+    /// Index of the last bound parameter (parameters are 1-based).
+    index: usize,
+}
+
+impl<'a, 'conn> StatementParams<'a, 'conn> {
+    pub fn new(statement: &'a mut Statement<'conn>) -> Self {
+        StatementParams {
+            statement,
+            index: 0,
+        }
+    }
+}
+
+impl<'a, 'conn> BindTarget for StatementParams<'a, 'conn> {
+    fn bind<T: ToSql>(&mut self, value: T) -> rusqlite::Result<()> {
+        self.index += 1;
+        self.statement.raw_bind_parameter(self.index, value)
+    }
+}
+
+/// Sets the result of a virtual table column.
+impl BindTarget for Context {
+    fn bind<T: ToSql>(&mut self, value: T) -> rusqlite::Result<()> {
+        self.set_result(&value)
+    }
+}
+
+/// Converts consumed arrow values into SQLite values and passes them to a [BindTarget].
+#[repr(transparent)]
+pub struct Binder<T> {
+    target: T,
+}
+
+impl<T: BindTarget> Binder<T> {
+    pub fn new(target: T) -> Self {
+        Binder { target }
+    }
+
+    /// Wraps a borrowed target, so transporters resolved for `Binder<T>` can be used
+    /// with targets that are only borrowed (i.e. [Context] of a virtual table column).
+    pub fn from_mut(target: &mut T) -> &mut Self {
+        // SAFETY: Binder is a transparent wrapper of T
+        unsafe { &mut *(target as *mut T as *mut Self) }
+    }
+
+    fn bind<V: ToSql>(&mut self, value: V) -> Result<(), ConnectorError> {
+        Ok(self.target.bind(value)?)
+    }
+}
+
+impl<'a, 'conn> Binder<StatementParams<'a, 'conn>> {
+    /// Executes the statement with bound parameters. Values consumed after this
+    /// are bound to the parameters of the next execution, starting with the first.
+    pub fn execute(&mut self) -> rusqlite::Result<()> {
+        self.target.index = 0;
+        self.target.statement.raw_execute()?;
+        Ok(())
+    }
+}
+
+impl<T: BindTarget> Consume for Binder<T> {}
+
+macro_rules! impl_consume_ty {
+    ($ArrTy: ty) => {
+        impl_consume_ty!($ArrTy, std::convert::identity);
+    };
+
+    ($ArrTy: ty, $conversion: expr) => {
+        impl<T: BindTarget> ConsumeTy<$ArrTy> for Binder<T> {
+            fn consume(
+                &mut self,
+                _ty: &DataType,
+                value: <$ArrTy as crate::types::ArrowType>::Native,
+            ) -> Result<(), ConnectorError> {
+                self.bind(($conversion)(value))
+            }
+
+            fn consume_null(&mut self) -> Result<(), ConnectorError> {
+                self.bind(Null)
+            }
+        }
+    };
+}
+
+impl<T: BindTarget> ConsumeTy<NullType> for Binder<T> {
+    fn consume(&mut self, _ty: &DataType, _value: ()) -> Result<(), ConnectorError> {
+        self.bind(Null)
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        self.bind(Null)
+    }
+}
+
+impl<T: BindTarget> ConsumeTy<Decimal128Type> for Binder<T> {
+    fn consume(&mut self, ty: &DataType, value: i128) -> Result<(), ConnectorError> {
+        self.bind(crate::util::decimal::decimal128_to_string(ty, value))
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        self.bind(Null)
+    }
+}
+
+impl<T: BindTarget> ConsumeTy<Decimal256Type> for Binder<T> {
+    fn consume(&mut self, ty: &DataType, value: i256) -> Result<(), ConnectorError> {
+        self.bind(crate::util::decimal::decimal256_to_string(ty, value))
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        self.bind(Null)
+    }
+}
+
+impl_consume_ty!(BooleanType, i64::from);
+impl_consume_ty!(Int8Type, i64::from);
+impl_consume_ty!(Int16Type, i64::from);
+impl_consume_ty!(Int32Type, i64::from);
+impl_consume_ty!(Int64Type);
+impl_consume_ty!(UInt8Type, i64::from);
+impl_consume_ty!(UInt16Type, i64::from);
+impl_consume_ty!(UInt32Type, i64::from);
+impl_consume_ty!(UInt64Type, u64_to_string);
+impl_consume_ty!(Float16Type, f64::from);
+impl_consume_ty!(Float32Type, f64::from);
+impl_consume_ty!(TimestampSecondType);
+impl_consume_ty!(TimestampMillisecondType);
+impl_consume_ty!(TimestampMicrosecondType);
+impl_consume_ty!(TimestampNanosecondType);
+impl_consume_ty!(Date32Type, i64::from);
+impl_consume_ty!(Date64Type);
+impl_consume_ty!(Time32SecondType, i64::from);
+impl_consume_ty!(Time32MillisecondType, i64::from);
+impl_consume_ty!(Time64MicrosecondType);
+impl_consume_ty!(Time64NanosecondType);
+impl_consume_ty!(DurationSecondType);
+impl_consume_ty!(DurationMillisecondType);
+impl_consume_ty!(DurationMicrosecondType);
+impl_consume_ty!(DurationNanosecondType);
+impl_consume_ty!(Float64Type);
+impl_consume_ty!(BinaryType);
+impl_consume_ty!(LargeBinaryType);
+impl_consume_ty!(FixedSizeBinaryType);
+impl_consume_ty!(Utf8Type);
+impl_consume_ty!(LargeUtf8Type);
+
+impl_consume_unsupported!(
+    impl[T: BindTarget] Binder<T>,
+    (
+        IntervalYearMonthType,
+        IntervalDayTimeType,
+        IntervalMonthDayNanoType,
+        ListType,
+        LargeListType,
+        FixedSizeListType,
+        StructType,
+        MapType,
+    )
+);
+
+fn u64_to_string(u: u64) -> String {
+    u64::to_string(&u)
+}
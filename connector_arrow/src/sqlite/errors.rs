@@ -0,0 +1,35 @@
+use rusqlite::ErrorCode;
+
+use crate::{DbErrorDetails, DbErrorKind};
+
+pub(crate) fn error_details(error: &rusqlite::Error) -> Option<DbErrorDetails> {
+    let rusqlite::Error::SqliteFailure(error, message) = error else {
+        return None;
+    };
+    let message = message.as_deref().unwrap_or_default();
+
+    let kind = match error.code {
+        // SQLite reports syntax and resolution errors with the generic SQLITE_ERROR code
+        ErrorCode::Unknown
+            if message.contains("syntax error") || message.starts_with("no such ") =>
+        {
+            DbErrorKind::Syntax
+        }
+        ErrorCode::ConstraintViolation => DbErrorKind::ConstraintViolation,
+        // busy timeout has expired or the table is locked by the same connection
+        ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => DbErrorKind::Timeout,
+        ErrorCode::OperationInterrupted => DbErrorKind::Timeout,
+        _ => DbErrorKind::Other,
+    };
+    let mut details = DbErrorDetails::new(kind, Some(error.extended_code.to_string()));
+
+    // i.e. "UNIQUE constraint failed: t.a" or "CHECK constraint failed: positive_a"
+    if let Some((constraint, target)) = message.split_once(" constraint failed: ") {
+        if constraint == "CHECK" {
+            details.constraint = Some(target.to_string());
+        } else {
+            details.column = Some(target.to_string());
+        }
+    }
+    Some(details)
+}
@@ -1,7 +1,10 @@
 //! Provides `connector_arrow` traits for [rusqlite crate](https://docs.rs/rusqlite).
 
 mod append;
+mod bind;
+mod errors;
 mod query;
+mod scan;
 mod schema;
 mod types;
 
@@ -10,49 +13,130 @@ pub use append::SQLiteAppender;
 #[doc(hidden)]
 pub use query::SQLiteStatement;
 
-use crate::api::Connector;
-use crate::errors::ConnectorError;
-use arrow::datatypes::DataType;
+pub(crate) use errors::error_details;
+
+use std::collections::HashMap;
+
+use crate::api::{Connector, ResultReader};
+use crate::errors::{ConnectorError, TableCreateError};
+use crate::util::escape::escaped_ident;
+use crate::util::{registry, ReaderOptions};
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::record_batch::RecordBatch;
 
 pub struct SQLiteConnection {
     inner: rusqlite::Connection,
+    parse_temporal: bool,
+    reader_options: ReaderOptions,
+
+    /// Ids of batches registered by [SQLiteConnection::register_batches], by table name.
+    registered: HashMap<String, u64>,
+    owner: registry::Owner,
+    scan_module_registered: bool,
 }
 
 impl SQLiteConnection {
     pub fn new(inner: rusqlite::Connection) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            parse_temporal: false,
+            reader_options: ReaderOptions::default(),
+            registered: HashMap::new(),
+            owner: registry::Owner::new(),
+            scan_module_registered: false,
+        }
     }
-}
 
-impl Connector for SQLiteConnection {
-    type Stmt<'conn> = SQLiteStatement<'conn> where Self: 'conn;
-
-    type Append<'conn> = SQLiteAppender<'conn> where Self: 'conn;
+    /// Enables conversion of columns declared as `TIMESTAMP` and `TIME` into
+    /// [DataType::Timestamp] and [DataType::Time64] (columns declared as `DATETIME` and `DATE`
+    /// are always converted).
+    ///
+    /// Values are parsed from ISO-8601 text, unixepoch integers (in seconds) or Julian day reals,
+    /// which is how SQLite date and time functions encode them. This is opt-in because
+    /// applications often use these names for columns with other encodings.
+    pub fn set_parse_temporal(&mut self, enabled: bool) {
+        self.parse_temporal = enabled;
+    }
 
-    fn query(&mut self, query: &str) -> Result<SQLiteStatement, ConnectorError> {
-        let stmt = self.inner.prepare(query)?;
-        Ok(SQLiteStatement { stmt })
+    /// Sets how query results are read into arrow.
+    pub fn set_reader_options(&mut self, options: ReaderOptions) {
+        self.reader_options = options;
     }
 
-    fn append<'a>(&'a mut self, table: &str) -> Result<Self::Append<'a>, ConnectorError> {
-        let transaction = self.inner.transaction()?;
+    /// Registers in-memory batches as a temporary, read-only virtual table that can be queried
+    /// (and joined with tables) for the lifetime of this connection.
+    ///
+    /// The data is not copied into SQLite, but scanned each time the table is queried.
+    /// Column types are declared as in [SQLiteConnection::type_arrow_into_db], so rows can be
+    /// copied into tables created from the same schema with `INSERT INTO t SELECT * FROM ...`.
+    /// Registering another table with the same name replaces the previous one, but fails with
+    /// [TableCreateError::TableExists] if a table or view of this name was not registered.
+    pub fn register_batches(
+        &mut self,
+        table_name: &str,
+        schema: SchemaRef,
+        batches: Vec<RecordBatch>,
+    ) -> Result<(), TableCreateError> {
+        let replaced = self.registered.get(table_name).copied();
+        if replaced.is_none() && self.table_exists(table_name)? {
+            return Err(TableCreateError::TableExists);
+        }
 
-        SQLiteAppender::new(table.to_string(), transaction)
-    }
+        if !self.scan_module_registered {
+            let module = rusqlite::vtab::read_only_module::<scan::ArrowTab>();
+            self.inner
+                .create_module(scan::MODULE_NAME, module, Some(self.owner))
+                .map_err(ConnectorError::from)?;
+            self.scan_module_registered = true;
+        }
 
-    fn type_db_into_arrow(database_ty: &str) -> Option<DataType> {
-        match database_ty {
-            "NULL" => Some(DataType::Null),
-            "INTEGER" => Some(DataType::Int64),
-            "REAL" => Some(DataType::Float64),
-            "TEXT" => Some(DataType::Utf8),
-            "BLOB" => Some(DataType::Binary),
-            _ => None,
+        let id = scan::register(self.owner, table_name, schema, batches)?;
+        let table = escaped_ident(table_name);
+        let mut ddl = String::new();
+        if replaced.is_some() {
+            ddl += &format!("DROP TABLE IF EXISTS temp.{table}; ");
+        }
+        ddl += &format!(
+            "CREATE VIRTUAL TABLE temp.{table} USING {}({id});",
+            scan::MODULE_NAME
+        );
+        if let Err(e) = self.inner.execute_batch(&ddl) {
+            registry::unregister(id);
+            return Err(ConnectorError::from(e).into());
         }
+
+        if let Some(replaced) = self.registered.insert(table_name.to_string(), id) {
+            registry::unregister(replaced);
+        }
+        Ok(())
     }
 
-    fn type_arrow_into_db(ty: &DataType) -> Option<String> {
-        let s = match ty {
+    /// Collects all batches of a result and registers them as a temporary virtual table.
+    /// See [SQLiteConnection::register_batches].
+    pub fn register_reader<'r, R: ResultReader<'r>>(
+        &mut self,
+        table_name: &str,
+        mut reader: R,
+    ) -> Result<(), TableCreateError> {
+        let schema = reader.get_schema()?;
+        let batches = reader.collect::<Result<Vec<_>, _>>()?;
+        self.register_batches(table_name, schema, batches)
+    }
+
+    /// Checks for tables and views in both the main and the temporary schema, since a temporary
+    /// table would shadow the table in the main schema.
+    fn table_exists(&self, name: &str) -> Result<bool, ConnectorError> {
+        let query = "SELECT EXISTS (
+            SELECT 1 FROM sqlite_master WHERE type IN ('table', 'view') AND name = ?1
+            UNION ALL
+            SELECT 1 FROM sqlite_temp_master WHERE type IN ('table', 'view') AND name = ?1
+        )";
+        Ok(self.inner.query_row(query, [name], |row| row.get(0))?)
+    }
+
+    /// Storage class that values of the arrow type are stored as.
+    pub(super) fn storage_class(ty: &DataType) -> Option<&'static str> {
+        Some(match ty {
             DataType::Null => "NULL",
             DataType::Boolean => "INTEGER",
 
@@ -86,14 +170,70 @@ impl Connector for SQLiteConnection {
             DataType::Binary => "BLOB",
             DataType::FixedSizeBinary(_) => "BLOB",
             DataType::LargeBinary => "BLOB",
+            DataType::BinaryView => "BLOB",
 
             DataType::Utf8 => "TEXT",
             DataType::LargeUtf8 => "TEXT",
+            DataType::Utf8View => "TEXT",
+            // see crate::util::dictionary_encode_schema
+            DataType::Dictionary(_, value)
+                if matches!(**value, DataType::Utf8 | DataType::LargeUtf8) =>
+            {
+                return Self::storage_class(value)
+            }
 
             DataType::Decimal128(_, _) => "TEXT",
             DataType::Decimal256(_, _) => "TEXT",
             _ => return None,
-        };
-        Some(s.to_string())
+        })
+    }
+}
+
+impl Drop for SQLiteConnection {
+    fn drop(&mut self) {
+        for id in self.registered.values() {
+            registry::unregister(*id);
+        }
+    }
+}
+
+impl Connector for SQLiteConnection {
+    type Stmt<'conn> = SQLiteStatement<'conn> where Self: 'conn;
+
+    type Append<'conn> = SQLiteAppender<'conn> where Self: 'conn;
+
+    fn query(&mut self, query: &str) -> Result<SQLiteStatement, ConnectorError> {
+        let stmt = self.inner.prepare(query)?;
+        Ok(SQLiteStatement {
+            stmt,
+            parse_temporal: self.parse_temporal,
+            reader_options: &self.reader_options,
+        })
+    }
+
+    fn append<'a>(&'a mut self, table: &str) -> Result<Self::Append<'a>, ConnectorError> {
+        let transaction = self.inner.transaction()?;
+
+        SQLiteAppender::new(table.to_string(), transaction)
+    }
+
+    fn type_db_into_arrow(database_ty: &str) -> Option<DataType> {
+        if let Some(ty) = types::parse_recorded_type(database_ty) {
+            return Some(ty);
+        }
+        match database_ty {
+            "NULL" => Some(DataType::Null),
+            "INTEGER" => Some(DataType::Int64),
+            "REAL" => Some(DataType::Float64),
+            "TEXT" => Some(DataType::Utf8),
+            "BLOB" => Some(DataType::Binary),
+            _ => None,
+        }
+    }
+
+    /// Declares the storage class of the type, together with the arrow type itself,
+    /// so it can be restored when the column is read (i.e. `INTEGER 'arrow:i8'`).
+    fn type_arrow_into_db(ty: &DataType) -> Option<String> {
+        types::declare_recorded_type(Self::storage_class(ty)?, ty)
     }
 }
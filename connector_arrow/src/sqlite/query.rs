@@ -1,20 +1,26 @@
+use std::convert::TryFrom;
 use std::sync::Arc;
 
 use arrow::datatypes::*;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use itertools::zip_eq;
 use rusqlite::types::{Type, Value};
 
-use crate::api::{ArrowValue, Connector, Statement};
-use crate::types::FixedSizeBinaryType;
+use crate::api::{ArrowValue, Connector, Statement, METADATA_DB_TYPE};
+use crate::types::{
+    FixedSizeBinaryType, FixedSizeListType, LargeListType, ListType, MapType, StructType,
+};
 use crate::util::transport::{Produce, ProduceTy};
-use crate::util::ArrowReader;
 use crate::util::{collect_rows_to_arrow, CellReader, RowsReader};
+use crate::util::{ArrowReader, ReaderOptions};
 use crate::ConnectorError;
 
 use super::SQLiteConnection;
 
 pub struct SQLiteStatement<'conn> {
     pub stmt: rusqlite::Statement<'conn>,
+    pub(super) parse_temporal: bool,
+    pub(super) reader_options: &'conn ReaderOptions,
 }
 
 impl<'conn> Statement<'conn> for SQLiteStatement<'conn> {
@@ -43,14 +49,19 @@ impl<'conn> Statement<'conn> for SQLiteStatement<'conn> {
         };
 
         // infer schema
-        let schema = infer_schema(&self.stmt, &rows, column_count)?;
+        let (schema, recorded_types) =
+            infer_schema(&self.stmt, &rows, column_count, self.parse_temporal)?;
+        let schema = self.reader_options.apply(schema);
 
         // iterate over rows and convert into arrow
         let row_count = rows.len();
         let mut rows = SQLiteRowsReader {
             rows: rows.into_iter(),
+            schema: schema.clone(),
+            row_number: 0,
         };
         let batches = collect_rows_to_arrow(schema.clone(), &mut rows, row_count)?;
+        let (schema, batches) = super::types::cast_to_recorded(schema, batches, &recorded_types)?;
 
         Ok(ArrowReader::new(schema, batches))
     }
@@ -60,7 +71,8 @@ fn infer_schema(
     stmt: &rusqlite::Statement,
     rows: &Vec<Vec<Value>>,
     column_count: usize,
-) -> Result<Arc<arrow::datatypes::Schema>, ConnectorError> {
+    parse_temporal: bool,
+) -> Result<(SchemaRef, Vec<Option<DataType>>), ConnectorError> {
     let mut types = vec![None; column_count];
 
     for row in rows {
@@ -88,104 +100,390 @@ fn infer_schema(
     }
 
     let mut fields = Vec::with_capacity(column_count);
-    for (name, ty) in zip_eq(stmt.column_names(), types) {
-        let ty = ty.unwrap_or(DataType::Null);
-
+    let mut recorded_types = Vec::with_capacity(column_count);
+    for (column, ty) in zip_eq(stmt.columns(), types) {
+        let name = column.name().to_string();
         let nullable = true; // dynamic type system FTW
+
+        // columns of tables created by table_create are read in their storage class
+        // and later converted into the recorded arrow type
+        let recorded = column.decl_type().and_then(super::types::parse_recorded_type);
+        if let Some(recorded) = recorded {
+            if let Some(ty) = super::types::storage_type(&recorded) {
+                fields.push(arrow::datatypes::Field::new(name, ty, nullable));
+                recorded_types.push(Some(recorded));
+                continue;
+            }
+        }
+        recorded_types.push(None);
+
+        // values of columns with NUMERIC affinity are stored in different storage classes,
+        // so their type must be determined from the declared type
+        let decl_ty = column.decl_type();
+        if let Some((decl_ty, ty)) = decl_ty.and_then(|d| {
+            Some((
+                d,
+                super::types::numeric_decl_ty_to_arrow(d, parse_temporal)?,
+            ))
+        }) {
+            fields.push(super::types::numeric_field(name, decl_ty, ty, nullable));
+            continue;
+        }
+
+        let ty = ty.unwrap_or(DataType::Null);
         fields.push(arrow::datatypes::Field::new(name, ty, nullable));
     }
 
-    Ok(Arc::new(arrow::datatypes::Schema::new(fields)))
+    let schema = Arc::new(arrow::datatypes::Schema::new(fields));
+    Ok((schema, recorded_types))
 }
 
 pub struct SQLiteRowsReader {
     rows: std::vec::IntoIter<Vec<Value>>,
+    schema: SchemaRef,
+    row_number: usize,
 }
 
 impl<'stmt> RowsReader<'stmt> for SQLiteRowsReader {
-    type CellReader<'rows> = SQLiteCellReader
+    type CellReader<'rows> = SQLiteCellReader<'rows>
     where
         Self: 'rows;
 
     fn next_row(&mut self) -> Result<Option<Self::CellReader<'_>>, ConnectorError> {
-        Ok(self.rows.next().map(|row| SQLiteCellReader {
+        let Some(row) = self.rows.next() else {
+            return Ok(None);
+        };
+        let row_number = self.row_number;
+        self.row_number += 1;
+
+        Ok(Some(SQLiteCellReader {
             row: row.into_iter(),
+            fields: self.schema.fields().iter(),
+            row_number,
         }))
     }
 }
 
-pub struct SQLiteCellReader {
+pub struct SQLiteCellReader<'rows> {
     row: std::vec::IntoIter<Value>,
+    fields: std::slice::Iter<'rows, FieldRef>,
+    row_number: usize,
 }
 
-impl<'rows> CellReader<'rows> for SQLiteCellReader {
-    type CellRef<'row> = Value
+impl<'rows> CellReader<'rows> for SQLiteCellReader<'rows> {
+    type CellRef<'row> = SQLiteCell<'row>
     where
         Self: 'row;
 
     fn next_cell(&mut self) -> Option<Self::CellRef<'_>> {
-        self.row.next()
+        Some(SQLiteCell {
+            value: self.row.next()?,
+            field: self.fields.next()?,
+            row_number: self.row_number,
+        })
+    }
+}
+
+/// A value, together with the field it is converted into.
+#[derive(Debug)]
+pub struct SQLiteCell<'a> {
+    value: Value,
+    field: &'a Field,
+    /// zero-based index of the row in the result, for error messages
+    row_number: usize,
+}
+
+impl<'a> SQLiteCell<'a> {
+    fn mismatch(&self) -> ConnectorError {
+        ConnectorError::DataSchemaMismatch(format!(
+            "cannot convert {:?} in row {} of column {} into {}",
+            self.value,
+            self.row_number,
+            self.field.name(),
+            self.field.data_type()
+        ))
+    }
+
+    /// Error for a null value of a non-nullable field,
+    /// which can be created after the cell has been consumed.
+    fn null_mismatch(&self) -> impl FnOnce() -> ConnectorError + 'a {
+        let (field, row_number) = (self.field, self.row_number);
+        move || {
+            let value = Value::Null;
+            SQLiteCell {
+                value,
+                field,
+                row_number,
+            }
+            .mismatch()
+        }
     }
 }
 
-impl<'r> Produce<'r> for Value {}
+impl<'r> Produce<'r> for SQLiteCell<'_> {}
 
-impl<'r> ProduceTy<'r, Int64Type> for Value {
+impl<'r> ProduceTy<'r, Int64Type> for SQLiteCell<'_> {
     fn produce(self) -> Result<i64, ConnectorError> {
-        unimplemented!()
+        let null_mismatch = self.null_mismatch();
+        ProduceTy::<Int64Type>::produce_opt(self)?.ok_or_else(null_mismatch)
     }
     fn produce_opt(self) -> Result<Option<i64>, ConnectorError> {
-        Ok(match self {
-            Self::Null => None,
-            Self::Integer(v) => Some(v),
-            _ => panic!("SQLite schema not inferred correctly"),
+        Ok(match self.value {
+            Value::Null => None,
+            Value::Integer(v) => Some(v),
+            _ => return Err(self.mismatch()),
         })
     }
 }
 
-impl<'r> ProduceTy<'r, Float64Type> for Value {
+impl<'r> ProduceTy<'r, Float64Type> for SQLiteCell<'_> {
     fn produce(self) -> Result<f64, ConnectorError> {
-        unimplemented!()
+        let null_mismatch = self.null_mismatch();
+        ProduceTy::<Float64Type>::produce_opt(self)?.ok_or_else(null_mismatch)
     }
     fn produce_opt(self) -> Result<Option<f64>, ConnectorError> {
-        Ok(match self {
-            Self::Null => None,
-            Self::Real(v) => Some(v),
-            _ => panic!("SQLite schema not inferred correctly"),
+        Ok(match self.value {
+            Value::Null => None,
+            Value::Real(v) => Some(v),
+            // columns declared as NUMERIC can contain integers as well
+            Value::Integer(v) if self.field.metadata().contains_key(METADATA_DB_TYPE) => {
+                Some(v as f64)
+            }
+            _ => return Err(self.mismatch()),
         })
     }
 }
 
-impl<'r> ProduceTy<'r, Utf8Type> for Value {
+impl<'r> ProduceTy<'r, Utf8Type> for SQLiteCell<'_> {
     fn produce(self) -> Result<String, ConnectorError> {
-        unimplemented!()
+        let null_mismatch = self.null_mismatch();
+        ProduceTy::<Utf8Type>::produce_opt(self)?.ok_or_else(null_mismatch)
     }
     fn produce_opt(self) -> Result<Option<String>, ConnectorError> {
-        Ok(match self {
-            Self::Null => None,
-            Self::Text(v) => Some(v),
-            _ => panic!("SQLite schema not inferred correctly"),
+        Ok(match self.value {
+            Value::Null => None,
+            Value::Text(v) => Some(v),
+            _ => return Err(self.mismatch()),
         })
     }
 }
 
-impl<'r> ProduceTy<'r, BinaryType> for Value {
+impl<'r> ProduceTy<'r, BinaryType> for SQLiteCell<'_> {
     fn produce(self) -> Result<Vec<u8>, ConnectorError> {
-        unimplemented!()
+        let null_mismatch = self.null_mismatch();
+        ProduceTy::<BinaryType>::produce_opt(self)?.ok_or_else(null_mismatch)
     }
     fn produce_opt(self) -> Result<Option<Vec<u8>>, ConnectorError> {
-        Ok(match self {
-            Self::Null => None,
-            Self::Blob(v) => Some(v),
-            _ => panic!("SQLite schema not inferred correctly"),
+        Ok(match self.value {
+            Value::Null => None,
+            Value::Blob(v) => Some(v),
+            _ => return Err(self.mismatch()),
+        })
+    }
+}
+
+impl<'r> ProduceTy<'r, BooleanType> for SQLiteCell<'_> {
+    fn produce(self) -> Result<bool, ConnectorError> {
+        let null_mismatch = self.null_mismatch();
+        ProduceTy::<BooleanType>::produce_opt(self)?.ok_or_else(null_mismatch)
+    }
+    fn produce_opt(self) -> Result<Option<bool>, ConnectorError> {
+        Ok(Some(match &self.value {
+            Value::Null => return Ok(None),
+            Value::Integer(v) => *v != 0,
+            Value::Real(v) => *v != 0.0,
+            Value::Text(v) if v.eq_ignore_ascii_case("true") => true,
+            Value::Text(v) if v.eq_ignore_ascii_case("false") => false,
+            _ => return Err(self.mismatch()),
+        }))
+    }
+}
+
+impl<'r> ProduceTy<'r, Decimal128Type> for SQLiteCell<'_> {
+    fn produce(self) -> Result<i128, ConnectorError> {
+        let null_mismatch = self.null_mismatch();
+        ProduceTy::<Decimal128Type>::produce_opt(self)?.ok_or_else(null_mismatch)
+    }
+    fn produce_opt(self) -> Result<Option<i128>, ConnectorError> {
+        let DataType::Decimal128(_, scale) = self.field.data_type() else {
+            unreachable!()
+        };
+        let factor = 10_i128.pow(*scale as u32);
+
+        Ok(Some(match &self.value {
+            Value::Null => return Ok(None),
+            Value::Integer(v) => (*v as i128)
+                .checked_mul(factor)
+                .ok_or(ConnectorError::DataOutOfRange)?,
+            Value::Real(v) => {
+                let v = (v * factor as f64).round();
+                if !v.is_finite() || v.abs() >= i128::MAX as f64 {
+                    return Err(ConnectorError::DataOutOfRange);
+                }
+                v as i128
+            }
+            Value::Text(v) => match crate::util::decimal::string_to_decimal128(v, *scale) {
+                Some(v) => v,
+                None => return Err(self.mismatch()),
+            },
+            Value::Blob(_) => return Err(self.mismatch()),
+        }))
+    }
+}
+
+impl<'r> ProduceTy<'r, Decimal256Type> for SQLiteCell<'_> {
+    fn produce(self) -> Result<i256, ConnectorError> {
+        let null_mismatch = self.null_mismatch();
+        ProduceTy::<Decimal256Type>::produce_opt(self)?.ok_or_else(null_mismatch)
+    }
+    fn produce_opt(self) -> Result<Option<i256>, ConnectorError> {
+        let DataType::Decimal256(_, scale) = self.field.data_type() else {
+            unreachable!()
+        };
+        let factor = i256::from_i128(10)
+            .checked_pow(*scale as u32)
+            .ok_or(ConnectorError::DataOutOfRange)?;
+
+        Ok(Some(match &self.value {
+            Value::Null => return Ok(None),
+            Value::Integer(v) => i256::from_i128(*v as i128)
+                .checked_mul(factor)
+                .ok_or(ConnectorError::DataOutOfRange)?,
+            Value::Real(v) => {
+                let v = (v * 10_f64.powi(*scale as i32)).round();
+                i256::from_f64(v).ok_or(ConnectorError::DataOutOfRange)?
+            }
+            Value::Text(v) => match crate::util::decimal::string_to_decimal256(v, *scale) {
+                Some(v) => v,
+                None => return Err(self.mismatch()),
+            },
+            Value::Blob(_) => return Err(self.mismatch()),
+        }))
+    }
+}
+
+/// Julian day number of 1970-01-01 00:00:00 UTC
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2440587.5;
+
+/// Formats accepted by SQLite date and time functions, without the time zone suffix.
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+];
+
+fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    // UTC is the only time zone that can be converted without information loss
+    let value = value.trim().trim_end_matches('Z');
+
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+            Some(date.and_time(NaiveTime::MIN))
         })
+}
+
+impl<'r> ProduceTy<'r, TimestampMicrosecondType> for SQLiteCell<'_> {
+    fn produce(self) -> Result<i64, ConnectorError> {
+        let null_mismatch = self.null_mismatch();
+        ProduceTy::<TimestampMicrosecondType>::produce_opt(self)?.ok_or_else(null_mismatch)
+    }
+    fn produce_opt(self) -> Result<Option<i64>, ConnectorError> {
+        Ok(Some(match &self.value {
+            Value::Null => return Ok(None),
+            // unix epoch, in seconds
+            Value::Integer(v) => v
+                .checked_mul(1_000_000)
+                .ok_or(ConnectorError::DataOutOfRange)?,
+            // julian day number
+            Value::Real(v) => {
+                let micros = ((v - UNIX_EPOCH_JULIAN_DAY) * 86_400_000_000.0).round();
+                if !micros.is_finite() || micros.abs() >= i64::MAX as f64 {
+                    return Err(ConnectorError::DataOutOfRange);
+                }
+                micros as i64
+            }
+            Value::Text(v) => match parse_datetime(v) {
+                Some(v) => v.and_utc().timestamp_micros(),
+                None => return Err(self.mismatch()),
+            },
+            Value::Blob(_) => return Err(self.mismatch()),
+        }))
+    }
+}
+
+impl<'r> ProduceTy<'r, Date32Type> for SQLiteCell<'_> {
+    fn produce(self) -> Result<i32, ConnectorError> {
+        let null_mismatch = self.null_mismatch();
+        ProduceTy::<Date32Type>::produce_opt(self)?.ok_or_else(null_mismatch)
+    }
+    fn produce_opt(self) -> Result<Option<i32>, ConnectorError> {
+        Ok(Some(match &self.value {
+            Value::Null => return Ok(None),
+            // unix epoch, in seconds
+            Value::Integer(v) => {
+                i32::try_from(v.div_euclid(86_400)).map_err(|_| ConnectorError::DataOutOfRange)?
+            }
+            // julian day number
+            Value::Real(v) => {
+                let days = (v - UNIX_EPOCH_JULIAN_DAY).floor();
+                if !days.is_finite() || days.abs() > i32::MAX as f64 {
+                    return Err(ConnectorError::DataOutOfRange);
+                }
+                days as i32
+            }
+            Value::Text(v) => match parse_datetime(v) {
+                Some(v) => Date32Type::from_naive_date(v.date()),
+                None => return Err(self.mismatch()),
+            },
+            Value::Blob(_) => return Err(self.mismatch()),
+        }))
+    }
+}
+
+impl<'r> ProduceTy<'r, Time64MicrosecondType> for SQLiteCell<'_> {
+    fn produce(self) -> Result<i64, ConnectorError> {
+        let null_mismatch = self.null_mismatch();
+        ProduceTy::<Time64MicrosecondType>::produce_opt(self)?.ok_or_else(null_mismatch)
+    }
+    fn produce_opt(self) -> Result<Option<i64>, ConnectorError> {
+        const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+        Ok(Some(match &self.value {
+            Value::Null => return Ok(None),
+            // unix epoch, in seconds
+            Value::Integer(v) => v.rem_euclid(86_400) * 1_000_000,
+            // julian day number, which starts at noon
+            Value::Real(v) => {
+                let micros = ((v - 0.5).rem_euclid(1.0) * MICROS_PER_DAY as f64).round() as i64;
+                micros % MICROS_PER_DAY
+            }
+            Value::Text(v) => match parse_time(v) {
+                Some(v) => {
+                    v.num_seconds_from_midnight() as i64 * 1_000_000 + v.nanosecond() as i64 / 1000
+                }
+                None => return Err(self.mismatch()),
+            },
+            Value::Blob(_) => return Err(self.mismatch()),
+        }))
     }
 }
 
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    let value = value.trim().trim_end_matches('Z');
+
+    ["%H:%M:%S%.f", "%H:%M"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(value, format).ok())
+        .or_else(|| parse_datetime(value).map(|v| v.time()))
+}
+
 crate::impl_produce_unsupported!(
-    Value,
+    SQLiteCell<'_>,
     (
-        BooleanType,
         Int8Type,
         Int16Type,
         Int32Type,
@@ -197,13 +495,10 @@ crate::impl_produce_unsupported!(
         Float32Type,
         TimestampSecondType,
         TimestampMillisecondType,
-        TimestampMicrosecondType,
         TimestampNanosecondType,
-        Date32Type,
         Date64Type,
         Time32SecondType,
         Time32MillisecondType,
-        Time64MicrosecondType,
         Time64NanosecondType,
         IntervalYearMonthType,
         IntervalDayTimeType,
@@ -215,7 +510,10 @@ crate::impl_produce_unsupported!(
         LargeBinaryType,
         FixedSizeBinaryType,
         LargeUtf8Type,
-        Decimal128Type,
-        Decimal256Type,
+        ListType,
+        LargeListType,
+        FixedSizeListType,
+        StructType,
+        MapType,
     )
 );
@@ -0,0 +1,182 @@
+//! Virtual table module that scans record batches registered with
+//! [SQLiteConnection::register_batches](super::SQLiteConnection::register_batches).
+
+use std::os::raw::c_int;
+use std::sync::Arc;
+
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use itertools::Itertools;
+use rusqlite::vtab::{
+    sqlite3_vtab, sqlite3_vtab_cursor, Context, CreateVTab, IndexInfo, VTab, VTabConnection,
+    VTabCursor, VTabKind, Values,
+};
+
+use super::bind::Binder;
+use super::SQLiteConnection;
+use crate::api::Connector;
+use crate::util::escape::escaped_ident;
+use crate::util::registry::{self, Owner, Registration};
+use crate::util::{ArrayCellRef, CellTransporter};
+use crate::ConnectorError;
+
+/// Name of the module, as registered in SQLite.
+pub(super) const MODULE_NAME: &str = "connector_arrow_scan";
+
+/// Stores the batches so they can be scanned with [MODULE_NAME] and returns their id.
+pub(super) fn register(
+    owner: Owner,
+    table_name: &str,
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+) -> Result<u64, ConnectorError> {
+    registry::register(owner, table_name, schema, batches, "SQLite", |ty| {
+        SQLiteConnection::type_arrow_into_db(ty).is_some()
+    })
+}
+
+#[repr(C)]
+pub(super) struct ArrowTab {
+    /// Base class, must be first.
+    base: sqlite3_vtab,
+    registration: Arc<Registration>,
+    transporters: Vec<CellTransporter<Binder<Context>>>,
+}
+
+unsafe impl<'vtab> VTab<'vtab> for ArrowTab {
+    /// Connection that registered the module, which can only scan its own batches.
+    type Aux = Owner;
+    type Cursor = ArrowTabCursor<'vtab>;
+
+    fn connect(
+        _: &mut VTabConnection,
+        owner: Option<&Owner>,
+        args: &[&[u8]],
+    ) -> rusqlite::Result<(String, Self)> {
+        // arguments are module name, database name, table name and then module arguments
+        let id = args
+            .get(3)
+            .and_then(|arg| std::str::from_utf8(arg).ok())
+            .and_then(|arg| arg.trim().parse::<u64>().ok())
+            .ok_or_else(|| rusqlite::Error::ModuleError("expected registration id".into()))?;
+
+        let registration = owner.and_then(|owner| registry::get(*owner, id));
+        let Some(registration) = registration else {
+            let message = format!("batches with id {id} are not registered");
+            return Err(rusqlite::Error::ModuleError(message));
+        };
+
+        let columns = registration
+            .schema
+            .fields()
+            .iter()
+            .map(|field| {
+                // types were checked on registration
+                let ty = SQLiteConnection::type_arrow_into_db(field.data_type()).unwrap();
+                format!("{} {ty}", escaped_ident(field.name()))
+            })
+            .join(", ");
+
+        let transporters = ArrayCellRef::transporters(registration.schema.fields())
+            .map_err(|e| rusqlite::Error::ModuleError(e.to_string()))?;
+
+        let vtab = ArrowTab {
+            base: sqlite3_vtab::default(),
+            registration,
+            transporters,
+        };
+        Ok((format!("CREATE TABLE x({columns})"), vtab))
+    }
+
+    fn best_index(&self, info: &mut IndexInfo) -> rusqlite::Result<()> {
+        // only full scans are supported
+        let rows = self
+            .registration
+            .batches
+            .iter()
+            .map(|b| b.num_rows())
+            .sum::<usize>();
+        info.set_estimated_cost(rows as f64);
+        info.set_estimated_rows(rows as i64);
+        Ok(())
+    }
+
+    fn open(&'vtab mut self) -> rusqlite::Result<ArrowTabCursor<'vtab>> {
+        Ok(ArrowTabCursor {
+            base: sqlite3_vtab_cursor::default(),
+            registration: &self.registration,
+            transporters: &self.transporters,
+            batch_index: 0,
+            row_number: 0,
+            row_id: 0,
+        })
+    }
+}
+
+impl<'vtab> CreateVTab<'vtab> for ArrowTab {
+    const KIND: VTabKind = VTabKind::Default;
+}
+
+#[repr(C)]
+pub(super) struct ArrowTabCursor<'vtab> {
+    /// Base class, must be first.
+    base: sqlite3_vtab_cursor,
+    registration: &'vtab Registration,
+    transporters: &'vtab [CellTransporter<Binder<Context>>],
+    batch_index: usize,
+    row_number: usize,
+    row_id: i64,
+}
+
+impl ArrowTabCursor<'_> {
+    /// Moves to the next batch while the current one is exhausted.
+    fn skip_exhausted(&mut self) {
+        while let Some(batch) = self.registration.batches.get(self.batch_index) {
+            if self.row_number < batch.num_rows() {
+                break;
+            }
+            self.batch_index += 1;
+            self.row_number = 0;
+        }
+    }
+}
+
+unsafe impl VTabCursor for ArrowTabCursor<'_> {
+    fn filter(&mut self, _: c_int, _: Option<&str>, _: &Values<'_>) -> rusqlite::Result<()> {
+        self.batch_index = 0;
+        self.row_number = 0;
+        self.row_id = 0;
+        self.skip_exhausted();
+        Ok(())
+    }
+
+    fn next(&mut self) -> rusqlite::Result<()> {
+        self.row_number += 1;
+        self.row_id += 1;
+        self.skip_exhausted();
+        Ok(())
+    }
+
+    fn eof(&self) -> bool {
+        self.batch_index >= self.registration.batches.len()
+    }
+
+    fn column(&self, ctx: &mut Context, i: c_int) -> rusqlite::Result<()> {
+        let batch = &self.registration.batches[self.batch_index];
+        let cell = ArrayCellRef {
+            array: batch.column(i as usize),
+            field: self.registration.schema.field(i as usize),
+            row_number: self.row_number,
+        };
+
+        let transport = self.transporters[i as usize];
+        transport(&cell, Binder::from_mut(ctx)).map_err(|e| match e {
+            ConnectorError::SQLite(e) => e,
+            e => rusqlite::Error::ModuleError(e.to_string()),
+        })
+    }
+
+    fn rowid(&self) -> rusqlite::Result<i64> {
+        Ok(self.row_id)
+    }
+}
@@ -32,11 +32,26 @@ impl SchemaGet for SQLiteConnection {
         let mut columns_res = statement.query(())?;
         // contains columns: cid, name, type, notnull, dflt_value, pk
 
-        let mut fields = Vec::new();
+        let mut columns = Vec::new();
         while let Some(row) = columns_res.next()? {
             let name: String = row.get(1)?;
             let ty: String = row.get(2)?;
             let not_null: bool = row.get(3)?;
+            columns.push((name, ty, not_null));
+        }
+
+        let mut fields = Vec::new();
+        for (name, ty, not_null) in columns {
+            // arrow types recorded by table_create
+            if let Some(recorded) = types::parse_recorded_type(&ty) {
+                fields.push(Field::new(name, recorded, !not_null));
+                continue;
+            }
+
+            if let Some(arrow_ty) = types::numeric_decl_ty_to_arrow(&ty, self.parse_temporal) {
+                fields.push(types::numeric_field(name, &ty, arrow_ty, !not_null));
+                continue;
+            }
 
             let ty = types::decl_ty_to_arrow(&ty, &name, table_name)?;
             fields.push(Field::new(name, ty, !not_null));
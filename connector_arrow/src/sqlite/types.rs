@@ -1,5 +1,13 @@
-use arrow::datatypes::DataType;
+use std::collections::HashMap;
+use std::sync::Arc;
 
+use arrow::compute::{cast_with_options, CastOptions};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use itertools::{zip_eq, Itertools};
+
+use super::SQLiteConnection;
+use crate::api::Connector;
 use crate::ConnectorError;
 
 pub fn decl_ty_to_arrow(decl_ty: &str, col: &str, table: &str) -> Result<DataType, ConnectorError> {
@@ -20,7 +28,8 @@ pub fn decl_ty_to_arrow(decl_ty: &str, col: &str, table: &str) -> Result<DataTyp
         return Ok(DataType::LargeUtf8);
     }
 
-    if ty.contains("BLOB") {
+    // columns without a declared type have BLOB affinity
+    if ty.contains("BLOB") || ty.trim().is_empty() {
         return Ok(DataType::LargeBinary);
     }
 
@@ -31,6 +40,260 @@ pub fn decl_ty_to_arrow(decl_ty: &str, col: &str, table: &str) -> Result<DataTyp
     Err(ConnectorError::IncompatibleSchema {
         table_name: table.to_string(),
         message: format!("column `{col}` was declared with type `{ty}`, which results in `NUMERIC` affinity, which is not supported."),
-        hint: Some("Supported types are INTEGER, REAL, TEXT and BLOB".to_string())
+        hint: Some("Supported types are INTEGER, REAL, TEXT, BLOB, DECIMAL(p, s), BOOLEAN, DATETIME and DATE. TIMESTAMP and TIME require SQLiteConnection::set_parse_temporal".to_string())
     })
 }
+
+/// Converts declared types that result in `NUMERIC` affinity.
+///
+/// Values in such columns are stored as INTEGER, REAL or TEXT (depending on whether they can be
+/// converted losslessly) and have to be converted into the arrow type on per-value basis.
+///
+/// `TIMESTAMP` and `TIME` are only recognized when `parse_temporal` is set,
+/// see [super::SQLiteConnection::set_parse_temporal].
+pub fn numeric_decl_ty_to_arrow(decl_ty: &str, parse_temporal: bool) -> Option<DataType> {
+    let ty = decl_ty.trim().to_ascii_uppercase();
+
+    let (name, params) = match ty.split_once('(') {
+        Some((name, params)) => (name.trim(), Some(params.strip_suffix(')')?)),
+        None => (ty.as_str(), None),
+    };
+
+    Some(match (name, params) {
+        ("DECIMAL" | "NUMERIC", Some(params)) => {
+            let (precision, scale) = match params.split_once(',') {
+                Some((p, s)) => (p.trim().parse::<u8>().ok()?, s.trim().parse::<i8>().ok()?),
+                None => (params.trim().parse::<u8>().ok()?, 0),
+            };
+            if precision == 0 || scale < 0 || scale as u8 > precision {
+                return None;
+            }
+            // SQLite stores numbers as 64-bit integers or floats,
+            // so larger precisions cannot be represented anyway
+            DataType::Decimal128(precision.min(38), scale.min(38))
+        }
+        // without static precision and scale, values can be integers or reals of any magnitude
+        ("DECIMAL" | "NUMERIC", None) => DataType::Float64,
+
+        ("BOOLEAN" | "BOOL", None) => DataType::Boolean,
+        ("DATETIME", None) => DataType::Timestamp(TimeUnit::Microsecond, None),
+        ("DATE", None) => DataType::Date32,
+        ("TIMESTAMP", None) if parse_temporal => DataType::Timestamp(TimeUnit::Microsecond, None),
+        ("TIME", None) if parse_temporal => DataType::Time64(TimeUnit::Microsecond),
+        _ => return None,
+    })
+}
+
+/// Creates a field for a column that has NUMERIC affinity. The declared type
+/// is stored in [crate::api::METADATA_DB_TYPE].
+pub fn numeric_field(name: String, decl_ty: &str, ty: DataType, nullable: bool) -> Field {
+    let metadata = HashMap::from([(
+        crate::api::METADATA_DB_TYPE.to_string(),
+        decl_ty.to_string(),
+    )]);
+    Field::new(name, ty, nullable).with_metadata(metadata)
+}
+
+/// Prefix of arrow types recorded in declared types of columns.
+const RECORDED_TYPE_PREFIX: &str = "arrow:";
+
+/// Declares a column of the given storage class that records its arrow type as a string
+/// in the declared type, i.e. `INTEGER 'arrow:i8'`.
+///
+/// SQLite keeps declared types as they were written and reports them for columns of
+/// query results, so the arrow type can be restored when the column is read.
+/// Dictionaries and views are recorded as the types they are decoded into when appended.
+pub fn declare_recorded_type(storage_class: &str, ty: &DataType) -> Option<String> {
+    let name = arrow_type_name(ty)?.replace('\'', "''");
+    Some(match ty {
+        // `NULL` would be parsed as a column constraint
+        DataType::Null => format!("'{RECORDED_TYPE_PREFIX}{name}'"),
+        _ => format!("{storage_class} '{RECORDED_TYPE_PREFIX}{name}'"),
+    })
+}
+
+/// Extracts the arrow type recorded by [declare_recorded_type] from a declared type.
+pub fn parse_recorded_type(decl_ty: &str) -> Option<DataType> {
+    let (_, name) = decl_ty.split_once(RECORDED_TYPE_PREFIX)?;
+    // SQLite unquotes declared types that consist of the string only
+    let name = name.strip_suffix('\'').unwrap_or(name).replace("''", "'");
+    parse_arrow_type_name(&name)
+}
+
+/// Names of arrow types that can be stored in SQLite.
+///
+/// SQLite determines the affinity of a column by looking for substrings such as `INT` or `TEXT`
+/// in the whole declared type, so names of types that are not stored as INTEGER must not
+/// contain any of them.
+fn arrow_type_name(ty: &DataType) -> Option<String> {
+    Some(match ty {
+        DataType::Null => "null".into(),
+        DataType::Boolean => "bool".into(),
+        DataType::Int8 => "i8".into(),
+        DataType::Int16 => "i16".into(),
+        DataType::Int32 => "i32".into(),
+        DataType::Int64 => "i64".into(),
+        DataType::UInt8 => "u8".into(),
+        DataType::UInt16 => "u16".into(),
+        DataType::UInt32 => "u32".into(),
+        DataType::UInt64 => "u64".into(),
+        DataType::Float16 => "f16".into(),
+        DataType::Float32 => "f32".into(),
+        DataType::Float64 => "f64".into(),
+
+        DataType::Timestamp(unit, None) => format!("timestamp[{}]", time_unit_name(unit)),
+        DataType::Timestamp(unit, Some(tz)) => {
+            format!("timestamp[{}, {tz}]", time_unit_name(unit))
+        }
+        DataType::Date32 => "date32".into(),
+        DataType::Date64 => "date64".into(),
+        DataType::Time32(unit) => format!("time32[{}]", time_unit_name(unit)),
+        DataType::Time64(unit) => format!("time64[{}]", time_unit_name(unit)),
+        DataType::Duration(unit) => format!("duration[{}]", time_unit_name(unit)),
+
+        DataType::Binary | DataType::BinaryView => "binary".into(),
+        DataType::LargeBinary => "large_binary".into(),
+        DataType::FixedSizeBinary(size) => format!("fixed_size_binary[{size}]"),
+        DataType::Utf8 | DataType::Utf8View => "utf8".into(),
+        DataType::LargeUtf8 => "large_utf8".into(),
+
+        DataType::Decimal128(precision, scale) => format!("decimal128({precision}, {scale})"),
+        DataType::Decimal256(precision, scale) => format!("decimal256({precision}, {scale})"),
+
+        DataType::Dictionary(_, value) => return arrow_type_name(value),
+        _ => return None,
+    })
+}
+
+fn parse_arrow_type_name(name: &str) -> Option<DataType> {
+    if let Some(params) = name.strip_suffix(']') {
+        let (name, params) = params.split_once('[')?;
+        return Some(match name {
+            "timestamp" => match params.split_once(", ") {
+                Some((unit, tz)) => {
+                    DataType::Timestamp(parse_time_unit(unit)?, Some(Arc::from(tz)))
+                }
+                None => DataType::Timestamp(parse_time_unit(params)?, None),
+            },
+            "time32" => DataType::Time32(parse_time_unit(params)?),
+            "time64" => DataType::Time64(parse_time_unit(params)?),
+            "duration" => DataType::Duration(parse_time_unit(params)?),
+            "fixed_size_binary" => DataType::FixedSizeBinary(params.parse().ok()?),
+            _ => return None,
+        });
+    }
+    if let Some(params) = name.strip_suffix(')') {
+        let (name, params) = params.split_once('(')?;
+        let (precision, scale) = params.split_once(", ")?;
+        let (precision, scale) = (precision.parse().ok()?, scale.parse().ok()?);
+        return Some(match name {
+            "decimal128" => DataType::Decimal128(precision, scale),
+            "decimal256" => DataType::Decimal256(precision, scale),
+            _ => return None,
+        });
+    }
+
+    Some(match name {
+        "null" => DataType::Null,
+        "bool" => DataType::Boolean,
+        "i8" => DataType::Int8,
+        "i16" => DataType::Int16,
+        "i32" => DataType::Int32,
+        "i64" => DataType::Int64,
+        "u8" => DataType::UInt8,
+        "u16" => DataType::UInt16,
+        "u32" => DataType::UInt32,
+        "u64" => DataType::UInt64,
+        "f16" => DataType::Float16,
+        "f32" => DataType::Float32,
+        "f64" => DataType::Float64,
+        "date32" => DataType::Date32,
+        "date64" => DataType::Date64,
+        "binary" => DataType::Binary,
+        "large_binary" => DataType::LargeBinary,
+        "utf8" => DataType::Utf8,
+        "large_utf8" => DataType::LargeUtf8,
+        _ => return None,
+    })
+}
+
+fn time_unit_name(unit: &TimeUnit) -> &'static str {
+    match unit {
+        TimeUnit::Second => "s",
+        TimeUnit::Millisecond => "ms",
+        TimeUnit::Microsecond => "us",
+        TimeUnit::Nanosecond => "ns",
+    }
+}
+
+fn parse_time_unit(unit: &str) -> Option<TimeUnit> {
+    Some(match unit {
+        "s" => TimeUnit::Second,
+        "ms" => TimeUnit::Millisecond,
+        "us" => TimeUnit::Microsecond,
+        "ns" => TimeUnit::Nanosecond,
+        _ => return None,
+    })
+}
+
+/// Converts columns that were read in their storage class into their recorded arrow types.
+///
+/// Columns that were requested as dictionaries or views are left as they are.
+pub fn cast_to_recorded(
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+    recorded_types: &[Option<DataType>],
+) -> Result<(SchemaRef, Vec<RecordBatch>), ConnectorError> {
+    let fields = zip_eq(schema.fields(), recorded_types)
+        .map(|(field, recorded)| match recorded {
+            Some(recorded) if Some(field.data_type()) == storage_type(recorded).as_ref() => {
+                Arc::new(field.as_ref().clone().with_data_type(recorded.clone()))
+            }
+            _ => field.clone(),
+        })
+        .collect_vec();
+    if zip_eq(schema.fields(), &fields).all(|(a, b)| a == b) {
+        return Ok((schema, batches));
+    }
+    let schema = Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()));
+
+    let options = CastOptions {
+        safe: false,
+        ..Default::default()
+    };
+    let batches = batches
+        .into_iter()
+        .map(|batch| {
+            let columns = zip_eq(batch.columns(), schema.fields())
+                .map(|(array, field)| {
+                    let ty = field.data_type();
+                    if array.data_type() == ty {
+                        return Ok(array.clone());
+                    }
+                    // arrow does not cast between integers of different widths and Time32
+                    let array = match ty {
+                        DataType::Time32(_) => {
+                            cast_with_options(array, &DataType::Int32, &options)?
+                        }
+                        _ => array.clone(),
+                    };
+                    cast_with_options(&array, ty, &options)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(RecordBatch::try_new(schema.clone(), columns)?)
+        })
+        .collect::<Result<_, ConnectorError>>()?;
+    Ok((schema, batches))
+}
+
+/// The type that values of a recorded type are read as, based on their storage class.
+pub fn storage_type(recorded: &DataType) -> Option<DataType> {
+    match recorded {
+        // decimals are stored as text, which is parsed directly into the recorded precision
+        DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => Some(recorded.clone()),
+        _ => {
+            let storage_class = SQLiteConnection::storage_class(recorded)?;
+            SQLiteConnection::type_db_into_arrow(storage_class)
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::{DbErrorDetails, DbErrorKind};
+
+/// See: https://learn.microsoft.com/en-us/sql/relational-databases/errors-events/database-engine-events-and-errors
+pub(crate) fn error_details(error: &tiberius::error::Error) -> Option<DbErrorDetails> {
+    let error = match error {
+        tiberius::error::Error::Server(e) => e,
+        tiberius::error::Error::Io { .. } => {
+            return Some(DbErrorDetails::new(DbErrorKind::ConnectionLost, None));
+        }
+        _ => return None,
+    };
+
+    let kind = match error.code() {
+        // incorrect syntax, invalid column name, invalid object name
+        102 | 207 | 208 => DbErrorKind::Syntax,
+        // unique index, primary key or unique constraint, foreign key or check, not null
+        2601 | 2627 | 547 | 515 => DbErrorKind::ConstraintViolation,
+        // deadlock victim, snapshot isolation update conflict
+        1205 | 3960 => DbErrorKind::SerializationFailure,
+        // lock request time out
+        1222 => DbErrorKind::Timeout,
+        _ => DbErrorKind::Other,
+    };
+    let mut details = DbErrorDetails::new(kind, Some(error.code().to_string()));
+
+    let message = error.message();
+    match error.code() {
+        // "Violation of PRIMARY KEY constraint 'pk_t'. Cannot insert duplicate key ..."
+        // "The INSERT statement conflicted with the FOREIGN KEY constraint "fk_t". ..."
+        2627 | 547 => {
+            details.constraint = message
+                .split_once(" constraint ")
+                .and_then(|(_, m)| m.get(1..)?.split_once(['\'', '"']))
+                .map(|(constraint, _)| constraint.to_string());
+        }
+        // "Cannot insert the value NULL into column 'a', table 'db.dbo.t'; ..."
+        515 => {
+            details.column = message
+                .split_once("into column '")
+                .and_then(|(_, m)| m.split_once('\''))
+                .map(|(column, _)| column.to_string());
+        }
+        _ => {}
+    }
+    Some(details)
+}
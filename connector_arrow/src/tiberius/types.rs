@@ -16,17 +16,24 @@ pub fn get_result_schema(columns: Option<&[Column]>) -> Result<SchemaRef, Connec
     for column in columns {
         let db_ty = get_name_of_column_type(&column.column_type());
 
-        fields.push(create_field(column.name().to_string(), db_ty, true));
+        fields.push(create_field(column.name().to_string(), db_ty, true)?);
     }
 
     Ok(Arc::new(Schema::new(fields)))
 }
 
-fn create_field(name: String, db_ty: &str, nullable: bool) -> Field {
+fn create_field(name: String, db_ty: &str, nullable: bool) -> Result<Field, ConnectorError> {
     let data_type = super::TiberiusConnection::<Compat<TcpStream>>::type_db_into_arrow(db_ty);
-    let data_type = data_type.unwrap_or_else(|| todo!("database type: {}", db_ty));
+    let Some(data_type) = data_type else {
+        return Err(ConnectorError::IncompatibleSchema {
+            // result columns do not carry the name of their table
+            table_name: String::new(),
+            message: format!("cannot read database type {db_ty} of column {name}"),
+            hint: None,
+        });
+    };
 
-    Field::new(name, data_type, nullable)
+    Ok(Field::new(name, data_type, nullable))
 }
 
 fn get_name_of_column_type(col_ty: &ColumnType) -> &'static str {
@@ -1,3 +1,4 @@
+use arrow::array::ArrayRef;
 use arrow::datatypes::*;
 
 /// For a given arrow type, this trait associates:
@@ -14,6 +15,17 @@ pub struct NullType;
 // arrow crate does not define fixed-sized binary array type
 pub struct FixedSizeBinaryType;
 
+// arrow crate does not define types of nested arrays.
+// Their values are represented with arrays:
+// - elements of a list,
+// - entries of a map (a struct array of keys and values),
+// - a struct array of length 1.
+pub struct ListType;
+pub struct LargeListType;
+pub struct FixedSizeListType;
+pub struct StructType;
+pub struct MapType;
+
 impl ArrowType for NullType {
     type Native = ();
 }
@@ -76,3 +88,18 @@ impl ArrowType for Utf8Type {
 impl ArrowType for LargeUtf8Type {
     type Native = String;
 }
+impl ArrowType for ListType {
+    type Native = ArrayRef;
+}
+impl ArrowType for LargeListType {
+    type Native = ArrayRef;
+}
+impl ArrowType for FixedSizeListType {
+    type Native = ArrayRef;
+}
+impl ArrowType for StructType {
+    type Native = ArrayRef;
+}
+impl ArrowType for MapType {
+    type Native = ArrayRef;
+}
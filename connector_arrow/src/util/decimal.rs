@@ -31,3 +31,59 @@ fn insert_dot(scale: &i8, value: String) -> String {
         value
     }
 }
+
+/// Parses a decimal number (i.e. `-12.345`) into an integer of a decimal with the given scale.
+/// Returns None if the string is not a number or if it has more fractional digits than the scale.
+pub fn string_to_decimal128(value: &str, scale: i8) -> Option<i128> {
+    let (negative, digits, exponent) = split_decimal(value, scale)?;
+
+    let mut res: i128 = 0;
+    for digit in digits.bytes() {
+        res = res.checked_mul(10)?.checked_add((digit - b'0') as i128)?;
+    }
+    res = res.checked_mul(10_i128.checked_pow(exponent)?)?;
+    Some(if negative { -res } else { res })
+}
+
+/// Same as [string_to_decimal128], but for 256-bit decimals.
+pub fn string_to_decimal256(value: &str, scale: i8) -> Option<i256> {
+    let (negative, digits, exponent) = split_decimal(value, scale)?;
+
+    let ten = i256::from_i128(10);
+    let mut res = i256::ZERO;
+    for digit in digits.bytes() {
+        res = res
+            .checked_mul(ten)?
+            .checked_add(i256::from_i128((digit - b'0') as i128))?;
+    }
+    res = res.checked_mul(ten.checked_pow(exponent)?)?;
+    Some(if negative { res.wrapping_neg() } else { res })
+}
+
+/// Splits a decimal number into its sign, its significant digits and
+/// the exponent of 10 that the digits have to be multiplied with to get the given scale.
+fn split_decimal(value: &str, scale: i8) -> Option<(bool, String, u32)> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(v) => (true, v),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (int, frac) = value.split_once('.').unwrap_or((value, ""));
+    if int.is_empty() && frac.is_empty() {
+        return None;
+    }
+    if !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    // trailing zeros do not contribute to the value
+    let frac = frac.trim_end_matches('0');
+    if scale < 0 || frac.len() > scale as usize {
+        return None;
+    }
+    Some((
+        negative,
+        format!("{int}{frac}"),
+        (scale as usize - frac.len()) as u32,
+    ))
+}
@@ -0,0 +1,63 @@
+//! Messages of the Flight SQL protocol that are missing from arrow-flight.
+
+use arrow_flight::sql::Any;
+use prost::Message;
+
+/// Request to ingest the record batches of a DoPut stream into a table.
+///
+/// Mirrors `CommandStatementIngest` of `FlightSql.proto`, which was added to the protocol
+/// after the arrow-flight version this crate depends on.
+#[derive(Clone, PartialEq, Message)]
+pub struct CommandStatementIngest {
+    #[prost(message, optional, tag = "1")]
+    pub table_definition_options: Option<TableDefinitionOptions>,
+    #[prost(string, tag = "2")]
+    pub table: String,
+    #[prost(string, optional, tag = "3")]
+    pub schema: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub catalog: Option<String>,
+    #[prost(bool, tag = "5")]
+    pub temporary: bool,
+    #[prost(bytes = "bytes", optional, tag = "6")]
+    pub transaction_id: Option<bytes::Bytes>,
+    #[prost(map = "string, string", tag = "1000")]
+    pub options: std::collections::HashMap<String, String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Message)]
+pub struct TableDefinitionOptions {
+    #[prost(enumeration = "TableNotExistOption", tag = "1")]
+    pub if_not_exist: i32,
+    #[prost(enumeration = "TableExistsOption", tag = "2")]
+    pub if_exists: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum TableNotExistOption {
+    Unspecified = 0,
+    Create = 1,
+    Fail = 2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum TableExistsOption {
+    Unspecified = 0,
+    Fail = 1,
+    Append = 2,
+    Replace = 3,
+}
+
+impl CommandStatementIngest {
+    pub const TYPE_URL: &'static str =
+        "type.googleapis.com/arrow.flight.protocol.sql.CommandStatementIngest";
+
+    pub fn as_any(&self) -> Any {
+        Any {
+            type_url: Self::TYPE_URL.to_string(),
+            value: self.encode_to_vec().into(),
+        }
+    }
+}
@@ -4,6 +4,12 @@
 mod arrow_reader;
 pub mod coerce;
 pub mod decimal;
+#[cfg(any(feature = "flight_sql_server", feature = "src_flight_sql"))]
+pub mod flight_sql;
+mod reader_options;
+mod record_batch_reader;
+#[cfg(any(feature = "src_sqlite", feature = "src_duckdb"))]
+pub(crate) mod registry;
 mod row_collect;
 mod row_reader;
 mod row_writer;
@@ -12,6 +18,8 @@ pub mod transport;
 pub(crate) mod escape;
 
 pub use arrow_reader::ArrowReader;
+pub use reader_options::ReaderOptions;
+pub use record_batch_reader::ResultRecordBatchReader;
 pub use row_collect::{collect_rows_to_arrow, next_batch_from_rows, CellReader, RowsReader};
-pub use row_reader::ArrayCellRef;
-pub use row_writer::ArrowRowWriter;
+pub use row_reader::{ArrayCellRef, CellTransporter};
+pub use row_writer::{dictionary_encode_schema, string_view_schema, ArrowRowWriter};
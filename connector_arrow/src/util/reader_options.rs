@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, AsArray, StringViewArray};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use itertools::zip_eq;
+
+use super::{dictionary_encode_schema, string_view_schema};
+use crate::ConnectorError;
+
+/// Options of how query results are read into arrow.
+#[derive(Debug, Clone, Default)]
+pub struct ReaderOptions {
+    /// Text columns that will be dictionary-encoded.
+    /// See [dictionary_encode_schema].
+    pub dictionary_columns: Vec<String>,
+
+    /// Read text columns as [DataType::Utf8View], which avoids copying long strings
+    /// into a contiguous buffer. Dictionary-encoded columns are left unchanged.
+    pub string_view: bool,
+}
+
+impl ReaderOptions {
+    /// Converts the schema of a query result into the schema the result will be read as.
+    pub fn apply(&self, schema: SchemaRef) -> SchemaRef {
+        let schema = dictionary_encode_schema(schema, &self.dictionary_columns);
+        if self.string_view {
+            string_view_schema(schema)
+        } else {
+            schema
+        }
+    }
+
+    /// Converts a batch into `schema`, which was obtained by [ReaderOptions::apply].
+    ///
+    /// Used by data stores that read query results into arrow themselves.
+    pub fn apply_to_batch(
+        &self,
+        schema: &SchemaRef,
+        batch: RecordBatch,
+    ) -> Result<RecordBatch, ConnectorError> {
+        if batch.schema().fields() == schema.fields() {
+            return Ok(batch);
+        }
+
+        let columns = zip_eq(batch.columns(), schema.fields())
+            .map(|(array, field)| -> Result<ArrayRef, ConnectorError> {
+                let ty = field.data_type();
+                Ok(match (array.data_type(), ty) {
+                    (from, to) if from == to => array.clone(),
+
+                    // arrow does not cast into string views
+                    (DataType::Utf8, DataType::Utf8View) => {
+                        Arc::new(array.as_string::<i32>().iter().collect::<StringViewArray>())
+                    }
+                    (DataType::LargeUtf8, DataType::Utf8View) => {
+                        Arc::new(array.as_string::<i64>().iter().collect::<StringViewArray>())
+                    }
+                    _ => cast(array, ty)?,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(RecordBatch::try_new(schema.clone(), columns)?)
+    }
+}
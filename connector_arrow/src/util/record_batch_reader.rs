@@ -0,0 +1,47 @@
+use std::marker::PhantomData;
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+
+use crate::api::ResultReader;
+use crate::errors::ConnectorError;
+
+/// Adapter of a [ResultReader] into arrow's [RecordBatchReader].
+///
+/// Created with [ResultReader::into_record_batch_reader]. Errors are converted into
+/// [ArrowError::ExternalError], except for [ConnectorError::Arrow], which is unwrapped.
+pub struct ResultRecordBatchReader<'stmt, R> {
+    schema: SchemaRef,
+    reader: R,
+    _stmt: PhantomData<&'stmt ()>,
+}
+
+impl<'stmt, R: ResultReader<'stmt>> ResultRecordBatchReader<'stmt, R> {
+    pub fn new(mut reader: R) -> Result<Self, ConnectorError> {
+        let schema = reader.get_schema()?;
+        Ok(ResultRecordBatchReader {
+            schema,
+            reader,
+            _stmt: PhantomData,
+        })
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<'stmt, R: ResultReader<'stmt>> Iterator for ResultRecordBatchReader<'stmt, R> {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.reader.next()?.map_err(ArrowError::from))
+    }
+}
+
+impl<'stmt, R: ResultReader<'stmt>> RecordBatchReader for ResultRecordBatchReader<'stmt, R> {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
@@ -0,0 +1,98 @@
+//! Record batches registered for scanning by virtual tables of embedded databases.
+//!
+//! Virtual tables only receive SQL values as arguments, so they reference
+//! the registered data by id instead of by pointer. Ids are sequential, so each registration
+//! also records the [Owner] that registered it, and only the owner can scan its batches.
+
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use arrow::datatypes::{DataType, SchemaRef};
+use arrow::record_batch::RecordBatch;
+
+use crate::ConnectorError;
+
+static REGISTRY: Mutex<BTreeMap<u64, Arc<Registration>>> = Mutex::new(BTreeMap::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static NEXT_OWNER: AtomicU64 = AtomicU64::new(0);
+
+/// Token of a connection that registers batches.
+///
+/// Tokens are random, so they cannot be guessed by other connections that share the database.
+/// They fit into a signed 64-bit integer, since some databases pass them as such.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Owner(pub u64);
+
+impl Owner {
+    pub(crate) fn new() -> Self {
+        // RandomState is seeded randomly, so the hash of a counter is unpredictable
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(NEXT_OWNER.fetch_add(1, Ordering::Relaxed));
+        Owner(hasher.finish() >> 1)
+    }
+}
+
+pub(crate) struct Registration {
+    owner: Owner,
+    pub schema: SchemaRef,
+    pub batches: Vec<RecordBatch>,
+}
+
+/// Stores the batches of the owner and returns their id.
+///
+/// Fails if any of the columns is not `is_scannable` by the data store named `db_name`,
+/// or if the batches do not match the schema.
+pub(crate) fn register(
+    owner: Owner,
+    table_name: &str,
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+    db_name: &str,
+    is_scannable: impl Fn(&DataType) -> bool,
+) -> Result<u64, ConnectorError> {
+    for field in schema.fields() {
+        if !is_scannable(field.data_type()) {
+            return Err(ConnectorError::IncompatibleSchema {
+                table_name: table_name.to_string(),
+                message: format!(
+                    "cannot scan arrow type {} of column {} in {db_name}",
+                    field.data_type(),
+                    field.name()
+                ),
+                hint: Some("cast the column to a primitive, string or binary type".into()),
+            });
+        }
+    }
+    for batch in &batches {
+        let batch_schema = batch.schema();
+        let batch_types = batch_schema.fields().iter().map(|f| f.data_type());
+        if !batch_types.eq(schema.fields().iter().map(|f| f.data_type())) {
+            return Err(ConnectorError::DataSchemaMismatch(
+                "registered batches do not match the schema".into(),
+            ));
+        }
+    }
+
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let registration = Arc::new(Registration {
+        owner,
+        schema,
+        batches,
+    });
+    REGISTRY.lock().unwrap().insert(id, registration);
+    Ok(id)
+}
+
+/// Batches with the id, unless they were registered by another owner.
+pub(crate) fn get(owner: Owner, id: u64) -> Option<Arc<Registration>> {
+    let registry = REGISTRY.lock().unwrap();
+    registry.get(&id).filter(|r| r.owner == owner).cloned()
+}
+
+/// Releases the batches of a registration. Scans that are already running keep their data.
+pub(crate) fn unregister(id: u64) {
+    REGISTRY.lock().unwrap().remove(&id);
+}
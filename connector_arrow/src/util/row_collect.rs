@@ -1,9 +1,12 @@
-use arrow::datatypes::SchemaRef;
+use std::marker::PhantomData;
+
+use arrow::datatypes::{Field, SchemaRef};
 use arrow::record_batch::RecordBatch;
-use itertools::Itertools;
+use itertools::{zip_eq, Itertools};
 
 use crate::errors::ConnectorError;
-use crate::util::{transport, ArrowRowWriter};
+use crate::util::transport::{self, Resolve, TransportTy};
+use crate::util::ArrowRowWriter;
 
 /// Get next [RecordBatch] from a row-major reader.
 pub fn next_batch_from_rows<'stmt, T: RowsReader<'stmt>>(
@@ -11,16 +14,15 @@ pub fn next_batch_from_rows<'stmt, T: RowsReader<'stmt>>(
     rows_reader: &mut T,
     batch_size: usize,
 ) -> Result<Option<RecordBatch>, ConnectorError> {
+    let transporters = cell_transporters::<T>(schema)?;
     let mut writer = ArrowRowWriter::new(schema.clone(), batch_size);
 
     for _ in 0..batch_size {
         if let Some(mut cell_reader) = rows_reader.next_row()? {
             writer.prepare_for_batch(1)?;
 
-            for field in &schema.fields {
-                let cell_ref = cell_reader.next_cell();
-
-                transport::transport(field, cell_ref.unwrap(), &mut writer)?;
+            for (field, transport) in zip_eq(&schema.fields, &transporters) {
+                transport(field, &mut cell_reader, &mut writer)?;
             }
         } else {
             break;
@@ -41,24 +43,55 @@ pub fn collect_rows_to_arrow<'stmt, T: RowsReader<'stmt>>(
     rows_reader: &mut T,
     batch_size: usize,
 ) -> Result<Vec<RecordBatch>, ConnectorError> {
+    let transporters = cell_transporters::<T>(&schema)?;
     let mut writer = ArrowRowWriter::new(schema.clone(), batch_size);
     log::debug!("reading rows");
 
-    while let Some(mut row_reader) = rows_reader.next_row()? {
+    while let Some(mut cell_reader) = rows_reader.next_row()? {
         writer.prepare_for_batch(1)?;
 
-        log::debug!("reading row");
-        for field in &schema.fields {
-            log::debug!("reading cell");
-            let cell_ref = row_reader.next_cell();
-
-            log::debug!("transporting cell: {cell_ref:?}");
-            transport::transport(field, cell_ref.unwrap(), &mut writer)?;
+        for (field, transport) in zip_eq(&schema.fields, &transporters) {
+            transport(field, &mut cell_reader, &mut writer)?;
         }
     }
     writer.finish()
 }
 
+/// Moves the next cell of a row into the writer.
+type CellTransporter<'stmt, T> = for<'row> fn(
+    &Field,
+    &mut <T as RowsReader<'stmt>>::CellReader<'row>,
+    &mut ArrowRowWriter,
+) -> Result<(), ConnectorError>;
+
+/// Resolves the type of each column once, instead of for each cell.
+fn cell_transporters<'stmt, T: RowsReader<'stmt>>(
+    schema: &SchemaRef,
+) -> Result<Vec<CellTransporter<'stmt, T>>, ConnectorError> {
+    (schema.fields().iter())
+        .map(|f| transport::resolve(f, ResolveCellTransporter::<T>(PhantomData)))
+        .collect()
+}
+
+struct ResolveCellTransporter<'stmt, T>(PhantomData<(&'stmt (), T)>);
+
+impl<'stmt, T: RowsReader<'stmt>> Resolve for ResolveCellTransporter<'stmt, T> {
+    type Output = CellTransporter<'stmt, T>;
+
+    fn resolve<Ty: TransportTy>(self, _field: &Field) -> Result<Self::Output, ConnectorError> {
+        Ok(transport_next_cell::<T, Ty>)
+    }
+}
+
+fn transport_next_cell<'stmt, 'row, T: RowsReader<'stmt>, Ty: TransportTy>(
+    field: &Field,
+    cell_reader: &mut T::CellReader<'row>,
+    writer: &mut ArrowRowWriter,
+) -> Result<(), ConnectorError> {
+    let cell_ref = cell_reader.next_cell().unwrap();
+    Ty::transport(field, cell_ref, writer)
+}
+
 /// Iterator over rows.
 // Cannot be an actual iterator, because of lifetime requirements (I think).
 pub trait RowsReader<'stmt> {
@@ -1,10 +1,15 @@
-use arrow::array::{ArrayRef, AsArray};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, AsArray, BinaryViewArray, StringViewArray};
 use arrow::datatypes::*;
 
-use crate::types::{ArrowType, FixedSizeBinaryType};
+use crate::types::{
+    ArrowType, FixedSizeBinaryType, FixedSizeListType, LargeListType, ListType, MapType, StructType,
+};
 use crate::ConnectorError;
 
-use super::transport::{Produce, ProduceTy};
+use super::transport::{self, Consume, Produce, ProduceTy, Resolve, TransportTy};
 
 #[derive(Debug)]
 pub struct ArrayCellRef<'a> {
@@ -13,6 +18,46 @@ pub struct ArrayCellRef<'a> {
     pub row_number: usize,
 }
 
+/// Moves the value of an [ArrayCellRef] into a consumer.
+/// Resolved once per column with [ArrayCellRef::transporters].
+pub type CellTransporter<C> =
+    for<'a, 'r> fn(&'a ArrayCellRef<'r>, &mut C) -> Result<(), ConnectorError>;
+
+impl ArrayCellRef<'_> {
+    /// Resolves the transport function for each of the fields, so the data type
+    /// does not have to be matched for each of the cells.
+    ///
+    /// Fails if any of the fields cannot be transported or consumed by the consumer.
+    pub fn transporters<C: Consume>(
+        fields: &Fields,
+    ) -> Result<Vec<CellTransporter<C>>, ConnectorError> {
+        fields.iter().map(|f| Self::transporter(f)).collect()
+    }
+
+    /// Resolves the transport function of a single field. See [ArrayCellRef::transporters].
+    pub fn transporter<C: Consume>(field: &Field) -> Result<CellTransporter<C>, ConnectorError> {
+        transport::resolve(field, ResolveCellTransporter(PhantomData))
+    }
+}
+
+struct ResolveCellTransporter<C>(PhantomData<C>);
+
+impl<C: Consume> Resolve for ResolveCellTransporter<C> {
+    type Output = CellTransporter<C>;
+
+    fn resolve<T: TransportTy>(self, field: &Field) -> Result<Self::Output, ConnectorError> {
+        transport::check_consumable::<T, C>(field)?;
+        Ok(transport_cell::<T, C>)
+    }
+}
+
+fn transport_cell<T: TransportTy, C: Consume>(
+    cell: &ArrayCellRef,
+    consumer: &mut C,
+) -> Result<(), ConnectorError> {
+    T::transport(cell.field, cell, consumer)
+}
+
 impl<'r> Produce<'r> for &ArrayCellRef<'r> {}
 
 impl<'r> ProduceTy<'r, BooleanType> for &ArrayCellRef<'r> {
@@ -90,6 +135,9 @@ impl_produce_ty!(
 
 impl<'r> ProduceTy<'r, BinaryType> for &ArrayCellRef<'r> {
     fn produce(self) -> Result<Vec<u8>, ConnectorError> {
+        if let Some(array) = self.array.as_any().downcast_ref::<BinaryViewArray>() {
+            return Ok(array.value(self.row_number).to_vec());
+        }
         let array = self.array.as_bytes::<BinaryType>();
         Ok(array.value(self.row_number).to_vec())
     }
@@ -131,27 +179,104 @@ impl<'r> ProduceTy<'r, FixedSizeBinaryType> for &ArrayCellRef<'r> {
 }
 impl<'r> ProduceTy<'r, Utf8Type> for &ArrayCellRef<'r> {
     fn produce(self) -> Result<String, ConnectorError> {
-        let array = self.array.as_bytes::<Utf8Type>();
-        Ok(array.value(self.row_number).to_string())
+        Ok(string_value(self.array, self.row_number).unwrap_or_default())
     }
     fn produce_opt(self) -> Result<Option<<Utf8Type as ArrowType>::Native>, ConnectorError> {
-        Ok(if self.array.is_null(self.row_number) {
-            None
-        } else {
-            Some(ProduceTy::<Utf8Type>::produce(self)?)
-        })
+        Ok(string_value(self.array, self.row_number))
     }
 }
 impl<'r> ProduceTy<'r, LargeUtf8Type> for &ArrayCellRef<'r> {
     fn produce(self) -> Result<String, ConnectorError> {
-        let array = self.array.as_bytes::<LargeUtf8Type>();
-        Ok(array.value(self.row_number).to_string())
+        Ok(string_value(self.array, self.row_number).unwrap_or_default())
     }
     fn produce_opt(self) -> Result<Option<<LargeUtf8Type as ArrowType>::Native>, ConnectorError> {
-        Ok(if self.array.is_null(self.row_number) {
-            None
-        } else {
-            Some(ProduceTy::<LargeUtf8Type>::produce(self)?)
-        })
+        Ok(string_value(self.array, self.row_number))
+    }
+}
+
+/// Reads a value of an Utf8, LargeUtf8 or Utf8View array, or of a dictionary with such values.
+/// Dictionaries are decoded, so a row is null if either its key or its value is null.
+fn string_value(array: &ArrayRef, row_number: usize) -> Option<String> {
+    if let Some(dictionary) = array.as_any_dictionary_opt() {
+        let index = dictionary_key(dictionary.keys(), row_number)?;
+        return string_value(dictionary.values(), index);
+    }
+
+    if array.is_null(row_number) {
+        return None;
+    }
+    Some(match array.data_type() {
+        DataType::LargeUtf8 => array.as_string::<i64>().value(row_number).to_string(),
+        DataType::Utf8View => {
+            let array = array.as_any().downcast_ref::<StringViewArray>().unwrap();
+            array.value(row_number).to_string()
+        }
+        _ => array.as_string::<i32>().value(row_number).to_string(),
+    })
+}
+
+fn dictionary_key(keys: &dyn Array, row_number: usize) -> Option<usize> {
+    if keys.is_null(row_number) {
+        return None;
     }
+    Some(match keys.data_type() {
+        DataType::Int8 => keys.as_primitive::<Int8Type>().value(row_number).as_usize(),
+        DataType::Int16 => keys
+            .as_primitive::<Int16Type>()
+            .value(row_number)
+            .as_usize(),
+        DataType::Int32 => keys
+            .as_primitive::<Int32Type>()
+            .value(row_number)
+            .as_usize(),
+        DataType::Int64 => keys
+            .as_primitive::<Int64Type>()
+            .value(row_number)
+            .as_usize(),
+        DataType::UInt8 => keys
+            .as_primitive::<UInt8Type>()
+            .value(row_number)
+            .as_usize(),
+        DataType::UInt16 => keys
+            .as_primitive::<UInt16Type>()
+            .value(row_number)
+            .as_usize(),
+        DataType::UInt32 => keys
+            .as_primitive::<UInt32Type>()
+            .value(row_number)
+            .as_usize(),
+        DataType::UInt64 => keys
+            .as_primitive::<UInt64Type>()
+            .value(row_number)
+            .as_usize(),
+        _ => unreachable!("dictionary keys are always integers"),
+    })
+}
+
+macro_rules! impl_produce_nested {
+    ($({ $t: ty => $value: expr })+) => {
+        $(
+            impl<'r> ProduceTy<'r, $t> for &ArrayCellRef<'r> {
+                fn produce(self) -> Result<ArrayRef, ConnectorError> {
+                    Ok(($value)(self))
+                }
+
+                fn produce_opt(self) -> Result<Option<ArrayRef>, ConnectorError> {
+                    Ok(if self.array.is_null(self.row_number) {
+                        None
+                    } else {
+                        Some(ProduceTy::<$t>::produce(self)?)
+                    })
+                }
+            }
+        )+
+    };
+}
+
+impl_produce_nested! {
+    { ListType => |c: &ArrayCellRef| c.array.as_list::<i32>().value(c.row_number) }
+    { LargeListType => |c: &ArrayCellRef| c.array.as_list::<i64>().value(c.row_number) }
+    { FixedSizeListType => |c: &ArrayCellRef| c.array.as_fixed_size_list().value(c.row_number) }
+    { StructType => |c: &ArrayCellRef| c.array.slice(c.row_number, 1) }
+    { MapType => |c: &ArrayCellRef| Arc::new(c.array.as_map().value(c.row_number)) as ArrayRef }
 }
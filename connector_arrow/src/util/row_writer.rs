@@ -1,11 +1,22 @@
 use std::any::Any;
-
-use arrow::array::{ArrayBuilder, ArrayRef, FixedSizeBinaryBuilder};
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayBuilder, ArrayRef, AsArray, BinaryBuilder, BinaryViewBuilder,
+    FixedSizeBinaryBuilder, FixedSizeListArray, LargeListArray, LargeStringBuilder,
+    LargeStringDictionaryBuilder, ListArray, MapArray, StringBuilder, StringDictionaryBuilder,
+    StringViewBuilder,
+};
+use arrow::buffer::{NullBuffer, OffsetBuffer};
 use arrow::datatypes::*;
+use arrow::error::ArrowError;
 use arrow::record_batch::RecordBatch;
 
 use crate::errors::ConnectorError;
-use crate::types::{ArrowType, FixedSizeBinaryType, NullType};
+use crate::types::{
+    ArrowType, FixedSizeBinaryType, FixedSizeListType, LargeListType, ListType, MapType, NullType,
+    StructType,
+};
 use crate::util::transport::{Consume, ConsumeTy};
 
 /// Receives values row-by-row and passes them to [ArrayBuilder]s,
@@ -19,7 +30,7 @@ pub struct ArrowRowWriter {
     receiver: Organizer,
 
     /// Array buffers.
-    builders: Option<Vec<Box<dyn ArrayBuilder>>>,
+    builders: Option<Vec<ColumnBuilder>>,
     /// Number of rows reserved to be written in by [ArrowPartitionWriter::prepare_for_batch]
     rows_reserved: usize,
     /// Number of rows allocated within builders.
@@ -62,12 +73,12 @@ impl ArrowRowWriter {
 
         let to_allocate = usize::max(row_count, self.min_batch_size);
 
-        let builders: Vec<Box<dyn ArrayBuilder>> = self
+        let builders: Vec<ColumnBuilder> = self
             .schema
             .fields
             .iter()
-            .map(|f| arrow::array::make_builder(f.data_type(), to_allocate))
-            .collect();
+            .map(|f| make_builder(f.data_type(), to_allocate))
+            .collect::<Result<_, _>>()?;
 
         self.builders = Some(builders);
         self.rows_reserved = row_count;
@@ -81,8 +92,8 @@ impl ArrowRowWriter {
         };
         let columns: Vec<ArrayRef> = builders
             .iter_mut()
-            .map(|builder| builder.finish().slice(0, self.rows_reserved))
-            .collect();
+            .map(|builder| Ok(builder.finish()?.slice(0, self.rows_reserved)))
+            .collect::<Result<_, ConnectorError>>()?;
         let rb = RecordBatch::try_new(self.schema.clone(), columns)?;
         self.data.push(rb);
         Ok(())
@@ -103,6 +114,307 @@ impl ArrowRowWriter {
 
 impl Consume for ArrowRowWriter {}
 
+/// Converts text fields with given names into `Dictionary(Int32, _)` of their type,
+/// so [ArrowRowWriter] will dictionary-encode the values of these columns.
+///
+/// Intended for columns with low cardinality (i.e. enums), where it greatly reduces memory usage.
+/// Columns are matched by name, columns that are not [DataType::Utf8] or [DataType::LargeUtf8]
+/// are left unchanged.
+///
+/// Data stores do not store dictionaries: columns of dictionaries of text are created with
+/// the type of their values and appended dictionaries are decoded into their values.
+pub fn dictionary_encode_schema(schema: SchemaRef, columns: &[String]) -> SchemaRef {
+    if columns.is_empty() {
+        return schema;
+    }
+
+    let fields: Vec<_> = (schema.fields().iter())
+        .map(|field| {
+            let is_text = matches!(field.data_type(), DataType::Utf8 | DataType::LargeUtf8);
+            if !is_text || !columns.contains(field.name()) {
+                return field.clone();
+            }
+            let ty = DataType::Dictionary(
+                Box::new(DataType::Int32),
+                Box::new(field.data_type().clone()),
+            );
+            Arc::new(field.as_ref().clone().with_data_type(ty))
+        })
+        .collect();
+    Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()))
+}
+
+/// Converts all [DataType::Utf8] and [DataType::LargeUtf8] fields into [DataType::Utf8View],
+/// so [ArrowRowWriter] will not copy long strings into a contiguous buffer.
+pub fn string_view_schema(schema: SchemaRef) -> SchemaRef {
+    let fields: Vec<_> = (schema.fields().iter())
+        .map(|field| match field.data_type() {
+            DataType::Utf8 | DataType::LargeUtf8 => {
+                Arc::new(field.as_ref().clone().with_data_type(DataType::Utf8View))
+            }
+            _ => field.clone(),
+        })
+        .collect();
+    Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()))
+}
+
+/// Builder of a column: either an arrow builder or a [NestedBuilder].
+enum ColumnBuilder {
+    Arrow(Box<dyn ArrayBuilder>),
+    Nested(NestedBuilder),
+}
+
+impl ColumnBuilder {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        match self {
+            ColumnBuilder::Arrow(builder) => builder.as_any_mut(),
+            ColumnBuilder::Nested(builder) => builder,
+        }
+    }
+
+    fn finish(&mut self) -> Result<ArrayRef, ConnectorError> {
+        match self {
+            ColumnBuilder::Arrow(builder) => Ok(builder.finish()),
+            ColumnBuilder::Nested(builder) => builder.finish(),
+        }
+    }
+}
+
+/// Like [arrow::array::make_builder], but also supports dictionaries of strings,
+/// views and builds nested arrays from array values.
+fn make_builder(data_type: &DataType, capacity: usize) -> Result<ColumnBuilder, ConnectorError> {
+    if matches!(
+        data_type,
+        DataType::List(_)
+            | DataType::LargeList(_)
+            | DataType::FixedSizeList(_, _)
+            | DataType::Struct(_)
+            | DataType::Map(_, _)
+    ) {
+        return Ok(ColumnBuilder::Nested(NestedBuilder {
+            data_type: data_type.clone(),
+            values: Vec::with_capacity(capacity),
+        }));
+    }
+    let builder = make_arrow_builder(data_type, capacity)?;
+    Ok(ColumnBuilder::Arrow(builder))
+}
+
+fn make_arrow_builder(
+    data_type: &DataType,
+    capacity: usize,
+) -> Result<Box<dyn ArrayBuilder>, ConnectorError> {
+    if let DataType::Utf8View = data_type {
+        return Ok(Box::new(StringViewBuilder::with_capacity(capacity)));
+    }
+    if let DataType::BinaryView = data_type {
+        return Ok(Box::new(BinaryViewBuilder::with_capacity(capacity)));
+    }
+
+    let DataType::Dictionary(key, value) = data_type else {
+        return Ok(arrow::array::make_builder(data_type, capacity));
+    };
+    let not_supported = || ConnectorError::NotSupported {
+        connector_name: "connector_arrow",
+        feature: format!("building arrays of {data_type}").into(),
+    };
+
+    macro_rules! dictionary_builder {
+        ($B: ident) => {
+            Ok(match **key {
+                DataType::Int8 => Box::new($B::<Int8Type>::with_capacity(capacity, 64, 1024)),
+                DataType::Int16 => Box::new($B::<Int16Type>::with_capacity(capacity, 64, 1024)),
+                DataType::Int32 => Box::new($B::<Int32Type>::with_capacity(capacity, 64, 1024)),
+                DataType::Int64 => Box::new($B::<Int64Type>::with_capacity(capacity, 64, 1024)),
+                DataType::UInt8 => Box::new($B::<UInt8Type>::with_capacity(capacity, 64, 1024)),
+                DataType::UInt16 => Box::new($B::<UInt16Type>::with_capacity(capacity, 64, 1024)),
+                DataType::UInt32 => Box::new($B::<UInt32Type>::with_capacity(capacity, 64, 1024)),
+                DataType::UInt64 => Box::new($B::<UInt64Type>::with_capacity(capacity, 64, 1024)),
+                _ => return Err(not_supported()),
+            })
+        };
+    }
+    match **value {
+        DataType::Utf8 => dictionary_builder!(StringDictionaryBuilder),
+        DataType::LargeUtf8 => dictionary_builder!(LargeStringDictionaryBuilder),
+        _ => Err(not_supported()),
+    }
+}
+
+/// Appends a value into a builder that was created for either [DataType::Utf8],
+/// [DataType::LargeUtf8], [DataType::Utf8View] or a [DataType::Dictionary] of those.
+fn append_string(builder: &mut dyn Any, value: Option<&str>) {
+    macro_rules! append_to {
+        ($builder: expr, $value: expr, ($($Key: ty,)+)) => {
+            if let Some(b) = $builder.downcast_mut::<StringBuilder>() {
+                return b.append_option($value);
+            }
+            if let Some(b) = $builder.downcast_mut::<LargeStringBuilder>() {
+                return b.append_option($value);
+            }
+            if let Some(b) = $builder.downcast_mut::<StringViewBuilder>() {
+                return b.append_option($value);
+            }
+            $(
+                if let Some(b) = $builder.downcast_mut::<StringDictionaryBuilder<$Key>>() {
+                    match $value {
+                        Some(v) => {
+                            b.append(v).expect("dictionary key overflow");
+                        }
+                        None => b.append_null(),
+                    }
+                    return;
+                }
+                if let Some(b) = $builder.downcast_mut::<LargeStringDictionaryBuilder<$Key>>() {
+                    match $value {
+                        Some(v) => {
+                            b.append(v).expect("dictionary key overflow");
+                        }
+                        None => b.append_null(),
+                    }
+                    return;
+                }
+            )+
+        };
+    }
+
+    append_to!(
+        builder,
+        value,
+        (
+            Int8Type, Int16Type, Int32Type, Int64Type, UInt8Type, UInt16Type, UInt32Type,
+            UInt64Type,
+        )
+    );
+    panic!("bad cast to StringBuilder")
+}
+
+/// Appends a value into a builder that was created for either [DataType::Binary]
+/// or [DataType::BinaryView].
+fn append_binary(builder: &mut dyn Any, value: Option<&[u8]>) {
+    if let Some(b) = builder.downcast_mut::<BinaryBuilder>() {
+        return b.append_option(value);
+    }
+    if let Some(b) = builder.downcast_mut::<BinaryViewBuilder>() {
+        return b.append_option(value);
+    }
+    panic!("bad cast to BinaryBuilder")
+}
+
+/// Builder of nested arrays (lists, structs and maps), whose values are produced as arrays.
+/// The values are concatenated when the builder is finished.
+struct NestedBuilder {
+    data_type: DataType,
+    values: Vec<Option<ArrayRef>>,
+}
+
+impl NestedBuilder {
+    /// Checks that the value can be concatenated with the other values of the column.
+    fn append(&mut self, value: Option<ArrayRef>) -> Result<(), ConnectorError> {
+        if let Some(value) = &value {
+            let (expected_ty, expected_len) = match &self.data_type {
+                DataType::List(field) | DataType::LargeList(field) | DataType::Map(field, _) => {
+                    (field.data_type(), None)
+                }
+                DataType::FixedSizeList(field, size) => (field.data_type(), Some(*size as usize)),
+                _ => (&self.data_type, Some(1)),
+            };
+            if value.data_type() != expected_ty || expected_len.is_some_and(|l| l != value.len()) {
+                return Err(ConnectorError::DataSchemaMismatch(format!(
+                    "cannot append {} values of {} into a column of {}",
+                    value.len(),
+                    value.data_type(),
+                    self.data_type
+                )));
+            }
+        }
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<ArrayRef, ConnectorError> {
+        let array = self.build()?;
+        self.values.clear();
+        Ok(array)
+    }
+
+    fn build(&self) -> Result<ArrayRef, ArrowError> {
+        let nulls: NullBuffer = self.values.iter().map(Option::is_some).collect();
+        let nulls = Some(nulls).filter(|n| n.null_count() > 0);
+
+        Ok(match &self.data_type {
+            DataType::List(field) => Arc::new(ListArray::try_new(
+                field.clone(),
+                OffsetBuffer::from_lengths(self.value_lengths()),
+                self.concat_values(field.data_type())?,
+                nulls,
+            )?),
+            DataType::LargeList(field) => Arc::new(LargeListArray::try_new(
+                field.clone(),
+                OffsetBuffer::from_lengths(self.value_lengths()),
+                self.concat_values(field.data_type())?,
+                nulls,
+            )?),
+            DataType::FixedSizeList(field, size) => {
+                // null lists still occupy `size` elements
+                let placeholder = arrow::array::new_null_array(field.data_type(), *size as usize);
+                let values = self
+                    .values
+                    .iter()
+                    .map(|v| v.as_deref().unwrap_or(placeholder.as_ref()))
+                    .collect::<Vec<_>>();
+                let values = concat(field.data_type(), &values)?;
+                Arc::new(FixedSizeListArray::try_new(
+                    field.clone(),
+                    *size,
+                    values,
+                    nulls,
+                )?)
+            }
+            DataType::Map(field, sorted) => {
+                let entries = self.concat_values(field.data_type())?;
+                Arc::new(MapArray::try_new(
+                    field.clone(),
+                    OffsetBuffer::from_lengths(self.value_lengths()),
+                    entries.as_struct().clone(),
+                    nulls,
+                    *sorted,
+                )?)
+            }
+            DataType::Struct(_) => {
+                // struct values are arrays of length 1, which contain their own validity
+                let placeholder = arrow::array::new_null_array(&self.data_type, 1);
+                let values = self
+                    .values
+                    .iter()
+                    .map(|v| v.as_deref().unwrap_or(placeholder.as_ref()))
+                    .collect::<Vec<_>>();
+                concat(&self.data_type, &values)?
+            }
+            _ => unreachable!("not a nested type: {}", self.data_type),
+        })
+    }
+
+    fn value_lengths(&self) -> impl Iterator<Item = usize> + '_ {
+        self.values
+            .iter()
+            .map(|v| v.as_ref().map_or(0, |a| a.len()))
+    }
+
+    fn concat_values(&self, data_type: &DataType) -> Result<ArrayRef, ArrowError> {
+        let values = self.values.iter().flatten().map(|a| a.as_ref());
+        concat(data_type, &values.collect::<Vec<_>>())
+    }
+}
+
+/// Like [arrow::compute::concat], but also accepts empty input.
+fn concat(data_type: &DataType, arrays: &[&dyn Array]) -> Result<ArrayRef, ArrowError> {
+    if arrays.is_empty() {
+        return Ok(arrow::array::new_empty_array(data_type));
+    }
+    arrow::compute::concat(arrays)
+}
+
 /// Determines into which column the next stream value should go.
 pub struct Organizer {
     col_count: usize,
@@ -145,18 +457,20 @@ macro_rules! impl_consume_ty {
     ($({ $ArrTy:ty => $Builder:tt } )*) => {
         $(
             impl ConsumeTy<$ArrTy> for ArrowRowWriter {
-                fn consume(&mut self, _ty: &DataType, value: <$ArrTy as ArrowType>::Native) {
+                fn consume(&mut self, _ty: &DataType, value: <$ArrTy as ArrowType>::Native) -> Result<(), ConnectorError> {
                     self.next_builder()
                         .downcast_mut::<arrow::array::builder::$Builder>()
                         .expect(concat!("bad cast to ", stringify!($Builder)))
                         .append_value(value);
+                    Ok(())
                 }
 
-                fn consume_null(&mut self) {
+                fn consume_null(&mut self) -> Result<(), ConnectorError> {
                     self.next_builder()
                         .downcast_mut::<arrow::array::builder::$Builder>()
                         .expect(concat!("bad cast to ", stringify!($Builder)))
                         .append_null();
+                    Ok(())
                 }
             }
         )+
@@ -204,12 +518,14 @@ impl_consume_ty! {
 }
 
 impl ConsumeTy<NullType> for ArrowRowWriter {
-    fn consume(&mut self, _ty: &DataType, _: ()) {
+    fn consume(&mut self, _ty: &DataType, _: ()) -> Result<(), ConnectorError> {
         self.next_builder();
+        Ok(())
     }
 
-    fn consume_null(&mut self) {
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
         self.next_builder();
+        Ok(())
     }
 }
 
@@ -217,44 +533,129 @@ macro_rules! impl_consume_ref_ty {
     ($({ $ArrTy:ty => $Builder:tt })*) => {
         $(
             impl ConsumeTy<$ArrTy> for ArrowRowWriter {
-                fn consume(&mut self, _ty: &DataType, value: <$ArrTy as ArrowType>::Native) {
+                fn consume(&mut self, _ty: &DataType, value: <$ArrTy as ArrowType>::Native) -> Result<(), ConnectorError> {
                     self.next_builder()
                         .downcast_mut::<arrow::array::builder::$Builder>()
                         .expect(concat!("bad cast to ", stringify!($Builder)))
                         .append_value(&value);
+                    Ok(())
                 }
 
-                fn consume_null(&mut self) {
+                fn consume_null(&mut self) -> Result<(), ConnectorError> {
                     self.next_builder()
                         .downcast_mut::<arrow::array::builder::$Builder>()
                         .expect(concat!("bad cast to ", stringify!($Builder)))
                         .append_null();
+                    Ok(())
                 }
             }
         )+
     };
 }
 impl_consume_ref_ty! {
-    { BinaryType          => BinaryBuilder          }
+//  { BinaryType          => BinaryBuilder          } custom impl
     { LargeBinaryType     => LargeBinaryBuilder     }
 //  { FixedSizeBinaryType => FixedSizeBinaryBuilder } custom impl
-    { Utf8Type            => StringBuilder          }
-    { LargeUtf8Type       => LargeStringBuilder     }
+//  { Utf8Type            => StringBuilder          } custom impl
+//  { LargeUtf8Type       => LargeStringBuilder     } custom impl
+}
+
+impl ConsumeTy<BinaryType> for ArrowRowWriter {
+    fn consume(
+        &mut self,
+        _ty: &DataType,
+        value: <BinaryType as ArrowType>::Native,
+    ) -> Result<(), ConnectorError> {
+        append_binary(self.next_builder(), Some(&value));
+        Ok(())
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        append_binary(self.next_builder(), None);
+        Ok(())
+    }
+}
+
+impl ConsumeTy<Utf8Type> for ArrowRowWriter {
+    fn consume(
+        &mut self,
+        _ty: &DataType,
+        value: <Utf8Type as ArrowType>::Native,
+    ) -> Result<(), ConnectorError> {
+        append_string(self.next_builder(), Some(&value));
+        Ok(())
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        append_string(self.next_builder(), None);
+        Ok(())
+    }
+}
+
+impl ConsumeTy<LargeUtf8Type> for ArrowRowWriter {
+    fn consume(
+        &mut self,
+        _ty: &DataType,
+        value: <LargeUtf8Type as ArrowType>::Native,
+    ) -> Result<(), ConnectorError> {
+        append_string(self.next_builder(), Some(&value));
+        Ok(())
+    }
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
+        append_string(self.next_builder(), None);
+        Ok(())
+    }
 }
 
 impl ConsumeTy<FixedSizeBinaryType> for ArrowRowWriter {
-    fn consume(&mut self, _ty: &DataType, value: <FixedSizeBinaryType as ArrowType>::Native) {
+    fn consume(
+        &mut self,
+        _ty: &DataType,
+        value: <FixedSizeBinaryType as ArrowType>::Native,
+    ) -> Result<(), ConnectorError> {
         self.next_builder()
             .downcast_mut::<arrow::array::builder::FixedSizeBinaryBuilder>()
             .expect(concat!("bad cast to ", stringify!(FixedSizeBinaryBuilder)))
             .append_value(&value)
             .unwrap();
+        Ok(())
     }
 
-    fn consume_null(&mut self) {
+    fn consume_null(&mut self) -> Result<(), ConnectorError> {
         self.next_builder()
             .downcast_mut::<FixedSizeBinaryBuilder>()
             .expect(concat!("bad cast to ", stringify!($Builder)))
             .append_null();
+        Ok(())
     }
 }
+
+macro_rules! impl_consume_nested_ty {
+    ($($ArrTy:ty,)+) => {
+        $(
+            impl ConsumeTy<$ArrTy> for ArrowRowWriter {
+                fn consume(&mut self, _ty: &DataType, value: <$ArrTy as ArrowType>::Native) -> Result<(), ConnectorError> {
+                    self.next_builder()
+                        .downcast_mut::<NestedBuilder>()
+                        .expect("bad cast to NestedBuilder")
+                        .append(Some(value))
+                }
+
+                fn consume_null(&mut self) -> Result<(), ConnectorError> {
+                    self.next_builder()
+                        .downcast_mut::<NestedBuilder>()
+                        .expect("bad cast to NestedBuilder")
+                        .append(None)
+                }
+            }
+        )+
+    };
+}
+impl_consume_nested_ty!(
+    ListType,
+    LargeListType,
+    FixedSizeListType,
+    StructType,
+    MapType,
+);
@@ -1,54 +1,36 @@
+use std::marker::PhantomData;
+
 use arrow::datatypes::*;
 
 /// Moving of typed values from a producer into a consumer
 use crate::errors::ConnectorError;
-use crate::types::{ArrowType, FixedSizeBinaryType, NullType};
+use crate::types::{
+    ArrowType, FixedSizeBinaryType, FixedSizeListType, LargeListType, ListType, MapType, NullType,
+    StructType,
+};
 
 macro_rules! impl_transport_match {
-    ($f: expr, $c: expr, $p: expr, $({ $Pat: pat => $ArrTy: ty })*) => {
-        let dt = $f.data_type();
-        if !$f.is_nullable() {
-            match dt {
-                Null => ConsumeTy::<NullType>::consume_null($c),
-                $(
-                    $Pat => ConsumeTy::<$ArrTy>::consume($c, dt, ProduceTy::<$ArrTy>::produce($p)?),
-                )*
-                _ => todo!("unimplemented transport of {:?}", dt),
-            }
-        } else {
-            match dt {
-                Null => ConsumeTy::<NullType>::consume_null($c),
-                $(
-                    $Pat => {
-                        if let Some(v) = ProduceTy::<$ArrTy>::produce_opt($p)? {
-                            ConsumeTy::<$ArrTy>::consume($c, dt, v)
-                        } else {
-                            ConsumeTy::<$ArrTy>::consume_null($c)
-                        }
-                    },
-                )*
-                _ => todo!("unimplemented transport of {:?}", dt),
-            }
+    ($f: expr, $r: expr, $({ $Pat: pat $(if $guard: expr)? => $ArrTy: ty })*) => {
+        match $f.data_type() {
+            Null => $r.resolve::<NullType>($f),
+            $(
+                $Pat $(if $guard)? => $r.resolve::<$ArrTy>($f),
+            )*
+            dt => Err(ConnectorError::NotSupported {
+                connector_name: "connector_arrow",
+                feature: format!("transport of column {} of type {dt}", $f.name()).into(),
+            }),
         }
     };
 }
 
-/// Take a value of type `ty` from [Produce] and insert it into [Consume].
-pub fn transport<'r, P: Produce<'r>, C: Consume>(
-    field: &Field,
-    producer: P,
-    consumer: &mut C,
-) -> Result<(), ConnectorError> {
-    log::debug!("transporting value of type {field:?}");
-
-    // TODO: connector-x goes a step further here: instead of having this match in the hot path,
-    // this function returns a transporter function that is stored in a vec, for faster lookup.
-
+/// Determines the arrow type that values of the field are transported as
+/// and passes it to the resolver.
+pub fn resolve<R: Resolve>(field: &Field, resolver: R) -> Result<R::Output, ConnectorError> {
     use DataType::*;
     impl_transport_match!(
         field,
-        consumer,
-        producer,
+        resolver,
         { Boolean => BooleanType }
         { Int8 => Int8Type }
         { Int16 => Int16Type }
@@ -83,12 +65,178 @@ pub fn transport<'r, P: Produce<'r>, C: Consume>(
         { FixedSizeBinary(_) => FixedSizeBinaryType }
         { Utf8 => Utf8Type }
         { LargeUtf8 => LargeUtf8Type }
+        // only dictionaries of Utf8 values are supported
+        { Dictionary(_, value) if matches!(**value, Utf8 | LargeUtf8 | Utf8View) => Utf8Type }
+        // views are transported as their non-view counterparts
+        { Utf8View => Utf8Type }
+        { BinaryView => BinaryType }
         { Decimal128(_, _) => Decimal128Type }
         { Decimal256(_, _) => Decimal256Type }
-    );
-    Ok(())
+        { List(_) => ListType }
+        { LargeList(_) => LargeListType }
+        { FixedSizeList(_, _) => FixedSizeListType }
+        { Struct(_) => StructType }
+        { Map(_, _) => MapType }
+    )
+}
+
+/// Receives the arrow type that values of a field are transported as. See [resolve].
+///
+/// Used to build transport functions once per column, instead of matching the
+/// data type for each transported value.
+pub trait Resolve {
+    type Output;
+
+    fn resolve<T: TransportTy>(self, field: &Field) -> Result<Self::Output, ConnectorError>;
+}
+
+/// Take a value of type `ty` from [Produce] and insert it into [Consume].
+///
+/// This matches the data type of the field on each call. When transporting many values,
+/// prefer transport functions resolved once per column (i.e. [crate::util::ArrayCellRef::transporters]).
+pub fn transport<'r, P: Produce<'r>, C: Consume>(
+    field: &Field,
+    producer: P,
+    consumer: &mut C,
+) -> Result<(), ConnectorError> {
+    resolve(
+        field,
+        Transport {
+            producer,
+            consumer,
+            _lifetime: PhantomData,
+        },
+    )
+}
+
+struct Transport<'r, 'c, P, C> {
+    producer: P,
+    consumer: &'c mut C,
+    _lifetime: PhantomData<&'r ()>,
+}
+
+impl<'r, 'c, P: Produce<'r>, C: Consume> Resolve for Transport<'r, 'c, P, C> {
+    type Output = ();
+
+    fn resolve<T: TransportTy>(self, field: &Field) -> Result<Self::Output, ConnectorError> {
+        check_consumable::<T, C>(field)?;
+        T::transport(field, self.producer, self.consumer)
+    }
+}
+
+/// Returns an error if the consumer cannot consume values of the field.
+pub fn check_consumable<T: TransportTy, C: Consume>(field: &Field) -> Result<(), ConnectorError> {
+    if T::is_consumable_by::<C>() {
+        return Ok(());
+    }
+    Err(ConnectorError::NotSupported {
+        connector_name: std::any::type_name::<C>(),
+        feature: format!(
+            "consuming column {} of type {}",
+            field.name(),
+            field.data_type()
+        )
+        .into(),
+    })
 }
 
+/// Moving of a value of an arrow type from a producer into a consumer.
+pub trait TransportTy: ArrowType {
+    fn transport<'r, P: Produce<'r>, C: Consume>(
+        field: &Field,
+        producer: P,
+        consumer: &mut C,
+    ) -> Result<(), ConnectorError>;
+
+    /// Whether the consumer supports values of this type. See [ConsumeTy::SUPPORTED].
+    fn is_consumable_by<C: Consume>() -> bool;
+}
+
+impl TransportTy for NullType {
+    fn transport<'r, P: Produce<'r>, C: Consume>(
+        _field: &Field,
+        _producer: P,
+        consumer: &mut C,
+    ) -> Result<(), ConnectorError> {
+        ConsumeTy::<NullType>::consume_null(consumer)
+    }
+
+    fn is_consumable_by<C: Consume>() -> bool {
+        <C as ConsumeTy<NullType>>::SUPPORTED
+    }
+}
+
+macro_rules! impl_transport_ty {
+    ($($ArrTy: ty,)+) => {
+        $(
+            impl TransportTy for $ArrTy {
+                fn transport<'r, P: Produce<'r>, C: Consume>(
+                    field: &Field,
+                    producer: P,
+                    consumer: &mut C,
+                ) -> Result<(), ConnectorError> {
+                    let dt = field.data_type();
+                    if !field.is_nullable() {
+                        ConsumeTy::<$ArrTy>::consume(consumer, dt, ProduceTy::<$ArrTy>::produce(producer)?)
+                    } else if let Some(v) = ProduceTy::<$ArrTy>::produce_opt(producer)? {
+                        ConsumeTy::<$ArrTy>::consume(consumer, dt, v)
+                    } else {
+                        ConsumeTy::<$ArrTy>::consume_null(consumer)
+                    }
+                }
+
+                fn is_consumable_by<C: Consume>() -> bool {
+                    <C as ConsumeTy<$ArrTy>>::SUPPORTED
+                }
+            }
+        )+
+    };
+}
+
+impl_transport_ty!(
+    BooleanType,
+    Int8Type,
+    Int16Type,
+    Int32Type,
+    Int64Type,
+    UInt8Type,
+    UInt16Type,
+    UInt32Type,
+    UInt64Type,
+    Float16Type,
+    Float32Type,
+    Float64Type,
+    TimestampSecondType,
+    TimestampMillisecondType,
+    TimestampMicrosecondType,
+    TimestampNanosecondType,
+    Date32Type,
+    Date64Type,
+    Time32SecondType,
+    Time32MillisecondType,
+    Time64MicrosecondType,
+    Time64NanosecondType,
+    IntervalYearMonthType,
+    IntervalDayTimeType,
+    IntervalMonthDayNanoType,
+    DurationSecondType,
+    DurationMillisecondType,
+    DurationMicrosecondType,
+    DurationNanosecondType,
+    BinaryType,
+    LargeBinaryType,
+    FixedSizeBinaryType,
+    Utf8Type,
+    LargeUtf8Type,
+    Decimal128Type,
+    Decimal256Type,
+    ListType,
+    LargeListType,
+    FixedSizeListType,
+    StructType,
+    MapType,
+);
+
 /// Ability to produce values of all arrow types.
 pub trait Produce<'r>:
     ProduceTy<'r, BooleanType>
@@ -127,6 +275,11 @@ pub trait Produce<'r>:
     + ProduceTy<'r, LargeUtf8Type>
     + ProduceTy<'r, Decimal128Type>
     + ProduceTy<'r, Decimal256Type>
+    + ProduceTy<'r, ListType>
+    + ProduceTy<'r, LargeListType>
+    + ProduceTy<'r, FixedSizeListType>
+    + ProduceTy<'r, StructType>
+    + ProduceTy<'r, MapType>
 {
 }
 
@@ -176,18 +329,28 @@ pub trait Consume:
     + ConsumeTy<LargeUtf8Type>
     + ConsumeTy<Decimal128Type>
     + ConsumeTy<Decimal256Type>
+    + ConsumeTy<ListType>
+    + ConsumeTy<LargeListType>
+    + ConsumeTy<FixedSizeListType>
+    + ConsumeTy<StructType>
+    + ConsumeTy<MapType>
 {
 }
 
 /// Ability to consume a value of an an arrow type
 pub trait ConsumeTy<T: ArrowType> {
-    fn consume(&mut self, ty: &DataType, value: T::Native);
+    /// Whether values of this type are supported. When false, transport functions
+    /// report an error before consuming any values.
+    const SUPPORTED: bool = true;
 
-    fn consume_null(&mut self);
+    fn consume(&mut self, ty: &DataType, value: T::Native) -> Result<(), ConnectorError>;
+
+    fn consume_null(&mut self) -> Result<(), ConnectorError>;
 }
 
 pub mod print {
     use super::{ArrowType, Consume, ConsumeTy, DataType};
+    use crate::errors::ConnectorError;
 
     pub struct PrintConsumer();
 
@@ -197,12 +360,14 @@ pub mod print {
     where
         T::Native: std::fmt::Debug,
     {
-        fn consume(&mut self, _ty: &DataType, value: T::Native) {
+        fn consume(&mut self, _ty: &DataType, value: T::Native) -> Result<(), ConnectorError> {
             println!("{}: {value:?}", std::any::type_name::<T>());
+            Ok(())
         }
 
-        fn consume_null(&mut self) {
+        fn consume_null(&mut self) -> Result<(), ConnectorError> {
             println!("{}: null", std::any::type_name::<T>());
+            Ok(())
         }
     }
 }
@@ -213,10 +378,16 @@ macro_rules! impl_produce_unsupported {
         $(
             impl<'r> $crate::util::transport::ProduceTy<'r, $t> for $p {
                 fn produce(self) -> Result<<$t as $crate::types::ArrowType>::Native, ConnectorError> {
-                   unimplemented!("unsupported");
+                    Err(ConnectorError::NotSupported {
+                        connector_name: stringify!($p),
+                        feature: concat!("producing ", stringify!($t)).into(),
+                    })
                 }
                 fn produce_opt(self) -> Result<Option<<$t as $crate::types::ArrowType>::Native>, ConnectorError> {
-                   unimplemented!("unsupported");
+                    Err(ConnectorError::NotSupported {
+                        connector_name: stringify!($p),
+                        feature: concat!("producing ", stringify!($t)).into(),
+                    })
                 }
             }
         )+
@@ -225,16 +396,35 @@ macro_rules! impl_produce_unsupported {
 
 #[macro_export]
 macro_rules! impl_consume_unsupported {
+    (@impl [$($g: tt)*] $c: ty, $t: ty) => {
+        impl<$($g)*> $crate::util::transport::ConsumeTy<$t> for $c {
+            const SUPPORTED: bool = false;
+
+            fn consume(&mut self, _ty: &arrow::datatypes::DataType, _val: <$t as $crate::types::ArrowType>::Native) -> Result<(), $crate::ConnectorError> {
+                Err($crate::ConnectorError::NotSupported {
+                    connector_name: stringify!($c),
+                    feature: concat!("consuming ", stringify!($t)).into(),
+                })
+            }
+            fn consume_null(&mut self) -> Result<(), $crate::ConnectorError> {
+                Err($crate::ConnectorError::NotSupported {
+                    connector_name: stringify!($c),
+                    feature: concat!("consuming ", stringify!($t)).into(),
+                })
+            }
+        }
+    };
+
+    // generic consumers: impl_consume_unsupported!(impl[T: Bound] Consumer<T>, (...))
+    (impl $g: tt $c: ty, ($($t: ty,)+)) => {
+        $(
+            $crate::impl_consume_unsupported!(@impl $g $c, $t);
+        )+
+    };
+
     ($c: ty, ($($t: ty,)+)) => {
         $(
-            impl $crate::util::transport::ConsumeTy<$t> for $c {
-                fn consume(&mut self, _ty: &arrow::datatypes::DataType, _val: <$t as $crate::types::ArrowType>::Native) {
-                    unimplemented!("unsupported");
-                }
-                fn consume_null(&mut self) {
-                    unimplemented!("unsupported");
-                }
-            }
+            $crate::impl_consume_unsupported!(@impl [] $c, $t);
         )+
     };
 }
@@ -1,10 +1,25 @@
 mod generator;
 mod spec;
+mod test_transport;
 mod tests;
 mod util;
 
+#[cfg(all(feature = "adbc", feature = "src_sqlite"))]
+mod test_adbc;
 #[cfg(feature = "src_duckdb")]
 mod test_duckdb;
+#[cfg(all(feature = "ffi", feature = "src_sqlite"))]
+mod test_ffi;
+#[cfg(feature = "src_file")]
+mod test_file;
+#[cfg(all(
+    feature = "src_flight_sql",
+    feature = "flight_sql_server",
+    feature = "src_sqlite"
+))]
+mod test_flight_sql;
+#[cfg(all(feature = "flight_sql_server", feature = "src_sqlite"))]
+mod test_flight_sql_server;
 #[cfg(feature = "src_mysql")]
 mod test_mysql;
 #[cfg(feature = "src_postgres")]
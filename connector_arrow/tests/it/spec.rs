@@ -247,6 +247,29 @@ pub fn binary_large() -> Vec<ColumnSpec> {
     )
 }
 
+/// Non-nullable columns of common types with more rows than fit into a single DuckDB vector.
+pub fn many_rows_non_null() -> Vec<ColumnSpec> {
+    let mut columns = domains_to_batch_spec(
+        &[
+            DataType::Boolean,
+            DataType::Int32,
+            DataType::Int64,
+            DataType::UInt64,
+            DataType::Float64,
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            DataType::Utf8,
+        ],
+        &[false],
+        &VALUE_GEN_PROCESS_ALL,
+    );
+    for column in &mut columns {
+        for values in &mut column.values {
+            values.repeat = 1000;
+        }
+    }
+    columns
+}
+
 fn domains_to_batch_spec(
     data_types_domain: &[DataType],
     is_nullable_domain: &[bool],
@@ -0,0 +1,296 @@
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::ptr::null_mut;
+use std::sync::Arc;
+
+use arrow::array::{AsArray, Int64Array, RecordBatch, StringArray, StructArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::ffi::{to_ffi, FFI_ArrowSchema};
+use arrow::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
+use arrow::util::pretty::pretty_format_batches;
+use connector_arrow::adbc::*;
+
+unsafe fn check(status: AdbcStatusCode, error: &mut AdbcError) {
+    if status != ADBC_STATUS_OK {
+        let message = CStr::from_ptr(error.message).to_string_lossy().to_string();
+        panic!("status {}: {}", status, message);
+    }
+}
+
+fn new_error() -> AdbcError {
+    AdbcError {
+        message: null_mut(),
+        vendor_code: 0,
+        sqlstate: [0; 5],
+        release: None,
+    }
+}
+
+/// Initializes the driver and opens a connection to an in-memory SQLite database.
+unsafe fn connect(err: &mut AdbcError) -> (AdbcDriver, AdbcDatabase, AdbcConnection) {
+    let mut driver = std::mem::MaybeUninit::<AdbcDriver>::uninit();
+    check(
+        AdbcDriverInit(ADBC_VERSION_1_0_0, driver.as_mut_ptr() as *mut _, err),
+        err,
+    );
+    let driver = driver.assume_init();
+
+    let mut db = AdbcDatabase {
+        private_data: null_mut(),
+        private_driver: null_mut(),
+    };
+    let key = CString::new("uri").unwrap();
+    let uri = CString::new("sqlite://:memory:").unwrap();
+    check(driver.DatabaseNew.unwrap()(&mut db, err), err);
+    check(
+        driver.DatabaseSetOption.unwrap()(&mut db, key.as_ptr(), uri.as_ptr(), err),
+        err,
+    );
+    check(driver.DatabaseInit.unwrap()(&mut db, err), err);
+
+    let mut conn = AdbcConnection {
+        private_data: null_mut(),
+        private_driver: null_mut(),
+    };
+    check(driver.ConnectionNew.unwrap()(&mut conn, err), err);
+    check(driver.ConnectionInit.unwrap()(&mut conn, &mut db, err), err);
+    (driver, db, conn)
+}
+
+unsafe fn new_statement(
+    driver: &AdbcDriver,
+    conn: &mut AdbcConnection,
+    err: &mut AdbcError,
+) -> AdbcStatement {
+    let mut stmt = AdbcStatement {
+        private_data: null_mut(),
+        private_driver: null_mut(),
+    };
+    check(driver.StatementNew.unwrap()(conn, &mut stmt, err), err);
+    stmt
+}
+
+unsafe fn bind(
+    driver: &AdbcDriver,
+    stmt: &mut AdbcStatement,
+    batch: RecordBatch,
+    err: &mut AdbcError,
+) {
+    let (mut array, mut ffi_schema) = to_ffi(&StructArray::from(batch).into()).unwrap();
+    check(
+        driver.StatementBind.unwrap()(stmt, &mut array, &mut ffi_schema, err),
+        err,
+    );
+}
+
+unsafe fn execute_query(
+    driver: &AdbcDriver,
+    stmt: &mut AdbcStatement,
+    query: &str,
+    err: &mut AdbcError,
+) -> FFI_ArrowArrayStream {
+    let query = CString::new(query).unwrap();
+    check(
+        driver.StatementSetSqlQuery.unwrap()(stmt, query.as_ptr(), err),
+        err,
+    );
+    let mut stream = FFI_ArrowArrayStream::empty();
+    check(
+        driver.StatementExecuteQuery.unwrap()(stmt, &mut stream, null_mut(), err),
+        err,
+    );
+    stream
+}
+
+fn read_stream(stream: FFI_ArrowArrayStream) -> Vec<RecordBatch> {
+    ArrowArrayStreamReader::try_new(stream)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+#[test]
+fn driver() {
+    unsafe {
+        let mut error = new_error();
+        let err = &mut error as *mut AdbcError;
+        let (driver, mut db, mut conn) = connect(&mut *err);
+        let mut stmt = new_statement(&driver, &mut conn, &mut *err);
+
+        // bulk ingest
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec!["x", "y", "z"])),
+            ],
+        )
+        .unwrap();
+        let key = CString::new("adbc.ingest.target_table").unwrap();
+        let target = CString::new("adbc_ingest").unwrap();
+        check(
+            driver.StatementSetOption.unwrap()(&mut stmt, key.as_ptr(), target.as_ptr(), err),
+            &mut *err,
+        );
+        bind(&driver, &mut stmt, batch, &mut *err);
+        let mut rows_affected = 0;
+        check(
+            driver.StatementExecuteQuery.unwrap()(&mut stmt, null_mut(), &mut rows_affected, err),
+            &mut *err,
+        );
+        assert_eq!(rows_affected, 3);
+
+        // query
+        let stream = execute_query(
+            &driver,
+            &mut stmt,
+            "SELECT b FROM adbc_ingest WHERE a = 2",
+            &mut *err,
+        );
+        let batches = read_stream(stream);
+        similar_asserts::assert_eq!(
+            pretty_format_batches(&batches).unwrap().to_string(),
+            "+---+\n| b |\n+---+\n| y |\n+---+"
+        );
+
+        // errors are reported with details of the database
+        let query = CString::new("SELEC 1").unwrap();
+        check(
+            driver.StatementSetSqlQuery.unwrap()(&mut stmt, query.as_ptr(), err),
+            &mut *err,
+        );
+        let mut stream = FFI_ArrowArrayStream::empty();
+        let status = driver.StatementExecuteQuery.unwrap()(&mut stmt, &mut stream, null_mut(), err);
+        assert_eq!(status, ADBC_STATUS_INVALID_ARGUMENT);
+        let message = CStr::from_ptr(error.message).to_string_lossy().to_string();
+        assert!(message.contains("syntax error"), "{}", message);
+        error.release.unwrap()(err);
+        assert!(error.message.is_null());
+
+        check(driver.StatementRelease.unwrap()(&mut stmt, err), &mut *err);
+
+        // introspection
+        let pattern = CString::new("adbc%").unwrap();
+        let mut stream = FFI_ArrowArrayStream::empty();
+        check(
+            driver.ConnectionGetObjects.unwrap()(
+                &mut conn,
+                ADBC_OBJECT_DEPTH_ALL,
+                std::ptr::null(),
+                std::ptr::null(),
+                pattern.as_ptr(),
+                std::ptr::null(),
+                std::ptr::null(),
+                &mut stream,
+                err,
+            ),
+            &mut *err,
+        );
+        let batches = read_stream(stream);
+        let db_schemas = batches[0].column(1).as_list::<i32>().value(0);
+        let tables = db_schemas.as_struct().column(1).as_list::<i32>().value(0);
+        let tables = tables.as_struct();
+        assert_eq!(tables.column(0).as_string::<i32>().value(0), "adbc_ingest");
+        let columns = tables.column(2).as_list::<i32>().value(0);
+        let column_names = columns.as_struct().column(0).as_string::<i32>();
+        assert_eq!(
+            column_names.iter().collect::<Vec<_>>(),
+            [Some("a"), Some("b")]
+        );
+
+        let mut ffi_schema = FFI_ArrowSchema::empty();
+        check(
+            driver.ConnectionGetTableSchema.unwrap()(
+                &mut conn,
+                std::ptr::null(),
+                std::ptr::null(),
+                target.as_ptr(),
+                &mut ffi_schema,
+                err,
+            ),
+            &mut *err,
+        );
+        let table_schema = Schema::try_from(&ffi_schema).unwrap();
+        assert_eq!(table_schema.field(0).name(), "a");
+        assert_eq!(table_schema.field(1).name(), "b");
+
+        check(driver.ConnectionRelease.unwrap()(&mut conn, err), &mut *err);
+        check(driver.DatabaseRelease.unwrap()(&mut db, err), &mut *err);
+    }
+}
+
+#[test]
+fn query_with_params() {
+    unsafe {
+        let mut error = new_error();
+        let err = &mut error as *mut AdbcError;
+        let (driver, mut db, mut conn) = connect(&mut *err);
+        let mut stmt = new_statement(&driver, &mut conn, &mut *err);
+
+        // the query is executed once for each row of the parameters
+        // (SQLite statements do not use the values of parameters)
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+        let batch =
+            RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(vec![1, 2, 3]))]).unwrap();
+        bind(&driver, &mut stmt, batch, &mut *err);
+        let stream = execute_query(&driver, &mut stmt, "SELECT 10 AS a", &mut *err);
+        let batches = read_stream(stream);
+        similar_asserts::assert_eq!(
+            pretty_format_batches(&batches).unwrap().to_string(),
+            "+----+\n| a  |\n+----+\n| 10 |\n| 10 |\n| 10 |\n+----+"
+        );
+
+        check(driver.StatementRelease.unwrap()(&mut stmt, err), &mut *err);
+        check(driver.ConnectionRelease.unwrap()(&mut conn, err), &mut *err);
+        check(driver.DatabaseRelease.unwrap()(&mut db, err), &mut *err);
+    }
+}
+
+#[test]
+fn result_stream_borrows_connection() {
+    unsafe {
+        let mut error = new_error();
+        let err = &mut error as *mut AdbcError;
+        let (driver, mut db, mut conn) = connect(&mut *err);
+        let mut stmt = new_statement(&driver, &mut conn, &mut *err);
+
+        let stream = execute_query(&driver, &mut stmt, "SELECT 1 AS a", &mut *err);
+
+        // the connection cannot be used while the stream is not released
+        let query = CString::new("SELECT 2 AS a").unwrap();
+        check(
+            driver.StatementSetSqlQuery.unwrap()(&mut stmt, query.as_ptr(), err),
+            &mut *err,
+        );
+        let mut other = FFI_ArrowArrayStream::empty();
+        let status = driver.StatementExecuteQuery.unwrap()(&mut stmt, &mut other, null_mut(), err);
+        assert_eq!(status, ADBC_STATUS_INVALID_STATE);
+        error.release.unwrap()(err);
+
+        let status = driver.ConnectionRelease.unwrap()(&mut conn, err);
+        assert_eq!(status, ADBC_STATUS_INVALID_STATE);
+        error.release.unwrap()(err);
+
+        let batches = read_stream(stream);
+        similar_asserts::assert_eq!(
+            pretty_format_batches(&batches).unwrap().to_string(),
+            "+---+\n| a |\n+---+\n| 1 |\n+---+"
+        );
+
+        // released streams give the connection back
+        let stream = execute_query(&driver, &mut stmt, "SELECT 2 AS a", &mut *err);
+        let batches = read_stream(stream);
+        similar_asserts::assert_eq!(
+            pretty_format_batches(&batches).unwrap().to_string(),
+            "+---+\n| a |\n+---+\n| 2 |\n+---+"
+        );
+
+        check(driver.StatementRelease.unwrap()(&mut stmt, err), &mut *err);
+        check(driver.ConnectionRelease.unwrap()(&mut conn, err), &mut *err);
+        check(driver.DatabaseRelease.unwrap()(&mut db, err), &mut *err);
+    }
+}
@@ -1,11 +1,26 @@
-use super::spec;
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::compute::concat_batches;
+use arrow::datatypes::{
+    DataType, Field, Fields, IntervalMonthDayNanoType, IntervalUnit, Schema, TimeUnit,
+};
+use arrow::util::pretty::pretty_format_batches;
+use connector_arrow::api::{Append, Connector, ResultReader, SchemaEdit, SchemaGet, Statement};
+use connector_arrow::duckdb::DuckDBConnection;
+use connector_arrow::util::ReaderOptions;
+use connector_arrow::{DbErrorKind, TableCreateError, TableDropError};
+use rand::SeedableRng;
 use rstest::*;
 
-fn init() -> connector_arrow::duckdb::DuckDBConnection {
+use super::generator::generate_batch;
+use super::spec;
+
+fn init() -> DuckDBConnection {
     let _ = env_logger::builder().is_test(true).try_init();
 
     let conn = duckdb::Connection::open_in_memory().unwrap();
-    connector_arrow::duckdb::DuckDBConnection::new(conn)
+    DuckDBConnection::new(conn)
 }
 
 #[test]
@@ -14,17 +29,20 @@ fn query_01() {
     super::tests::query_01(&mut conn);
 }
 
+#[test]
+fn record_batch_reader() {
+    let mut conn = init();
+    super::tests::record_batch_reader(&mut conn);
+}
+
 #[rstest]
 #[case::empty("roundtrip::empty", spec::empty())]
 #[case::null_bool("roundtrip::null_bool", spec::null_bool())]
 #[case::int("roundtrip::int", spec::int())]
 #[case::uint("roundtrip::uint", spec::uint())]
 #[case::float("roundtrip::float", spec::float())]
-// #[case::decimal("roundtrip::decimal", spec::decimal())]
+#[case::decimal("roundtrip::decimal", spec::decimal())]
 #[case::timestamp("roundtrip::timestamp", spec::timestamp())]
-// #[case::date("roundtrip::date", spec::date())]
-// #[case::time("roundtrip::time", spec::time())]
-// #[case::duration("roundtrip::duration", spec::duration())]
 // #[case::interval("roundtrip::interval", spec::interval())]
 #[case::utf8("roundtrip::utf8", spec::utf8_large())]
 #[case::binary("roundtrip::binary", spec::binary_large())]
@@ -33,6 +51,193 @@ fn roundtrip(#[case] table_name: &str, #[case] spec: spec::ArrowGenSpec) {
     super::tests::roundtrip(&mut conn, table_name, spec, '"', false);
 }
 
+#[test]
+fn roundtrip_many_rows() {
+    let mut conn = init();
+    let spec = spec::many_rows_non_null();
+    super::tests::roundtrip_many_rows(&mut conn, "roundtrip::many_rows", spec, '"', false);
+}
+
+#[test]
+fn roundtrip_temporal() {
+    let table_name = "roundtrip_temporal";
+    let mut conn = init();
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("date32", DataType::Date32, true),
+        Field::new("date64", DataType::Date64, true),
+        Field::new("time32", DataType::Time32(TimeUnit::Second), true),
+        Field::new("time64", DataType::Time64(TimeUnit::Nanosecond), true),
+        Field::new("duration", DataType::Duration(TimeUnit::Millisecond), true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Date32Array::from(vec![
+            Some(0),
+            Some(19776),
+            Some(-1000000),
+            None,
+        ])),
+        Arc::new(Date64Array::from(vec![
+            Some(0),
+            Some(1708646400000),
+            Some(-86400000000000),
+            None,
+        ])),
+        Arc::new(Time32SecondArray::from(vec![
+            Some(0),
+            Some(62316),
+            Some(86399),
+            None,
+        ])),
+        Arc::new(Time64NanosecondArray::from(vec![
+            Some(0),
+            Some(62316789123456),
+            Some(86399999999999),
+            None,
+        ])),
+        Arc::new(DurationMillisecondArray::from(vec![
+            Some(0),
+            Some(-1500),
+            Some(90061001),
+            None,
+        ])),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+    super::util::load_into_table(&mut conn, schema, &[batch], table_name).unwrap();
+
+    // dates are stored as days, times as microseconds, durations as their units
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("date32", DataType::Date32, true),
+        Field::new("date64", DataType::Date32, true),
+        Field::new("time32", DataType::Time64(TimeUnit::Microsecond), true),
+        Field::new("time64", DataType::Time64(TimeUnit::Microsecond), true),
+        Field::new("duration", DataType::Int64, true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Date32Array::from(vec![
+            Some(0),
+            Some(19776),
+            Some(-1000000),
+            None,
+        ])),
+        Arc::new(Date32Array::from(vec![
+            Some(0),
+            Some(19776),
+            Some(-1000000),
+            None,
+        ])),
+        Arc::new(Time64MicrosecondArray::from(vec![
+            Some(0),
+            Some(62316000000),
+            Some(86399000000),
+            None,
+        ])),
+        Arc::new(Time64MicrosecondArray::from(vec![
+            Some(0),
+            Some(62316789123),
+            Some(86399999999),
+            None,
+        ])),
+        Arc::new(Int64Array::from(vec![
+            Some(0),
+            Some(-1500),
+            Some(90061001),
+            None,
+        ])),
+    ];
+    let expected = RecordBatch::try_new(schema, columns).unwrap();
+
+    let (_, batches) = super::util::query_table(&mut conn, table_name, '"').unwrap();
+    similar_asserts::assert_eq!(vec![expected], batches);
+}
+
+#[test]
+fn roundtrip_interval() {
+    let table_name = "roundtrip_interval";
+    let mut conn = init();
+
+    let interval = IntervalMonthDayNanoType::make_value;
+    let schema = Arc::new(Schema::new(vec![Field::new(
+        "interval",
+        DataType::Interval(IntervalUnit::MonthDayNano),
+        true,
+    )]));
+    let array = IntervalMonthDayNanoArray::from(vec![
+        Some(interval(0, 0, 0)),
+        Some(interval(-1, 2, -1_500_000_000)),
+        Some(interval(14, 31, 90_061_001_000_001)),
+        None,
+    ]);
+    let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(array)]).unwrap();
+    super::util::load_into_table(&mut conn, schema.clone(), &[batch], table_name).unwrap();
+
+    // intervals are stored in microseconds
+    let array = IntervalMonthDayNanoArray::from(vec![
+        Some(interval(0, 0, 0)),
+        Some(interval(-1, 2, -1_500_000_000)),
+        Some(interval(14, 31, 90_061_001_000_000)),
+        None,
+    ]);
+    let expected = RecordBatch::try_new(schema, vec![Arc::new(array)]).unwrap();
+
+    let (_, batches) = super::util::query_table(&mut conn, table_name, '"').unwrap();
+    similar_asserts::assert_eq!(vec![expected], batches);
+}
+
+#[rstest]
+#[case::second(TimeUnit::Second, None, "TIMESTAMP_S")]
+#[case::millisecond(TimeUnit::Millisecond, None, "TIMESTAMP_MS")]
+#[case::microsecond(TimeUnit::Microsecond, None, "TIMESTAMP")]
+#[case::nanosecond(TimeUnit::Nanosecond, None, "TIMESTAMP_NS")]
+#[case::microsecond_tz(TimeUnit::Microsecond, Some("UTC"), "TIMESTAMP WITH TIME ZONE")]
+fn roundtrip_timestamp(#[case] unit: TimeUnit, #[case] tz: Option<&str>, #[case] db_ty: &str) {
+    let table_name = "roundtrip_timestamp";
+    let mut conn = init();
+
+    let ty = DataType::Timestamp(unit, tz.map(Arc::from));
+    let schema = Arc::new(Schema::new(vec![Field::new("t", ty.clone(), true)]));
+    let values = vec![Some(0), Some(-1), Some(1708646400123), None];
+    let array = arrow::compute::cast(&Int64Array::from(values), &ty).unwrap();
+    let batch = RecordBatch::try_new(schema.clone(), vec![array]).unwrap();
+    super::util::load_into_table(&mut conn, schema, std::slice::from_ref(&batch), table_name)
+        .unwrap();
+
+    let (_, batches) = super::util::query_table(&mut conn, table_name, '"').unwrap();
+    similar_asserts::assert_eq!(vec![batch], batches);
+
+    let mut stmt = conn
+        .query(&format!(
+            "SELECT data_type FROM information_schema.columns WHERE table_name = '{table_name}'"
+        ))
+        .unwrap();
+    let batches: Vec<_> = stmt.start([]).unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(batches[0].column(0).as_string::<i32>().value(0), db_ty);
+}
+
+#[rstest]
+#[case::date(DataType::Date32, Arc::new(Date32Array::from(vec![i32::MAX])))]
+#[case::time(DataType::Time32(TimeUnit::Second), Arc::new(Time32SecondArray::from(vec![86400])))]
+#[case::time_nanosecond(
+    DataType::Time64(TimeUnit::Nanosecond),
+    Arc::new(Time64NanosecondArray::from(vec![-1000]))
+)]
+fn append_temporal_out_of_range(#[case] ty: DataType, #[case] array: ArrayRef) {
+    let table_name = "append_temporal_out_of_range";
+    let mut conn = init();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("t", ty, true)]));
+    super::util::load_into_table(&mut conn, schema.clone(), &[], table_name).unwrap();
+
+    let batch = RecordBatch::try_new(schema, vec![array]).unwrap();
+    let mut appender = conn.append(table_name).unwrap();
+    let err = appender.append(batch).unwrap_err();
+    assert!(
+        matches!(err, connector_arrow::ConnectorError::DataOutOfRange),
+        "{:?}",
+        err
+    );
+}
+
 #[test]
 fn schema_get() {
     let table_name = "schema_get";
@@ -49,6 +254,100 @@ fn schema_edit() {
     super::tests::schema_edit(&mut conn, table_name, spec::basic_types());
 }
 
+#[test]
+fn schema_create_unsupported() {
+    let mut conn = init();
+    super::tests::schema_create_unsupported(
+        &mut conn,
+        "schema_create_unsupported",
+        DataType::Interval(IntervalUnit::DayTime),
+    );
+}
+
+#[test]
+fn append_unsupported() {
+    let mut conn = init();
+    super::tests::append_unsupported(
+        &mut conn,
+        "append_unsupported",
+        DataType::Interval(IntervalUnit::DayTime),
+    );
+}
+
+#[test]
+fn append_nulls() {
+    let mut conn = init();
+    let table_name = "append_nulls";
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("a", DataType::Int64, true),
+        Field::new("b", DataType::Utf8, true),
+    ]));
+    let batch = |a: Vec<Option<i64>>, b: Vec<Option<&str>>| {
+        RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(a)),
+                Arc::new(StringArray::from(b)),
+            ],
+        )
+        .unwrap()
+    };
+    // batches with and without nulls are appended differently, but keep their order
+    let batches = vec![
+        batch(vec![Some(1), Some(2)], vec![Some("x"), Some("y")]),
+        batch(vec![None, Some(4)], vec![Some("z"), None]),
+        batch(vec![Some(5)], vec![Some("w")]),
+    ];
+
+    conn.table_create(table_name, schema.clone()).unwrap();
+    let mut appender = conn.append(table_name).unwrap();
+    for batch in batches.clone() {
+        appender.append(batch).unwrap();
+    }
+    appender.finish().unwrap();
+
+    let (_, batches_query) = super::util::query_table(&mut conn, table_name, '"').unwrap();
+    similar_asserts::assert_eq!(
+        concat_batches(&schema, &batches).unwrap(),
+        concat_batches(&schema, &batches_query).unwrap()
+    );
+}
+
+#[test]
+fn db_errors() {
+    let mut conn = init();
+    let [syntax, unique, not_null] = super::tests::db_errors(&mut conn, "db_errors");
+
+    assert_eq!(syntax.kind, DbErrorKind::Syntax);
+    assert_eq!(syntax.code.as_deref(), Some("Parser"));
+
+    assert_eq!(unique.kind, DbErrorKind::ConstraintViolation);
+
+    assert_eq!(not_null.kind, DbErrorKind::ConstraintViolation);
+    assert_eq!(not_null.column.as_deref(), Some("db_errors.a"));
+}
+
+#[test]
+fn catalog_errors() {
+    let table_name = "catalog_errors";
+    let mut conn = init();
+
+    let err = connector_arrow::query(&mut conn, "SELECT * FROM catalog_errors").unwrap_err();
+    let details = err.db_error().unwrap();
+    assert_eq!(details.kind, DbErrorKind::Syntax);
+    assert_eq!(details.code.as_deref(), Some("Catalog"));
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    conn.table_create(table_name, schema.clone()).unwrap();
+    let err = conn.table_create(table_name, schema).unwrap_err();
+    assert!(matches!(err, TableCreateError::TableExists), "{:?}", err);
+
+    conn.table_drop(table_name).unwrap();
+    let err = conn.table_drop(table_name).unwrap_err();
+    assert!(matches!(err, TableDropError::TableNonexistent), "{:?}", err);
+}
+
 #[test]
 fn ident_escaping() {
     let table_name = "simple::ident_escaping";
@@ -56,3 +355,310 @@ fn ident_escaping() {
     let mut conn = init();
     super::tests::ident_escaping(&mut conn, table_name);
 }
+
+#[test]
+fn dyn_connector() {
+    let mut conn = connector_arrow::connect("duckdb://:memory:").unwrap();
+    super::tests::dyn_connector(conn.as_mut(), "dyn_connector");
+}
+
+#[rstest]
+#[case::hugeint("HUGEINT")]
+#[case::decimal("DECIMAL(10,2)")]
+#[case::decimal_max("DECIMAL(38,38)")]
+#[case::uuid("UUID")]
+#[case::timestamptz("TIMESTAMPTZ")]
+#[case::timestamp_ns("TIMESTAMP_NS")]
+#[case::timestamp_ms("TIMESTAMP_MS")]
+#[case::timestamp_s("TIMESTAMP_S")]
+#[case::date("DATE")]
+#[case::time("TIME")]
+#[case::interval("INTERVAL")]
+#[case::list("INTEGER[]")]
+#[case::list_nested("VARCHAR[][]")]
+#[case::array("DOUBLE[3]")]
+#[case::struct_("STRUCT(a INTEGER, \"b c\" VARCHAR[])")]
+#[case::map("MAP(VARCHAR, DECIMAL(5,1))")]
+#[case::enum_("ENUM('sad', 'ok', 'happy')")]
+fn type_db_into_arrow(#[case] db_ty: &str) {
+    let mut conn = init();
+
+    let mut stmt = conn
+        .query(&format!("SELECT CAST(NULL AS {db_ty}) AS a"))
+        .unwrap();
+    let schema = stmt.start([]).unwrap().get_schema().unwrap();
+
+    let expected: Option<DataType> = DuckDBConnection::type_db_into_arrow(db_ty);
+    assert_eq!(Some(schema.field(0).data_type()), expected.as_ref());
+}
+
+#[test]
+fn table_create_nested() {
+    let table_name = "table_create_nested";
+    let mut conn = init();
+
+    let item = Arc::new(Field::new("item", DataType::Int32, true));
+    let entries = Field::new(
+        "entries",
+        DataType::Struct(Fields::from(vec![
+            Field::new("key", DataType::Utf8, false),
+            Field::new("value", DataType::Float64, true),
+        ])),
+        false,
+    );
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("list", DataType::List(item.clone()), true),
+        Field::new("fixed", DataType::FixedSizeList(item.clone(), 2), true),
+        Field::new(
+            "struct",
+            DataType::Struct(Fields::from(vec![
+                Field::new("a", DataType::Utf8, true),
+                Field::new("b c", DataType::LargeList(item), true),
+            ])),
+            true,
+        ),
+        Field::new("map", DataType::Map(Arc::new(entries), false), true),
+    ]));
+
+    let _ = conn.table_drop(table_name);
+    conn.table_create(table_name, schema.clone()).unwrap();
+
+    let introspected = conn.table_get(table_name).unwrap();
+    for (field, introspected) in schema.fields().iter().zip(introspected.fields()) {
+        let db_ty = DuckDBConnection::type_arrow_into_db(field.data_type()).unwrap();
+        let expected = DuckDBConnection::type_db_into_arrow(&db_ty).unwrap();
+        assert_eq!(introspected.data_type(), &expected);
+    }
+}
+
+#[test]
+fn roundtrip_containers() {
+    let table_name = "roundtrip_containers";
+    let mut conn = init();
+
+    let batch = super::util::containers_batch();
+    super::util::load_into_table(
+        &mut conn,
+        batch.schema(),
+        std::slice::from_ref(&batch),
+        table_name,
+    )
+    .unwrap();
+    conn.register_batches("containers", batch.schema(), vec![batch.clone()])
+        .unwrap();
+
+    // DuckDB names list items and map entries differently, so only the values are compared
+    let expected = pretty_format_batches(&[batch]).unwrap().to_string();
+    for name in [table_name, "containers"] {
+        let (_, batches) = super::util::query_table(&mut conn, name, '"').unwrap();
+        let actual = pretty_format_batches(&batches).unwrap().to_string();
+        similar_asserts::assert_eq!(expected, actual);
+    }
+}
+
+#[test]
+fn append_nested_mixed() {
+    let table_name = "append_nested_mixed";
+    let mut conn = init();
+
+    let list = ListArray::from_iter_primitive::<arrow::datatypes::Int32Type, _, _>(vec![
+        Some(vec![Some(1), None]),
+        None,
+    ]);
+    let float = Float16Array::from(vec![Some(half::f16::from_f32(1.5)), None]);
+    let binary = FixedSizeBinaryArray::try_from_iter(vec![[1u8, 2], [3, 4]].into_iter()).unwrap();
+    let batch = RecordBatch::try_from_iter(vec![
+        ("list", Arc::new(list) as ArrayRef),
+        ("float", Arc::new(float) as ArrayRef),
+        ("fixed_binary", Arc::new(binary) as ArrayRef),
+    ])
+    .unwrap();
+    super::util::load_into_table(
+        &mut conn,
+        batch.schema(),
+        std::slice::from_ref(&batch),
+        table_name,
+    )
+    .unwrap();
+
+    let expected = pretty_format_batches(&[batch]).unwrap().to_string();
+    let (_, batches) = super::util::query_table(&mut conn, table_name, '"').unwrap();
+    let actual = pretty_format_batches(&batches).unwrap().to_string();
+    similar_asserts::assert_eq!(expected, actual);
+}
+
+#[test]
+fn append_nested_unscannable() {
+    let table_name = "append_nested_unscannable";
+    let mut conn = init();
+
+    let list =
+        ListArray::from_iter_primitive::<arrow::datatypes::Int32Type, _, _>(vec![Some(vec![
+            Some(1),
+        ])]);
+    let date = Date64Array::from(vec![86_400_000]);
+    let batch = RecordBatch::try_from_iter(vec![
+        ("list", Arc::new(list) as ArrayRef),
+        ("date", Arc::new(date) as ArrayRef),
+    ])
+    .unwrap();
+    conn.table_create(table_name, batch.schema()).unwrap();
+
+    // batches that cannot be scanned are appended by rows, which do not support nested columns
+    let mut appender = conn.append(table_name).unwrap();
+    let err = appender.append(batch).unwrap_err();
+    assert!(
+        matches!(err, connector_arrow::ConnectorError::NotSupported { .. }),
+        "{:?}",
+        err
+    );
+}
+
+#[rstest]
+#[case::int(spec::int())]
+#[case::uint(spec::uint())]
+#[case::utf8(spec::utf8())]
+#[case::many_rows(spec::many_rows_non_null())]
+fn register_batches(#[case] spec: spec::ArrowGenSpec) {
+    let mut conn = init();
+
+    let mut rng = rand_chacha::ChaCha8Rng::from_seed([0; 32]);
+    let (schema, batches) = generate_batch(spec, &mut rng);
+
+    conn.register_batches("registered", schema.clone(), batches.clone())
+        .unwrap();
+
+    // the view can be scanned repeatedly
+    for _ in 0..2 {
+        let (_, batches_query) = super::util::query_table(&mut conn, "registered", '"').unwrap();
+        let batches_query = batches_query
+            .into_iter()
+            .map(|b| RecordBatch::try_new(schema.clone(), b.columns().to_vec()).unwrap())
+            .collect::<Vec<_>>();
+
+        similar_asserts::assert_eq!(
+            concat_batches(&schema, &batches).unwrap(),
+            concat_batches(&schema, &batches_query).unwrap()
+        );
+    }
+}
+
+#[test]
+fn register_batches_decimal_out_of_range() {
+    let mut conn = init();
+
+    let schema = Arc::new(Schema::new(vec![Field::new(
+        "d",
+        DataType::Decimal128(4, 0),
+        true,
+    )]));
+    // does not fit into precision 4
+    let array = Decimal128Array::from(vec![100_000])
+        .with_precision_and_scale(4, 0)
+        .unwrap();
+    let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(array)]).unwrap();
+    conn.register_batches("registered", schema, vec![batch])
+        .unwrap();
+
+    let err = super::util::query_table(&mut conn, "registered", '"').unwrap_err();
+    assert!(
+        err.to_string().contains("out of supported range"),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn register_batches_other_connection() {
+    let inner = duckdb::Connection::open_in_memory().unwrap();
+    let mut other = DuckDBConnection::new(inner.try_clone().unwrap());
+    let mut conn = DuckDBConnection::new(inner);
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    let batch = |values: Vec<i64>| {
+        let array = Arc::new(Int64Array::from(values)) as ArrayRef;
+        RecordBatch::try_new(schema.clone(), vec![array]).unwrap()
+    };
+    conn.register_batches("registered", schema.clone(), vec![batch(vec![1, 2])])
+        .unwrap();
+
+    // connections of the same database share the scan function, but scan only their own batches
+    other
+        .register_batches("registered", schema.clone(), vec![batch(vec![3])])
+        .unwrap();
+    for (conn, expected) in [(&mut conn, batch(vec![1, 2])), (&mut other, batch(vec![3]))] {
+        let (_, batches) = super::util::query_table(conn, "registered", '"').unwrap();
+        similar_asserts::assert_eq!(vec![expected], batches);
+    }
+
+    // ids are sequential, so they do not give access to batches of other connections
+    for id in 0..8 {
+        let query = format!("SELECT * FROM connector_arrow_scan(0::UBIGINT, {id}::UBIGINT)");
+        let err = connector_arrow::query(&mut other, &query).unwrap_err();
+        assert!(err.to_string().contains("not registered"), "{:?}", err);
+    }
+}
+
+#[test]
+fn register_reader_join() {
+    let mut conn = init();
+    let mut source = init();
+
+    let mut stmt = conn
+        .query("CREATE TABLE register_reader_join AS SELECT 1 AS id, 'one' AS name UNION ALL SELECT 2, 'two'")
+        .unwrap();
+    stmt.start([]).unwrap().for_each(drop);
+    drop(stmt);
+
+    // registering under the same name replaces the view
+    let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, true)]));
+    conn.register_batches("labels", schema, vec![]).unwrap();
+
+    let mut stmt = source
+        .query("SELECT * FROM (VALUES (2, 'b'), (3, NULL), (NULL, 'c')) AS t(id, label)")
+        .unwrap();
+    let reader = stmt.start([]).unwrap();
+    conn.register_reader("labels", reader).unwrap();
+
+    let batches = connector_arrow::query(
+        &mut conn,
+        "SELECT t.name, l.label FROM register_reader_join t JOIN labels l ON (t.id = l.id)",
+    )
+    .unwrap();
+    let batch = concat_batches(&batches[0].schema(), &batches).unwrap();
+    assert_eq!(batch.num_rows(), 1);
+    assert_eq!(
+        batch.column(0).as_string::<i32>(),
+        &StringArray::from(vec!["two"])
+    );
+    assert_eq!(
+        batch.column(1).as_string::<i32>(),
+        &StringArray::from(vec!["b"])
+    );
+}
+
+#[test]
+fn reader_options() {
+    let mut conn = init();
+    conn.set_reader_options(ReaderOptions {
+        dictionary_columns: vec!["kind".into()],
+        string_view: true,
+    });
+
+    let query = "SELECT * FROM (VALUES ('a', 'short'), ('b', NULL), ('a', 'longer than twelve bytes')) AS t(kind, name)";
+    let batches = connector_arrow::query(&mut conn, query).unwrap();
+    let schema = batches[0].schema();
+    assert_eq!(
+        schema.field(0).data_type(),
+        &DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+    );
+    assert_eq!(schema.field(1).data_type(), &DataType::Utf8View);
+
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    assert_eq!(batch.column(0).as_any_dictionary().values().len(), 2);
+    let names: StringViewArray = vec![Some("short"), None, Some("longer than twelve bytes")]
+        .into_iter()
+        .collect();
+    assert_eq!(batch.column(1).as_ref(), &names as &dyn Array);
+}
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Int64Array, RecordBatch, RecordBatchReader};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::ffi_stream::ArrowArrayStreamReader;
+use arrow::util::pretty::pretty_format_batches;
+use connector_arrow::api::{Connector, SchemaEdit};
+use connector_arrow::ffi::{append_stream, export_query};
+use connector_arrow::sqlite::SQLiteConnection;
+use connector_arrow::DbErrorKind;
+
+use super::util::{load_into_table, query_table};
+
+fn init() -> SQLiteConnection {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    SQLiteConnection::new(conn)
+}
+
+fn init_with_batch(table_name: &str) -> (SQLiteConnection, RecordBatch) {
+    let mut conn = init();
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    let array = Arc::new(Int64Array::from(vec![Some(1), None, Some(3)])) as ArrayRef;
+    let batch = RecordBatch::try_new(schema.clone(), vec![array]).unwrap();
+    load_into_table(&mut conn, schema, std::slice::from_ref(&batch), table_name).unwrap();
+    (conn, batch)
+}
+
+#[test]
+fn export_and_append() {
+    let (source, batch) = init_with_batch("export_and_append");
+    let stream = export_query(source, "SELECT a FROM export_and_append").unwrap();
+
+    let mut target = init();
+    target
+        .table_create("export_and_append", batch.schema())
+        .unwrap();
+    let appender = target.append("export_and_append").unwrap();
+    append_stream(appender, stream).unwrap();
+
+    let (_, batches) = query_table(&mut target, "export_and_append", '"').unwrap();
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&batches).unwrap().to_string(),
+        pretty_format_batches(&[batch]).unwrap().to_string()
+    );
+}
+
+#[test]
+fn export_release_early() {
+    let (source, batch) = init_with_batch("export_release_early");
+    let stream = export_query(source, "SELECT a FROM export_release_early").unwrap();
+
+    let reader = ArrowArrayStreamReader::try_new(stream).unwrap();
+    assert_eq!(reader.schema(), batch.schema());
+    // the connection is dropped by the exporting thread
+    drop(reader);
+}
+
+#[test]
+fn export_error() {
+    let conn = init();
+    let err = export_query(conn, "SELECT a FROM export_error")
+        .err()
+        .unwrap();
+    assert_eq!(err.db_error().unwrap().kind, DbErrorKind::Syntax);
+}
@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, AsArray, BooleanArray, Float64Array, Int32Array, Int64Array, RecordBatch,
+    StringArray, TimestampMicrosecondArray,
+};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::util::pretty::pretty_format_batches;
+use connector_arrow::api::{Append, Connector, SchemaEdit, SchemaGet};
+use connector_arrow::file::{FileConnection, FileFormat};
+use connector_arrow::{ConnectorError, TableCreateError, TableDropError};
+use rstest::*;
+
+use super::util::{load_into_table, query_table};
+
+fn init(name: &str) -> (FileConnection, PathBuf) {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dir = std::env::temp_dir().join(format!("connector_arrow_file_{name}"));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    (FileConnection::new(&dir), dir)
+}
+
+#[rstest]
+#[case::parquet(FileFormat::Parquet)]
+#[case::ipc(FileFormat::ArrowIpc)]
+fn roundtrip(#[case] format: FileFormat) {
+    let table_name = format!("roundtrip_{}", format.extension());
+    let (mut conn, dir) = init(&table_name);
+    conn.set_format(format);
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("int", DataType::Int32, true),
+        Field::new("float", DataType::Float64, true),
+        Field::new("bool", DataType::Boolean, true),
+        Field::new("text", DataType::Utf8, true),
+        Field::new("ts", DataType::Timestamp(TimeUnit::Microsecond, None), true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int32Array::from(vec![Some(1), None, Some(-3)])),
+        Arc::new(Float64Array::from(vec![Some(0.5), Some(2.0), None])),
+        Arc::new(BooleanArray::from(vec![Some(true), None, Some(false)])),
+        Arc::new(StringArray::from(vec![Some("007"), Some(""), None])),
+        Arc::new(TimestampMicrosecondArray::from(vec![
+            Some(1),
+            None,
+            Some(2),
+        ])),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+    load_into_table(
+        &mut conn,
+        schema.clone(),
+        std::slice::from_ref(&batch),
+        &table_name,
+    )
+    .unwrap();
+
+    // types are stored in the file
+    let (schema_query, batches) = query_table(&mut conn, &table_name, '"').unwrap();
+    assert_eq!(schema_query, schema);
+    assert_eq!(batches, vec![batch]);
+    assert_eq!(conn.table_get(&table_name).unwrap(), schema);
+    assert!(dir
+        .join(format!("{table_name}.{}", format.extension()))
+        .is_file());
+}
+
+#[test]
+fn roundtrip_csv() {
+    let (mut conn, dir) = init("roundtrip_csv");
+    conn.set_format(FileFormat::Csv);
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("int", DataType::Int32, true),
+        Field::new("float", DataType::Float64, true),
+        Field::new("bool", DataType::Boolean, true),
+        Field::new("text", DataType::Utf8, true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int32Array::from(vec![Some(1), None, Some(-3)])),
+        Arc::new(Float64Array::from(vec![Some(0.5), Some(2.0), None])),
+        Arc::new(BooleanArray::from(vec![Some(true), None, Some(false)])),
+        Arc::new(StringArray::from(vec!["a,b", "007", ""])),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+    load_into_table(&mut conn, schema, std::slice::from_ref(&batch), "roundtrip").unwrap();
+
+    // declared types are applied, so "007" stays text and "" is not null
+    let (schema_query, batches) = query_table(&mut conn, "roundtrip", '"').unwrap();
+    let types: Vec<_> = schema_query
+        .fields()
+        .iter()
+        .map(|f| f.data_type())
+        .collect();
+    assert_eq!(
+        types,
+        [
+            &DataType::Int64,
+            &DataType::Float64,
+            &DataType::Boolean,
+            &DataType::Utf8
+        ]
+    );
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&batches).unwrap().to_string(),
+        pretty_format_batches(&[batch]).unwrap().to_string()
+    );
+    assert_eq!(batches[0].column(3).null_count(), 0);
+
+    assert_eq!(conn.table_get("roundtrip").unwrap(), schema_query);
+    assert!(dir.join("roundtrip.csv").is_file());
+    assert!(dir.join("roundtrip.csv.types").is_file());
+
+    // text columns cannot store nulls
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("int", DataType::Int64, true),
+        Field::new("float", DataType::Float64, true),
+        Field::new("bool", DataType::Boolean, true),
+        Field::new("text", DataType::Utf8, true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from(vec![1])),
+        Arc::new(Float64Array::from(vec![1.0])),
+        Arc::new(BooleanArray::from(vec![true])),
+        Arc::new(StringArray::from(vec![None::<&str>])),
+    ];
+    let batch = RecordBatch::try_new(schema, columns).unwrap();
+    let err = conn.append("roundtrip").unwrap().append(batch).unwrap_err();
+    assert!(
+        matches!(err, ConnectorError::NotSupported { .. }),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn csv_without_types() {
+    let (mut conn, dir) = init("csv_without_types");
+    std::fs::write(dir.join("t.csv"), "a,b\n007,\n").unwrap();
+
+    // columns are read as text, without guessing their types
+    let batches = connector_arrow::query(&mut conn, "SELECT * FROM t").unwrap();
+    let schema = batches[0].schema();
+    assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+    assert_eq!(schema.field(1).data_type(), &DataType::Utf8);
+    assert_eq!(batches[0].column(0).as_string::<i32>().value(0), "007");
+    assert_eq!(batches[0].column(1).as_string::<i32>().value(0), "");
+    assert_eq!(batches[0].column(1).null_count(), 0);
+}
+
+#[rstest]
+#[case::parquet(FileFormat::Parquet)]
+#[case::ipc(FileFormat::ArrowIpc)]
+#[case::csv(FileFormat::Csv)]
+fn schema_edit(#[case] format: FileFormat) {
+    let (mut conn, dir) = init(&format!("schema_edit_{}", format.extension()));
+    conn.set_format(format);
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    conn.table_create("t", schema.clone()).unwrap();
+    assert!(matches!(
+        conn.table_create("t", schema).unwrap_err(),
+        TableCreateError::TableExists
+    ));
+    assert_eq!(conn.table_list().unwrap(), vec!["t".to_string()]);
+
+    conn.table_drop("t").unwrap();
+    assert!(matches!(
+        conn.table_drop("t").unwrap_err(),
+        TableDropError::TableNonexistent
+    ));
+    assert!(conn.table_list().unwrap().is_empty());
+    assert_eq!(std::fs::read_dir(dir).unwrap().count(), 0);
+}
+
+#[test]
+fn query_projection() {
+    let (mut conn, _) = init("query_projection");
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("a", DataType::Int64, true),
+        Field::new("b b", DataType::Utf8, true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(arrow::array::Int64Array::from(vec![1, 2])),
+        Arc::new(StringArray::from(vec!["x", "y"])),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+    load_into_table(&mut conn, schema, &[batch], "t").unwrap();
+
+    let batches = connector_arrow::query(&mut conn, "select \"b b\", a from t;").unwrap();
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&batches).unwrap().to_string(),
+        "+-----+---+\n| b b | a |\n+-----+---+\n| x   | 1 |\n| y   | 2 |\n+-----+---+"
+    );
+
+    let err = conn.query("SELECT a FROM t WHERE a = 1").err().unwrap();
+    assert!(matches!(err, ConnectorError::NotSupported { .. }));
+}
+
+#[test]
+fn dyn_connector() {
+    let (_, dir) = init("dyn_connector");
+
+    let mut conn = connector_arrow::connect(&format!("file://{}", dir.display())).unwrap();
+    super::tests::dyn_connector(conn.as_mut(), "dyn_connector");
+}
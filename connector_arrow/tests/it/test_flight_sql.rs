@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, IntervalUnit, Schema, TimeUnit};
+use arrow::util::pretty::pretty_format_batches;
+use arrow_flight::sql::client::FlightSqlServiceClient;
+use connector_arrow::api::{Append, ArrowValue, Connector, SchemaEdit, SchemaGet, Statement};
+use connector_arrow::flight_sql::FlightSqlConnection;
+use connector_arrow::flight_sql_server::FlightSqlServer;
+use connector_arrow::{ConnectorError, TableCreateError, TableDropError};
+use tokio::runtime;
+
+use super::util::{load_into_table, query_table};
+
+/// Serves a new in-memory SQLite database from a background thread.
+fn serve() -> std::net::SocketAddr {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    std::thread::spawn(move || {
+        let rt = runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+            let incoming = tokio_stream::wrappers::TcpListenerStream::new(listener);
+
+            let conn = rusqlite::Connection::open_in_memory().unwrap();
+            let conn = connector_arrow::sqlite::SQLiteConnection::new(conn);
+            tonic::transport::Server::builder()
+                .add_service(FlightSqlServer::new(conn).into_service())
+                .serve_with_incoming(incoming)
+                .await
+                .unwrap();
+        })
+    });
+    addr
+}
+
+fn init() -> FlightSqlConnection {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let addr = serve();
+
+    let rt = Arc::new(
+        runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap(),
+    );
+    let endpoint = tonic::transport::Endpoint::new(format!("http://{addr}")).unwrap();
+    let channel = rt.block_on(endpoint.connect()).unwrap();
+    FlightSqlConnection::new(rt, FlightSqlServiceClient::new(channel))
+}
+
+#[test]
+fn query_01() {
+    let mut conn = init();
+    super::tests::query_01(&mut conn);
+}
+
+#[test]
+fn record_batch_reader() {
+    let mut conn = init();
+    super::tests::record_batch_reader(&mut conn);
+}
+
+#[test]
+fn roundtrip() {
+    let mut conn = init();
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("a", DataType::Int64, true),
+        Field::new("b", DataType::Utf8, true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from(vec![Some(1), None, Some(3)])),
+        Arc::new(StringArray::from(vec![Some("x"), Some("y"), None])),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+
+    conn.table_create("roundtrip", schema.clone()).unwrap();
+    let mut appender = conn.append("roundtrip").unwrap();
+    appender.append(batch.clone()).unwrap();
+    appender.append(batch.clone()).unwrap();
+    appender.finish().unwrap();
+
+    let (schema_query, batches) = query_table(&mut conn, "roundtrip", '"').unwrap();
+    assert_eq!(schema_query.fields().len(), 2);
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&batches).unwrap().to_string(),
+        pretty_format_batches(&[batch.clone(), batch])
+            .unwrap()
+            .to_string()
+    );
+}
+
+#[test]
+fn append_not_finished() {
+    let mut conn = init();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![Arc::new(Int64Array::from(vec![1, 2, 3])) as ArrayRef],
+    )
+    .unwrap();
+
+    conn.table_create("append_not_finished", schema).unwrap();
+    let mut appender = conn.append("append_not_finished").unwrap();
+    appender.append(batch).unwrap();
+    drop(appender);
+
+    let (_, batches) = query_table(&mut conn, "append_not_finished", '"').unwrap();
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 0);
+}
+
+#[test]
+fn append_schema_mismatch() {
+    let mut conn = init();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    conn.table_create("append_schema_mismatch", schema.clone())
+        .unwrap();
+    let mut appender = conn.append("append_schema_mismatch").unwrap();
+
+    let batch = RecordBatch::try_new(
+        schema,
+        vec![Arc::new(Int64Array::from(vec![1])) as ArrayRef],
+    )
+    .unwrap();
+    appender.append(batch).unwrap();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Utf8, true)]));
+    let batch = RecordBatch::try_new(
+        schema,
+        vec![Arc::new(StringArray::from(vec!["x"])) as ArrayRef],
+    )
+    .unwrap();
+    let err = appender.append(batch).unwrap_err();
+    assert!(
+        matches!(err, ConnectorError::DataSchemaMismatch(_)),
+        "{:?}",
+        err
+    );
+}
+
+#[rstest::rstest]
+#[case(DataType::Null)]
+#[case(DataType::Boolean)]
+#[case(DataType::UInt16)]
+#[case(DataType::Float16)]
+#[case(DataType::LargeUtf8)]
+#[case(DataType::Date64)]
+#[case(DataType::Timestamp(TimeUnit::Nanosecond, None))]
+#[case(DataType::Timestamp(TimeUnit::Second, Some("+07:30".into())))]
+#[case(DataType::Timestamp(TimeUnit::Millisecond, Some("America/New_York".into())))]
+#[case(DataType::Time32(TimeUnit::Millisecond))]
+#[case(DataType::Time64(TimeUnit::Microsecond))]
+#[case(DataType::Duration(TimeUnit::Second))]
+#[case(DataType::Interval(IntervalUnit::MonthDayNano))]
+#[case(DataType::FixedSizeBinary(16))]
+#[case(DataType::Decimal128(38, 10))]
+#[case(DataType::Decimal256(76, -3))]
+fn type_roundtrip(#[case] ty: DataType) {
+    let db_ty = FlightSqlConnection::type_arrow_into_db(&ty).unwrap();
+    assert_eq!(FlightSqlConnection::type_db_into_arrow(&db_ty), Some(ty));
+}
+
+#[test]
+fn type_nested_unsupported() {
+    let ty = DataType::new_list(DataType::Int64, true);
+    let db_ty = FlightSqlConnection::type_arrow_into_db(&ty).unwrap();
+    assert_eq!(FlightSqlConnection::type_db_into_arrow(&db_ty), None);
+}
+
+#[test]
+fn schema_get() {
+    let mut conn = init();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    conn.table_create("schema_get", schema).unwrap();
+    assert_eq!(conn.table_list().unwrap(), vec!["schema_get".to_string()]);
+
+    let schema = conn.table_get("schema_get").unwrap();
+    assert_eq!(schema.field(0).name(), "a");
+
+    let err = conn.table_get("schema_get_nonexistent").unwrap_err();
+    assert!(matches!(err, ConnectorError::FlightSql(_)));
+}
+
+#[test]
+fn schema_edit() {
+    let mut conn = init();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    conn.table_create("schema_edit", schema.clone()).unwrap();
+    assert!(matches!(
+        conn.table_create("schema_edit", schema).unwrap_err(),
+        TableCreateError::TableExists
+    ));
+
+    assert!(matches!(
+        conn.table_drop("schema_edit").unwrap_err(),
+        TableDropError::Connector(ConnectorError::NotSupported { .. })
+    ));
+}
+
+#[test]
+fn load_into_table_unsupported_drop() {
+    let mut conn = init();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    let err = load_into_table(&mut conn, schema, &[], "load").unwrap_err();
+    assert!(matches!(err, ConnectorError::NotSupported { .. }));
+}
+
+#[test]
+fn query_params() {
+    let mut conn = init();
+
+    // SQLite connection does not bind the parameters,
+    // but the server executes the query once for each row of them
+    let mut stmt = conn.query("SELECT 1 AS a").unwrap();
+    let reader = stmt.start([&2_i64 as &dyn ArrowValue]).unwrap();
+    let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&batches).unwrap().to_string(),
+        "+---+\n\
+         | a |\n\
+         +---+\n\
+         | 1 |\n\
+         +---+"
+    );
+}
+
+#[test]
+fn dyn_connector() {
+    let addr = serve();
+    let mut conn = connector_arrow::connect(&format!("grpc://{addr}")).unwrap();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    conn.table_create("dyn_connector", schema).unwrap();
+    assert_eq!(
+        conn.table_list().unwrap(),
+        vec!["dyn_connector".to_string()]
+    );
+}
+
+#[test]
+fn query_error() {
+    let mut conn = init();
+
+    let err = connector_arrow::query(&mut conn, "SELECT * FROM nonexistent").unwrap_err();
+    assert!(matches!(err, ConnectorError::Arrow(_)));
+}
@@ -0,0 +1,233 @@
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, AsArray, Int64Array, RecordBatch};
+use arrow::compute::concat_batches;
+use arrow::datatypes::{DataType, Field, Int64Type, Schema};
+use arrow::util::pretty::pretty_format_batches;
+use arrow_flight::decode::FlightRecordBatchStream;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::sql::client::FlightSqlServiceClient;
+use arrow_flight::sql::{
+    CommandGetTables, DoPutUpdateResult, ProstMessageExt, TicketStatementQuery,
+};
+use arrow_flight::{FlightDescriptor, FlightInfo, Ticket};
+use connector_arrow::flight_sql_server::FlightSqlServer;
+use connector_arrow::sqlite::SQLiteConnection;
+use connector_arrow::util::flight_sql::{
+    CommandStatementIngest, TableDefinitionOptions, TableExistsOption, TableNotExistOption,
+};
+use futures::{StreamExt, TryStreamExt};
+use prost::Message;
+use tonic::transport::Channel;
+
+type Client = FlightSqlServiceClient<Channel>;
+
+/// Serves a new in-memory SQLite database and connects a client to it.
+async fn init() -> Client {
+    init_with(|_| {}).await
+}
+
+async fn init_with(configure: impl FnOnce(&mut FlightSqlServer<SQLiteConnection>)) -> Client {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let conn = SQLiteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+    let mut server = FlightSqlServer::new(conn);
+    configure(&mut server);
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(server.into_service())
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+    );
+
+    let channel = tonic::transport::Endpoint::new(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    FlightSqlServiceClient::new(channel)
+}
+
+async fn fetch(client: &mut Client, info: &FlightInfo) -> Vec<RecordBatch> {
+    let ticket = info.endpoint[0].ticket.clone().unwrap();
+    let stream: FlightRecordBatchStream = client.do_get(ticket).await.unwrap();
+    stream.try_collect().await.unwrap()
+}
+
+/// Ingests a table with a single column `a` of values 1, null and 3.
+async fn ingest(client: &mut Client, table_name: &str) -> RecordBatch {
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    let array = Arc::new(Int64Array::from(vec![Some(1), None, Some(3)])) as ArrayRef;
+    let batch = RecordBatch::try_new(schema.clone(), vec![array]).unwrap();
+    let ingest = CommandStatementIngest {
+        table_definition_options: Some(TableDefinitionOptions {
+            if_not_exist: TableNotExistOption::Create as i32,
+            if_exists: TableExistsOption::Append as i32,
+        }),
+        table: table_name.into(),
+        ..Default::default()
+    };
+    let descriptor = FlightDescriptor::new_cmd(ingest.as_any().encode_to_vec());
+    let flight_data: Vec<_> = FlightDataEncoderBuilder::new()
+        .with_flight_descriptor(Some(descriptor))
+        .build(futures::stream::iter([Ok(batch.clone())]))
+        .try_collect()
+        .await
+        .unwrap();
+    let results: Vec<_> = client
+        .do_put(futures::stream::iter(flight_data))
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let any = arrow_flight::sql::Any::decode(&*results[0].app_metadata).unwrap();
+    let result: DoPutUpdateResult = any.unpack().unwrap().unwrap();
+    assert_eq!(result.record_count, 3);
+    batch
+}
+
+#[test]
+fn ingest_and_tables() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let mut client = init().await;
+        ingest(&mut client, "ingest_and_tables").await;
+
+        let info = client
+            .get_tables(CommandGetTables {
+                include_schema: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let batches = fetch(&mut client, &info).await;
+        let tables = batches[0].column_by_name("table_name").unwrap();
+        assert_eq!(tables.as_string::<i32>().value(0), "ingest_and_tables");
+    });
+}
+
+#[test]
+fn statement() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let mut client = init().await;
+        let batch = ingest(&mut client, "statement").await;
+
+        let info = client
+            .execute("SELECT a FROM statement".into(), None)
+            .await
+            .unwrap();
+        let batches = fetch(&mut client, &info).await;
+        similar_asserts::assert_eq!(
+            pretty_format_batches(&batches).unwrap().to_string(),
+            pretty_format_batches(&[batch]).unwrap().to_string()
+        );
+
+        // the query is executed on each DoGet of the ticket
+        let batches = fetch(&mut client, &info).await;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+    });
+}
+
+#[test]
+fn prepared_statement() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let mut client = init().await;
+        ingest(&mut client, "prepared_statement").await;
+
+        // executed once for each row of parameters
+        // (SQLite connection does not bind the parameters into the query)
+        let mut stmt = client
+            .prepare("SELECT a FROM prepared_statement WHERE a = 3".into(), None)
+            .await
+            .unwrap();
+        let params = Arc::new(Int64Array::from(vec![3, 1])) as ArrayRef;
+        let params = RecordBatch::try_from_iter([("p", params)]).unwrap();
+        stmt.set_parameters(params).unwrap();
+        let info = stmt.execute().await.unwrap();
+        let batches = fetch(&mut client, &info).await;
+        let batch = concat_batches(&batches[0].schema(), &batches).unwrap();
+        assert_eq!(
+            batch.column(0).as_primitive::<Int64Type>(),
+            &Int64Array::from(vec![3, 3])
+        );
+        stmt.close().await.unwrap();
+    });
+}
+
+#[test]
+fn cancelled_results() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let mut client = init().await;
+        ingest(&mut client, "cancelled_results").await;
+
+        // dropping the results releases the connection for other requests
+        let info = client
+            .execute("SELECT a FROM cancelled_results".into(), None)
+            .await
+            .unwrap();
+        let ticket = info.endpoint[0].ticket.clone().unwrap();
+        let mut stream = client.do_get(ticket).await.unwrap();
+        stream.next().await.unwrap().unwrap();
+        drop(stream);
+
+        let batches = fetch(&mut client, &info).await;
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+    });
+}
+
+#[test]
+fn errors() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let mut client = init().await;
+
+        // queries are prepared by GetFlightInfo, which reports their errors
+        let err = client.execute("SELECT * FROM nope".into(), None).await;
+        assert!(err.is_err());
+    });
+}
+
+#[test]
+fn tickets() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let mut client = init_with(|server| server.set_max_statements(1)).await;
+
+        // tickets do not contain the query, so clients cannot make up their own
+        let ticket = TicketStatementQuery {
+            statement_handle: "SELECT 1".into(),
+        };
+        let ticket = Ticket::new(ticket.as_any().encode_to_vec());
+        assert!(client.do_get(ticket).await.is_err());
+
+        // issuing a ticket over the limit expires the oldest one
+        let first = client.execute("SELECT 1".into(), None).await.unwrap();
+        let second = client.execute("SELECT 2".into(), None).await.unwrap();
+        let ticket = first.endpoint[0].ticket.clone().unwrap();
+        let err = client.do_get(ticket).await.unwrap_err();
+        assert!(err.to_string().contains("expired"), "{:?}", err);
+        assert_eq!(fetch(&mut client, &second).await[0].num_rows(), 1);
+    });
+}
+
+#[test]
+fn prepared_statement_limit() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let mut client = init_with(|server| server.set_max_statements(1)).await;
+
+        let stmt = client.prepare("SELECT 1".into(), None).await.unwrap();
+        let err = client.prepare("SELECT 2".into(), None).await.unwrap_err();
+        assert!(err.to_string().contains("too many"), "{:?}", err);
+
+        // closing a statement makes room for another
+        stmt.close().await.unwrap();
+        let stmt = client.prepare("SELECT 2".into(), None).await.unwrap();
+        stmt.close().await.unwrap();
+    });
+}
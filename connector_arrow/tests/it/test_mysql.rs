@@ -1,7 +1,18 @@
-use connector_arrow::mysql::MySQLConnection;
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, Date32Array, Int64Array, RecordBatch, Time64MicrosecondArray,
+    TimestampMicrosecondArray, TimestampSecondArray,
+};
+use arrow::datatypes::{DataType, Field, IntervalUnit, Schema, TimeUnit};
+use connector_arrow::api::{Append, Connector, SchemaEdit};
+use connector_arrow::mysql::{AppendMode, MySQLConnection};
+use connector_arrow::ConnectorError;
+use itertools::Itertools;
 use rstest::*;
 
 use crate::spec;
+use crate::util::QueryOfSingleLiteral;
 
 fn init() -> MySQLConnection<mysql::Conn> {
     let _ = env_logger::builder().is_test(true).try_init();
@@ -11,12 +22,42 @@ fn init() -> MySQLConnection<mysql::Conn> {
     MySQLConnection::new(conn)
 }
 
+fn init_text() -> MySQLConnection<mysql::Conn, mysql::Text> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let url = std::env::var("MYSQL_URL").unwrap();
+    let conn = mysql::Conn::new(url.as_str()).unwrap();
+    MySQLConnection::new(conn)
+}
+
 #[test]
 fn query_01() {
     let mut conn = init();
     super::tests::query_01(&mut conn);
 }
 
+#[test]
+fn record_batch_reader() {
+    let mut conn = init();
+    super::tests::record_batch_reader(&mut conn);
+}
+
+#[test]
+fn query_01_text() {
+    let mut conn = init_text();
+    super::tests::query_01(&mut conn);
+}
+
+#[test]
+fn query_show_text() {
+    let mut conn = init_text();
+
+    // SHOW cannot be prepared, so this only works over the text protocol
+    let batches = connector_arrow::query(&mut conn, "SHOW VARIABLES LIKE 'version'").unwrap();
+    let batch = batches.into_iter().exactly_one().unwrap();
+    assert_eq!(batch.num_rows(), 1);
+}
+
 #[test]
 fn schema_get() {
     let table_name = "schema_get";
@@ -35,6 +76,16 @@ fn schema_edit() {
     super::tests::schema_edit(&mut conn, table_name, column_spec);
 }
 
+#[test]
+fn schema_create_unsupported() {
+    let mut conn = init();
+    super::tests::schema_create_unsupported(
+        &mut conn,
+        "schema_create_unsupported",
+        DataType::Interval(IntervalUnit::MonthDayNano),
+    );
+}
+
 #[test]
 fn ident_escaping() {
     // https://github.com/blackbeam/rust_mysql_common/issues/129
@@ -44,17 +95,21 @@ fn ident_escaping() {
     super::tests::ident_escaping(&mut conn, table_name);
 }
 
+#[test]
+fn dyn_connector() {
+    let url = std::env::var("MYSQL_URL").unwrap();
+    let mut conn = connector_arrow::connect(&url).unwrap();
+    super::tests::dyn_connector(conn.as_mut(), "dyn_connector");
+}
+
 #[rstest]
 #[case::empty("roundtrip__empty", spec::empty())]
 #[case::null_bool("roundtrip__null_bool", spec::null_bool())]
 #[case::int("roundtrip__int", spec::int())]
 #[case::uint("roundtrip__uint", spec::uint())]
 #[case::float("roundtrip__float", spec::float())]
-// #[case::decimal("roundtrip__decimal", spec::decimal())]
-// #[case::timestamp("roundtrip__timestamp", spec::timestamp())]
-// #[case::date("roundtrip__date", spec::date())]
-// #[case::time("roundtrip__time", spec::time())]
-// #[case::duration("roundtrip__duration", spec::duration())]
+#[case::decimal("roundtrip__decimal", spec::decimal())]
+#[case::duration("roundtrip__duration", spec::duration())]
 // #[case::interval("roundtrip__interval", spec::interval())]
 #[case::utf8("roundtrip__utf8", spec::utf8())]
 #[case::binary("roundtrip__binary", spec::binary())]
@@ -62,3 +117,223 @@ fn roundtrip(#[case] table_name: &str, #[case] spec: spec::ArrowGenSpec) {
     let mut conn = init();
     super::tests::roundtrip(&mut conn, table_name, spec, '`', true);
 }
+
+#[rstest]
+#[case::null_bool("roundtrip_load_data__null_bool", spec::null_bool())]
+#[case::int("roundtrip_load_data__int", spec::int())]
+#[case::uint("roundtrip_load_data__uint", spec::uint())]
+#[case::float("roundtrip_load_data__float", spec::float())]
+#[case::decimal("roundtrip_load_data__decimal", spec::decimal())]
+#[case::utf8("roundtrip_load_data__utf8", spec::utf8())]
+#[case::binary("roundtrip_load_data__binary", spec::binary())]
+fn roundtrip_load_data(#[case] table_name: &str, #[case] spec: spec::ArrowGenSpec) {
+    let mut conn = init();
+    conn.set_append_mode(AppendMode::LoadDataLocalInfile);
+    super::tests::roundtrip(&mut conn, table_name, spec, '`', true);
+}
+
+#[rstest]
+#[case::null_bool("roundtrip_text__null_bool", spec::null_bool())]
+#[case::int("roundtrip_text__int", spec::int())]
+#[case::uint("roundtrip_text__uint", spec::uint())]
+#[case::decimal("roundtrip_text__decimal", spec::decimal())]
+#[case::utf8("roundtrip_text__utf8", spec::utf8())]
+#[case::binary("roundtrip_text__binary", spec::binary())]
+fn roundtrip_text(#[case] table_name: &str, #[case] spec: spec::ArrowGenSpec) {
+    let mut conn = init_text();
+    super::tests::roundtrip(&mut conn, table_name, spec, '`', true);
+}
+
+/// Values of generated temporal columns are out of range of MySQL DATETIME, DATE and TIME,
+/// so these are tested with values in range.
+fn temporal_batch() -> RecordBatch {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+            true,
+        ),
+        Field::new("date", DataType::Date32, true),
+        Field::new("time", DataType::Time64(TimeUnit::Microsecond), true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(TimestampMicrosecondArray::from(vec![
+            Some(0),
+            Some(1708701516123456),
+            Some(-30610224000000000),
+            None,
+        ])),
+        Arc::new(Date32Array::from(vec![
+            Some(0),
+            Some(19776),
+            Some(-354285),
+            None,
+        ])),
+        Arc::new(Time64MicrosecondArray::from(vec![
+            Some(0),
+            Some(62316789000),
+            Some(86399999999),
+            None,
+        ])),
+    ];
+    RecordBatch::try_new(schema, columns).unwrap()
+}
+
+fn roundtrip_temporal_of<C: Connector + SchemaEdit>(conn: &mut C, table_name: &str) {
+    let batch = temporal_batch();
+    crate::util::load_into_table(
+        conn,
+        batch.schema(),
+        std::slice::from_ref(&batch),
+        table_name,
+    )
+    .unwrap();
+
+    let (_, batches) = crate::util::query_table(conn, table_name, '`').unwrap();
+    similar_asserts::assert_eq!(vec![batch], batches);
+}
+
+#[test]
+fn roundtrip_temporal() {
+    let mut conn = init();
+    roundtrip_temporal_of(&mut conn, "roundtrip_temporal");
+}
+
+#[test]
+fn roundtrip_temporal_load_data() {
+    let mut conn = init();
+    conn.set_append_mode(AppendMode::LoadDataLocalInfile);
+    roundtrip_temporal_of(&mut conn, "roundtrip_temporal_load_data");
+}
+
+#[test]
+fn roundtrip_temporal_text() {
+    let mut conn = init_text();
+    roundtrip_temporal_of(&mut conn, "roundtrip_temporal_text");
+}
+
+#[test]
+fn append_temporal_out_of_range() {
+    let table_name = "append_temporal_out_of_range";
+    let mut conn = init();
+
+    let ty = DataType::Timestamp(TimeUnit::Second, None);
+    let schema = Arc::new(Schema::new(vec![Field::new("t", ty, true)]));
+    crate::util::load_into_table(&mut conn, schema.clone(), &[], table_name).unwrap();
+
+    // year 10000
+    let array = TimestampSecondArray::from(vec![253402300800]);
+    let batch = RecordBatch::try_new(schema, vec![Arc::new(array)]).unwrap();
+    let mut appender = conn.append(table_name).unwrap();
+    let err = appender.append(batch).unwrap_err();
+    assert!(matches!(err, ConnectorError::DataOutOfRange), "{:?}", err);
+}
+
+#[test]
+fn append_load_data_out_of_range() {
+    let table_name = "append_load_data_out_of_range";
+    let mut conn = init();
+    conn.set_append_mode(AppendMode::LoadDataLocalInfile);
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int8, true)]));
+    crate::util::load_into_table(&mut conn, schema, &[], table_name).unwrap();
+
+    // the server would store 127 and report a warning
+    let array = Int64Array::from(vec![1, 300]);
+    let batch = RecordBatch::try_from_iter([("a", Arc::new(array) as ArrayRef)]).unwrap();
+    let mut appender = conn.append(table_name).unwrap();
+    let err = appender.append(batch).unwrap_err();
+    assert!(matches!(err, ConnectorError::MySQL(_)), "{:?}", err);
+    drop(appender);
+
+    let (_, batches) = crate::util::query_table(&mut conn, table_name, '`').unwrap();
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 0);
+}
+
+#[rstest]
+#[case::decimal(literals_cases::decimal())]
+#[case::timestamp(literals_cases::timestamp())]
+#[case::date(literals_cases::date())]
+#[case::time(literals_cases::time())]
+fn query_literals(#[case] queries: Vec<QueryOfSingleLiteral>) {
+    let mut conn = init();
+    crate::util::query_literals(&mut conn, queries)
+}
+
+#[rstest]
+#[case::decimal(literals_cases::decimal())]
+#[case::timestamp(literals_cases::timestamp())]
+#[case::date(literals_cases::date())]
+#[case::time(literals_cases::time())]
+fn query_literals_text(#[case] queries: Vec<QueryOfSingleLiteral>) {
+    let mut conn = init_text();
+    crate::util::query_literals(&mut conn, queries)
+}
+
+/// These tests cases are used to test of querying of MySQL-native types
+/// that cannot be obtained by converting Arrow into MySQL.
+mod literals_cases {
+    use arrow::datatypes::{i256, DataType, TimeUnit};
+
+    use crate::util::QueryOfSingleLiteral;
+
+    pub fn decimal() -> Vec<QueryOfSingleLiteral> {
+        vec![
+            (
+                "decimal(10, 2)",
+                "100234.44",
+                (DataType::Decimal128(10, 2), 10023444_i128),
+            )
+                .into(),
+            (
+                "decimal(10, 0)",
+                "-100234",
+                (DataType::Decimal128(10, 0), -100234_i128),
+            )
+                .into(),
+            (
+                "decimal(50, 5)",
+                "-1.5",
+                (DataType::Decimal256(50, 5), i256::from_i128(-150000)),
+            )
+                .into(),
+        ]
+    }
+
+    pub fn timestamp() -> Vec<QueryOfSingleLiteral> {
+        vec![
+            ("datetime", "'2024-02-23 15:18:36'", 1708701516000000_i64).into(),
+            (
+                "datetime(6)",
+                "'2024-02-23 15:18:36.123456'",
+                1708701516123456_i64,
+            )
+                .into(),
+            ("datetime", "'1000-01-01 00:00:00'", -30610224000000000_i64).into(),
+        ]
+    }
+
+    pub fn date() -> Vec<QueryOfSingleLiteral> {
+        vec![
+            ("date", "'2024-02-23'", (DataType::Date32, 19776_i32)).into(),
+            ("date", "'1000-01-01'", (DataType::Date32, -354285_i32)).into(),
+        ]
+    }
+
+    pub fn time() -> Vec<QueryOfSingleLiteral> {
+        vec![
+            (
+                "time",
+                "'17:18:36'",
+                (DataType::Time64(TimeUnit::Microsecond), 62316000000_i64),
+            )
+                .into(),
+            (
+                "time(3)",
+                "'17:18:36.789'",
+                (DataType::Time64(TimeUnit::Microsecond), 62316789000_i64),
+            )
+                .into(),
+        ]
+    }
+}
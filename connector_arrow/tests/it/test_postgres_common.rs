@@ -1,4 +1,6 @@
+use arrow::datatypes::{DataType, IntervalUnit};
 use connector_arrow::postgres::{PostgresConnection, ProtocolSimple};
+use connector_arrow::DbErrorKind;
 
 fn init() -> PostgresConnection<ProtocolSimple> {
     let _ = env_logger::builder().is_test(true).try_init();
@@ -26,6 +28,44 @@ fn schema_edit() {
     super::tests::schema_edit(&mut conn, table_name, column_spec);
 }
 
+#[test]
+fn schema_create_unsupported() {
+    let mut conn = init();
+    super::tests::schema_create_unsupported(
+        &mut conn,
+        "simple::schema_create_unsupported",
+        DataType::Interval(IntervalUnit::MonthDayNano),
+    );
+}
+
+#[test]
+fn append_unsupported() {
+    let mut conn = init();
+    super::tests::append_unsupported(
+        &mut conn,
+        "simple::append_unsupported",
+        DataType::Interval(IntervalUnit::MonthDayNano),
+    );
+}
+
+#[test]
+fn db_errors() {
+    let mut conn = init();
+    let [syntax, unique, not_null] = super::tests::db_errors(&mut conn, "db_errors");
+
+    assert_eq!(syntax.kind, DbErrorKind::Syntax);
+    assert_eq!(syntax.code.as_deref(), Some("42601"));
+    assert_eq!(syntax.position, Some(1));
+
+    assert_eq!(unique.kind, DbErrorKind::ConstraintViolation);
+    assert_eq!(unique.code.as_deref(), Some("23505"));
+    assert_eq!(unique.constraint.as_deref(), Some("db_errors_a_key"));
+
+    assert_eq!(not_null.kind, DbErrorKind::ConstraintViolation);
+    assert_eq!(not_null.code.as_deref(), Some("23502"));
+    assert_eq!(not_null.column.as_deref(), Some("a"));
+}
+
 #[test]
 fn ident_escaping() {
     let table_name = "simple::ident_escaping";
@@ -34,6 +74,13 @@ fn ident_escaping() {
     super::tests::ident_escaping(&mut conn, table_name);
 }
 
+#[test]
+fn dyn_connector() {
+    let dburl = std::env::var("POSTGRES_URL").unwrap();
+    let mut conn = connector_arrow::connect(&dburl).unwrap();
+    super::tests::dyn_connector(conn.as_mut(), "dyn_connector");
+}
+
 /// These tests cases are used to test of querying of Postgres-native types
 /// that cannot be obtained by converting Arrow into PostgreSQL.
 pub mod literals_cases {
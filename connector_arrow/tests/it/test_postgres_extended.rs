@@ -1,3 +1,4 @@
+use connector_arrow::api::SchemaEdit;
 use connector_arrow::postgres::{PostgresConnection, ProtocolExtended};
 use rstest::*;
 
@@ -19,6 +20,12 @@ fn query_01() {
     super::tests::query_01(&mut conn);
 }
 
+#[test]
+fn record_batch_reader() {
+    let mut conn = init();
+    super::tests::record_batch_reader(&mut conn);
+}
+
 #[test]
 fn query_02() {
     let mut conn = init();
@@ -51,6 +58,29 @@ fn roundtrip(#[case] table_name: &str, #[case] spec: spec::ArrowGenSpec) {
     super::tests::roundtrip(&mut conn, &table_name, spec, '"', false);
 }
 
+/// Lists are stored as arrays, structs as composite types and maps as arrays of composite types.
+#[test]
+fn roundtrip_containers() {
+    let table_name = "extended::roundtrip_containers";
+    let mut conn = init();
+
+    let batch = crate::util::containers_batch();
+    crate::util::load_into_table(
+        &mut conn,
+        batch.schema(),
+        std::slice::from_ref(&batch),
+        table_name,
+    )
+    .unwrap();
+
+    let (_, batches) = crate::util::query_table(&mut conn, table_name, '"').unwrap();
+    similar_asserts::assert_eq!(vec![batch.clone()], batches);
+
+    // composite types are dropped along with the table
+    conn.table_drop(table_name).unwrap();
+    conn.table_create(table_name, batch.schema()).unwrap();
+}
+
 #[rstest]
 #[case::bool(literals_cases::bool())]
 #[case::int(literals_cases::int())]
@@ -1,6 +1,23 @@
-use super::spec;
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, AsArray, BinaryViewArray, DictionaryArray, Int64Array, LargeStringArray,
+    RecordBatch, StringViewArray, UInt8Array,
+};
+use arrow::compute::concat_batches;
+use arrow::datatypes::{DataType, Field, Int32Type, IntervalUnit, Schema, TimeUnit};
+use arrow::util::pretty::pretty_format_batches;
+use connector_arrow::api::{SchemaEdit, SchemaGet, METADATA_DB_TYPE};
+use connector_arrow::sqlite::SQLiteConnection;
+use connector_arrow::util::{coerce, ReaderOptions};
+use connector_arrow::{ConnectorError, DbErrorKind, TableCreateError};
+use rand::SeedableRng;
 use rstest::*;
 
+use super::generator::generate_batch;
+use super::spec;
+use super::util::{coerce_type, load_into_table, query_table};
+
 fn init() -> connector_arrow::sqlite::SQLiteConnection {
     let _ = env_logger::builder().is_test(true).try_init();
 
@@ -14,6 +31,12 @@ fn query_01() {
     super::tests::query_01(&mut conn);
 }
 
+#[test]
+fn record_batch_reader() {
+    let mut conn = init();
+    super::tests::record_batch_reader(&mut conn);
+}
+
 #[rstest]
 // #[case::empty("roundtrip::empty", spec::empty())]
 #[case::null_bool("roundtrip::null_bool", spec::null_bool())]
@@ -33,14 +56,205 @@ fn roundtrip(#[case] table_name: &str, #[case] spec: spec::ArrowGenSpec) {
     super::tests::roundtrip(&mut conn, table_name, spec, '"', false);
 }
 
+#[rstest]
+#[case::int("append_variable_limit::int", spec::int())]
+#[case::many_rows("append_variable_limit::many_rows", spec::many_rows_non_null())]
+fn append_variable_limit(#[case] table_name: &str, #[case] spec: spec::ArrowGenSpec) {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    // inserts must be split so they don't bind more than 20 variables
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    conn.set_limit(rusqlite::limits::Limit::SQLITE_LIMIT_VARIABLE_NUMBER, 20);
+    let mut conn = connector_arrow::sqlite::SQLiteConnection::new(conn);
+
+    super::tests::roundtrip(&mut conn, table_name, spec, '"', false);
+}
+
+#[rstest]
+#[case::int("register_batches::int", spec::int())]
+#[case::uint("register_batches::uint", spec::uint())]
+#[case::decimal("register_batches::decimal", spec::decimal())]
+#[case::utf8("register_batches::utf8", spec::utf8_large())]
+#[case::binary("register_batches::binary", spec::binary_large())]
+#[case::many_rows("register_batches::many_rows", spec::many_rows_non_null())]
+fn register_batches(#[case] table_name: &str, #[case] spec: spec::ArrowGenSpec) {
+    let mut conn = init();
+
+    let mut rng = rand_chacha::ChaCha8Rng::from_seed([0; 32]);
+    let (schema, batches) = generate_batch(spec, &mut rng);
+    conn.register_batches("registered", schema.clone(), batches.clone())
+        .unwrap();
+
+    // copy into a table without going through the appender
+    conn.table_create(table_name, schema.clone()).unwrap();
+    let insert = format!("INSERT INTO \"{table_name}\" SELECT * FROM registered");
+    connector_arrow::query(&mut conn, &insert).unwrap();
+
+    let (schema_coerced, batches_coerced) = coerce::coerce_batches(
+        schema,
+        &batches,
+        coerce_type::<SQLiteConnection>,
+        Some(true),
+    )
+    .unwrap();
+    let expected = concat_batches(&schema_coerced, &batches_coerced).unwrap();
+
+    // the virtual table can be scanned repeatedly and contains the same values as the copy
+    for table in [table_name, "registered", "registered"] {
+        let (schema_query, batches_query) = query_table(&mut conn, table, '"').unwrap();
+        similar_asserts::assert_eq!(&schema_coerced, &schema_query);
+        similar_asserts::assert_eq!(
+            &expected,
+            &concat_batches(&schema_query, &batches_query).unwrap()
+        );
+    }
+}
+
+#[test]
+fn register_batches_read_only() {
+    let mut conn = init();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    conn.register_batches("registered", schema.clone(), vec![])
+        .unwrap();
+
+    // registering under the same name replaces the table
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![Arc::new(Int64Array::from(vec![Some(1), None, Some(3)]))],
+    )
+    .unwrap();
+    conn.register_batches("registered", schema, vec![batch.clone(), batch])
+        .unwrap();
+
+    let res = connector_arrow::query(&mut conn, "SELECT SUM(a), COUNT(*) FROM registered").unwrap();
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&res).unwrap().to_string(),
+        "+--------+----------+\n\
+         | SUM(a) | COUNT(*) |\n\
+         +--------+----------+\n\
+         | 8      | 6        |\n\
+         +--------+----------+"
+    );
+
+    let err = connector_arrow::query(&mut conn, "DELETE FROM registered").unwrap_err();
+    assert!(matches!(err, ConnectorError::SQLite(_)));
+
+    let schema = Arc::new(Schema::new(vec![Field::new(
+        "a",
+        DataType::Interval(IntervalUnit::DayTime),
+        true,
+    )]));
+    let err = conn
+        .register_batches("unsupported", schema, vec![])
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        TableCreateError::Connector(ConnectorError::IncompatibleSchema { .. })
+    ));
+}
+
+#[test]
+fn register_batches_table_exists() {
+    let mut conn = init();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    conn.table_create("existing", schema.clone()).unwrap();
+    connector_arrow::query(&mut conn, "CREATE TEMP VIEW existing_view AS SELECT 1").unwrap();
+
+    // tables that were not registered are never replaced
+    for name in ["existing", "existing_view"] {
+        let err = conn
+            .register_batches(name, schema.clone(), vec![])
+            .unwrap_err();
+        assert!(matches!(err, TableCreateError::TableExists), "{:?}", err);
+    }
+    conn.table_get("existing").unwrap();
+}
+
+#[test]
+fn register_batches_other_connection() {
+    let mut conn = init();
+    let mut other = init();
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    let batch =
+        RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(vec![1, 2]))]).unwrap();
+    conn.register_batches("registered", schema.clone(), vec![batch])
+        .unwrap();
+    other.register_batches("own", schema, vec![]).unwrap();
+
+    // the id of the batches is in the definition of the virtual table
+    let query = "SELECT sql FROM sqlite_temp_master WHERE name = 'registered'";
+    let res = connector_arrow::query(&mut conn, query).unwrap();
+    let sql = res[0].column(0).as_string::<i32>().value(0).to_string();
+    let using = &sql[sql.find("USING").unwrap()..];
+
+    // batches can only be scanned by the connection that registered them
+    let ddl = format!("CREATE VIRTUAL TABLE temp.stolen {using}");
+    let err = connector_arrow::query(&mut other, &ddl).unwrap_err();
+    assert!(matches!(err, ConnectorError::SQLite(_)), "{:?}", err);
+}
+
 #[test]
-#[ignore] // cannot introspect the Null column
 fn schema_get() {
     let table_name = "schema_get";
 
     let mut conn = init();
     let column_spec = super::spec::basic_types();
-    super::tests::schema_get(&mut conn, table_name, column_spec);
+    let mut rng = rand_chacha::ChaCha8Rng::from_seed([0; 32]);
+    let (schema, batches) = generate_batch(column_spec, &mut rng);
+
+    // SQLite stores the original arrow types, so schema roundtrips exactly
+    load_into_table(&mut conn, schema.clone(), &batches, table_name).unwrap();
+    let schema_introspection = conn.table_get(table_name).unwrap();
+    similar_asserts::assert_eq!(schema, schema_introspection);
+
+    // ... and queries of the table produce the same types
+    let (schema_query, _) = query_table(&mut conn, table_name, '"').unwrap();
+    let types = |s: &Schema| {
+        s.fields()
+            .iter()
+            .map(|f| f.data_type().clone())
+            .collect::<Vec<_>>()
+    };
+    similar_asserts::assert_eq!(types(&schema), types(&schema_query));
+}
+
+#[test]
+fn schema_get_altered() {
+    let table_name = "schema_get_altered";
+
+    let mut conn = init();
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("a", DataType::Int8, false),
+        Field::new(
+            "b",
+            DataType::Timestamp(TimeUnit::Second, Some("+07:30".into())),
+            true,
+        ),
+        Field::new("c", DataType::Null, true),
+    ]));
+    conn.table_create(table_name, schema.clone()).unwrap();
+    similar_asserts::assert_eq!(conn.table_get(table_name).unwrap(), schema);
+
+    // recorded types are kept per column, so they survive altering the table
+    connector_arrow::query(
+        &mut conn,
+        &format!("ALTER TABLE {table_name} ADD COLUMN d TEXT"),
+    )
+    .unwrap();
+    let schema_altered = Arc::new(Schema::new(vec![
+        Field::new("a", DataType::Int8, false),
+        Field::new(
+            "b",
+            DataType::Timestamp(TimeUnit::Second, Some("+07:30".into())),
+            true,
+        ),
+        Field::new("c", DataType::Null, true),
+        Field::new("d", DataType::LargeUtf8, true),
+    ]));
+    similar_asserts::assert_eq!(conn.table_get(table_name).unwrap(), schema_altered);
 }
 
 #[test]
@@ -52,6 +266,16 @@ fn schema_edit() {
     super::tests::schema_edit(&mut conn, table_name, column_spec);
 }
 
+#[test]
+fn append_unsupported() {
+    let mut conn = init();
+    super::tests::append_unsupported(
+        &mut conn,
+        "append_unsupported",
+        DataType::Interval(IntervalUnit::MonthDayNano),
+    );
+}
+
 #[test]
 fn ident_escaping() {
     let table_name = "simple::ident_escaping";
@@ -59,3 +283,266 @@ fn ident_escaping() {
     let mut conn = init();
     super::tests::ident_escaping(&mut conn, table_name);
 }
+
+#[test]
+fn dyn_connector() {
+    let mut conn = connector_arrow::connect("sqlite://:memory:").unwrap();
+    super::tests::dyn_connector(conn.as_mut(), "dyn_connector");
+}
+
+#[test]
+fn dyn_connector_collection() {
+    let path = std::env::temp_dir().join("connector_arrow_dyn_connector_collection.db");
+    let _ = std::fs::remove_file(&path);
+
+    let mut conns: Vec<Box<dyn connector_arrow::dynamic::DynConnector>> = vec![
+        connector_arrow::connect("sqlite://:memory:").unwrap(),
+        connector_arrow::connect(&format!("sqlite://{}", path.display())).unwrap(),
+    ];
+    for (index, conn) in conns.iter_mut().enumerate() {
+        super::tests::dyn_connector(conn.as_mut(), &format!("dyn_connector_{index}"));
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn connect_invalid_url() {
+    let err = connector_arrow::connect("sqlite:memory").err().unwrap();
+    assert!(matches!(err, ConnectorError::InvalidUrl(_)));
+
+    let err = connector_arrow::connect("oracle://localhost")
+        .err()
+        .unwrap();
+    assert!(matches!(err, ConnectorError::InvalidUrl(_)));
+}
+
+#[test]
+fn numeric_affinity() {
+    let table_name = "numeric_affinity";
+
+    let mut conn = init();
+    connector_arrow::query(
+        &mut conn,
+        &format!(
+            "CREATE TABLE {table_name} (dec DECIMAL(10, 2), bool BOOLEAN, dt DATETIME, d DATE)"
+        ),
+    )
+    .unwrap();
+    connector_arrow::query(
+        &mut conn,
+        &format!(
+            "INSERT INTO {table_name} VALUES \
+            (12.5, 1, '2024-02-29 13:45:01.5', '2024-02-29'), \
+            ('-0.01', 0, 1709214301, 1709214301), \
+            (3, 'true', 2460369.5, 2460369.5), \
+            (NULL, NULL, NULL, NULL)"
+        ),
+    )
+    .unwrap();
+
+    let schema_introspected = conn.table_get(table_name).unwrap();
+    let (schema_query, batches) = super::util::query_table(&mut conn, table_name, '"').unwrap();
+    similar_asserts::assert_eq!(schema_introspected, schema_query);
+
+    let field = schema_query.field(0);
+    assert_eq!(field.data_type(), &DataType::Decimal128(10, 2));
+    assert_eq!(
+        field.metadata().get(METADATA_DB_TYPE).map(String::as_str),
+        Some("DECIMAL(10, 2)")
+    );
+
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&batches).unwrap().to_string(),
+        "+-------+-------+-------------------------+------------+
+| dec   | bool  | dt                      | d          |
++-------+-------+-------------------------+------------+
+| 12.50 | true  | 2024-02-29T13:45:01.500 | 2024-02-29 |
+| -0.01 | false | 2024-02-29T13:45:01     | 2024-02-29 |
+| 3.00  | true  | 2024-02-29T00:00:00     | 2024-02-29 |
+|       |       |                         |            |
++-------+-------+-------------------------+------------+"
+    );
+}
+
+#[test]
+fn parse_temporal() {
+    let table_name = "parse_temporal";
+
+    let mut conn = init();
+    connector_arrow::query(
+        &mut conn,
+        &format!("CREATE TABLE {table_name} (ts TIMESTAMP, t TIME)"),
+    )
+    .unwrap();
+    connector_arrow::query(
+        &mut conn,
+        &format!(
+            "INSERT INTO {table_name} VALUES \
+            ('2024-02-29T13:45:01.5Z', '13:45:01.5'), \
+            (1709214301, 1709214301), \
+            (2460369.75, 2460369.75)"
+        ),
+    )
+    .unwrap();
+
+    // without the opt-in, the columns cannot be introspected
+    assert!(conn.table_get(table_name).is_err());
+
+    conn.set_parse_temporal(true);
+    let schema_introspected = conn.table_get(table_name).unwrap();
+    let (schema_query, batches) = super::util::query_table(&mut conn, table_name, '"').unwrap();
+    similar_asserts::assert_eq!(schema_introspected, schema_query);
+
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&batches).unwrap().to_string(),
+        "+-------------------------+--------------+
+| ts                      | t            |
++-------------------------+--------------+
+| 2024-02-29T13:45:01.500 | 13:45:01.500 |
+| 2024-02-29T13:45:01     | 13:45:01     |
+| 2024-02-29T06:00:00     | 06:00:00     |
++-------------------------+--------------+"
+    );
+}
+
+#[test]
+fn parse_temporal_error() {
+    let table_name = "parse_temporal_error";
+
+    let mut conn = init();
+    connector_arrow::query(&mut conn, &format!("CREATE TABLE {table_name} (d DATE)")).unwrap();
+    connector_arrow::query(
+        &mut conn,
+        &format!("INSERT INTO {table_name} VALUES ('2024-02-29'), ('yesterday')"),
+    )
+    .unwrap();
+
+    let err = super::util::query_table(&mut conn, table_name, '"').unwrap_err();
+    assert_eq!(err.to_string(), "Result data does not match the schema");
+    let ConnectorError::DataSchemaMismatch(message) = err else {
+        panic!()
+    };
+    assert_eq!(
+        message,
+        "cannot convert Text(\"yesterday\") in row 1 of column d into Date32"
+    );
+}
+
+#[test]
+fn db_errors() {
+    let mut conn = init();
+    let [syntax, unique, not_null] = super::tests::db_errors(&mut conn, "db_errors");
+
+    assert_eq!(syntax.kind, DbErrorKind::Syntax);
+    assert_eq!(syntax.code.as_deref(), Some("1"));
+
+    assert_eq!(unique.kind, DbErrorKind::ConstraintViolation);
+    assert_eq!(unique.code.as_deref(), Some("2067"));
+    assert_eq!(unique.column.as_deref(), Some("db_errors.a"));
+
+    assert_eq!(not_null.kind, DbErrorKind::ConstraintViolation);
+    assert_eq!(not_null.code.as_deref(), Some("1299"));
+    assert_eq!(not_null.column.as_deref(), Some("db_errors.a"));
+}
+
+#[test]
+fn dictionary() {
+    let table_name = "dictionary";
+
+    let mut conn = init();
+
+    let kind: DictionaryArray<Int32Type> = vec![Some("a"), Some("b"), None, Some("a")]
+        .into_iter()
+        .collect();
+    let name = DictionaryArray::try_new(
+        UInt8Array::from(vec![0, 1, 1, 2]),
+        Arc::new(LargeStringArray::from(vec![Some("x"), None, Some("y")])),
+    )
+    .unwrap();
+    let batch = RecordBatch::try_from_iter(vec![
+        ("kind", Arc::new(kind) as ArrayRef),
+        ("name", Arc::new(name) as ArrayRef),
+    ])
+    .unwrap();
+    let expected = pretty_format_batches(std::slice::from_ref(&batch))
+        .unwrap()
+        .to_string();
+
+    // dictionaries are decoded on append
+    super::util::load_into_table(&mut conn, batch.schema(), &[batch], table_name).unwrap();
+
+    let (schema, batches) = query_table(&mut conn, table_name, '"').unwrap();
+    assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+    assert_eq!(schema.field(1).data_type(), &DataType::LargeUtf8);
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&batches).unwrap().to_string(),
+        expected
+    );
+
+    // ... and can be encoded again on read
+    conn.set_reader_options(ReaderOptions {
+        dictionary_columns: vec!["kind".into(), "name".into()],
+        ..Default::default()
+    });
+    let (schema, batches) = query_table(&mut conn, table_name, '"').unwrap();
+    assert_eq!(
+        schema.field(0).data_type(),
+        &DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+    );
+    assert_eq!(schema.field(1).data_type(), schema.field(0).data_type());
+    assert_eq!(batches[0].column(0).as_any_dictionary().values().len(), 2);
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&batches).unwrap().to_string(),
+        expected
+    );
+}
+
+#[test]
+fn views() {
+    let table_name = "views";
+
+    let mut conn = init();
+
+    let text: StringViewArray = vec![Some("short"), None, Some("longer than twelve bytes")]
+        .into_iter()
+        .collect();
+    let blob: BinaryViewArray = vec![Some(b"\x00\x01".as_slice()), Some(&[0xff; 20]), None]
+        .into_iter()
+        .collect();
+    let batch = RecordBatch::try_from_iter(vec![
+        ("text", Arc::new(text.clone()) as ArrayRef),
+        ("blob", Arc::new(blob.clone()) as ArrayRef),
+    ])
+    .unwrap();
+
+    // views are appended as their non-view counterparts
+    super::util::load_into_table(&mut conn, batch.schema(), &[batch], table_name).unwrap();
+
+    let (schema, batches) = query_table(&mut conn, table_name, '"').unwrap();
+    assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+    assert_eq!(
+        batches[0]
+            .column(0)
+            .as_string::<i32>()
+            .iter()
+            .collect::<Vec<_>>(),
+        text.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        batches[0]
+            .column(1)
+            .as_binary::<i32>()
+            .iter()
+            .collect::<Vec<_>>(),
+        blob.iter().collect::<Vec<_>>()
+    );
+
+    // ... and text can be read into views
+    conn.set_reader_options(ReaderOptions {
+        string_view: true,
+        ..Default::default()
+    });
+    let (schema, batches) = query_table(&mut conn, table_name, '"').unwrap();
+    assert_eq!(schema.field(0).data_type(), &DataType::Utf8View);
+    assert_eq!(batches[0].column(0).as_ref(), &text as &dyn Array);
+}
@@ -41,3 +41,9 @@ fn query_01() {
     let mut conn = init();
     super::tests::query_01(&mut conn);
 }
+
+#[test]
+fn record_batch_reader() {
+    let mut conn = init();
+    super::tests::record_batch_reader(&mut conn);
+}
@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use arrow::array::*;
+use arrow::buffer::NullBuffer;
+use arrow::compute::concat_batches;
+use arrow::datatypes::*;
+use connector_arrow::util::transport::transport;
+use connector_arrow::util::{ArrayCellRef, ArrowRowWriter};
+
+fn nested_batch() -> RecordBatch {
+    let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+        Some(vec![Some(1), None]),
+        None,
+        Some(vec![]),
+        Some(vec![Some(4)]),
+    ]);
+    let large_list = LargeListArray::from_iter_primitive::<Float64Type, _, _>(vec![
+        None,
+        Some(vec![Some(0.5)]),
+        Some(vec![Some(1.5), Some(2.5), None]),
+        Some(vec![]),
+    ]);
+    let fixed_size_list = FixedSizeListArray::from_iter_primitive::<Int64Type, _, _>(
+        vec![
+            Some(vec![Some(1), Some(2)]),
+            None,
+            Some(vec![None, Some(3)]),
+            Some(vec![Some(4), None]),
+        ],
+        2,
+    );
+
+    let strukt = StructArray::try_new(
+        Fields::from(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Utf8, true),
+        ]),
+        vec![
+            Arc::new(Int32Array::from(vec![Some(1), Some(2), None, Some(4)])),
+            Arc::new(StringArray::from(vec![
+                Some("one"),
+                None,
+                None,
+                Some("four"),
+            ])),
+        ],
+        Some(NullBuffer::from(vec![true, true, false, true])),
+    )
+    .unwrap();
+
+    let mut map = MapBuilder::new(None, StringBuilder::new(), Int64Builder::new());
+    map.keys().append_value("a");
+    map.values().append_value(1);
+    map.keys().append_value("b");
+    map.values().append_null();
+    map.append(true).unwrap();
+    map.append(false).unwrap();
+    map.append(true).unwrap();
+    map.keys().append_value("c");
+    map.values().append_value(3);
+    map.append(true).unwrap();
+    let map = map.finish();
+
+    let mut list_of_lists = ListBuilder::new(ListBuilder::new(Int32Builder::new()));
+    list_of_lists.values().values().append_value(1);
+    list_of_lists.values().append(true);
+    list_of_lists.values().append(false);
+    list_of_lists.append(true);
+    list_of_lists.append(false);
+    list_of_lists.append(true);
+    list_of_lists.values().append(true);
+    list_of_lists.append(true);
+    let list_of_lists = list_of_lists.finish();
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(list),
+        Arc::new(large_list),
+        Arc::new(fixed_size_list),
+        Arc::new(strukt),
+        Arc::new(map),
+        Arc::new(list_of_lists),
+    ];
+    let fields = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| Field::new(format!("c{i}"), c.data_type().clone(), true))
+        .collect::<Vec<_>>();
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap()
+}
+
+/// Transports the batch cell-by-cell into an [ArrowRowWriter] with given schema.
+fn write_rows(batch: &RecordBatch, schema: SchemaRef, min_batch_size: usize) -> Vec<RecordBatch> {
+    let mut writer = ArrowRowWriter::new(schema.clone(), min_batch_size);
+    for row_number in 0..batch.num_rows() {
+        writer.prepare_for_batch(1).unwrap();
+
+        for (array, field) in batch.columns().iter().zip(batch.schema().fields()) {
+            let cell = ArrayCellRef {
+                array,
+                field,
+                row_number,
+            };
+            let target = schema.field_with_name(field.name()).unwrap();
+            transport(target, &cell, &mut writer).unwrap();
+        }
+    }
+    writer.finish().unwrap()
+}
+
+#[test]
+fn nested_types() {
+    let batch = nested_batch();
+    let schema = batch.schema();
+
+    // small batch size, so the values are split into multiple batches
+    let batches = write_rows(&batch, schema.clone(), 3);
+    assert_eq!(batches.len(), 2);
+
+    similar_asserts::assert_eq!(batch, concat_batches(&schema, &batches).unwrap());
+}
+
+#[test]
+fn dictionary() {
+    let encoded = DictionaryArray::try_new(
+        Int8Array::from(vec![Some(0), None, Some(1), Some(2), Some(0)]),
+        Arc::new(LargeStringArray::from(vec![Some("a"), None, Some("b")])),
+    )
+    .unwrap();
+    let plain = StringArray::from(vec![Some("x"), Some("y"), None, Some("x"), Some("x")]);
+    let batch = RecordBatch::try_from_iter(vec![
+        ("encoded", Arc::new(encoded) as ArrayRef),
+        ("plain", Arc::new(plain) as ArrayRef),
+    ])
+    .unwrap();
+
+    // decode the first column and encode the second
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("encoded", DataType::LargeUtf8, true),
+        Field::new(
+            "plain",
+            DataType::Dictionary(Box::new(DataType::UInt16), Box::new(DataType::Utf8)),
+            true,
+        ),
+    ]));
+    let batches = write_rows(&batch, schema, 5);
+
+    let decoded = batches[0].column(0).as_string::<i64>();
+    let decoded: Vec<_> = decoded.iter().collect();
+    assert_eq!(decoded, vec![Some("a"), None, None, Some("b"), Some("a")]);
+
+    let dictionary = batches[0].column(1).as_dictionary::<UInt16Type>();
+    assert_eq!(dictionary.values().len(), 2);
+    similar_asserts::assert_eq!(
+        arrow::util::pretty::pretty_format_batches(&[batch])
+            .unwrap()
+            .to_string(),
+        arrow::util::pretty::pretty_format_batches(&batches)
+            .unwrap()
+            .to_string(),
+    );
+}
+
+#[test]
+fn dictionary_unsupported_values() {
+    let fields = Fields::from(vec![Field::new(
+        "a",
+        DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Int64)),
+        true,
+    )]);
+
+    let res = ArrayCellRef::transporters::<ArrowRowWriter>(&fields);
+    assert!(matches!(
+        res,
+        Err(connector_arrow::ConnectorError::NotSupported { .. })
+    ));
+}
+
+#[test]
+fn row_writer_unsupported_dictionary() {
+    let schema = Arc::new(Schema::new(vec![Field::new(
+        "a",
+        DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Int64)),
+        true,
+    )]));
+
+    let mut writer = ArrowRowWriter::new(schema, 1);
+    let res = writer.prepare_for_batch(1);
+    assert!(matches!(
+        res,
+        Err(connector_arrow::ConnectorError::NotSupported { .. })
+    ));
+}
@@ -2,13 +2,18 @@
 
 use std::sync::Arc;
 
-use arrow::array::{ArrayRef, Int64Builder, RecordBatch};
-use arrow::datatypes::{Field, Schema};
+use arrow::array::{ArrayRef, Int64Array, Int64Builder, RecordBatch, RecordBatchReader};
+use arrow::compute::concat_batches;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::error::ArrowError;
 use arrow::util::pretty::pretty_format_batches;
 use connector_arrow::api::{
     Append, ArrowValue, Connector, ResultReader, SchemaEdit, SchemaGet, Statement,
 };
-use connector_arrow::{util::coerce, TableCreateError, TableDropError};
+use connector_arrow::dynamic::DynConnector;
+use connector_arrow::{
+    util::coerce, ConnectorError, DbErrorDetails, TableCreateError, TableDropError,
+};
 use rand::SeedableRng;
 
 use crate::util::{coerce_type, load_into_table, query_table};
@@ -28,6 +33,27 @@ pub fn query_01<C: Connector>(conn: &mut C) {
     );
 }
 
+pub fn record_batch_reader<C: Connector>(conn: &mut C) {
+    fn concat(reader: impl RecordBatchReader) -> RecordBatch {
+        let schema = reader.schema();
+        let batches = reader.collect::<Result<Vec<_>, ArrowError>>().unwrap();
+        concat_batches(&schema, &batches).unwrap()
+    }
+
+    let mut stmt = conn.query("SELECT 1 as a, NULL as b").unwrap();
+    let reader = stmt.start([]).unwrap();
+    let batch = concat(reader.into_record_batch_reader().unwrap());
+
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&[batch]).unwrap().to_string(),
+        "+---+---+\n\
+         | a | b |\n\
+         +---+---+\n\
+         | 1 |   |\n\
+         +---+---+"
+    );
+}
+
 pub fn query_02<C: Connector>(conn: &mut C) {
     let query = "SELECT
         CAST(45927858023429386042648415184323464939503124872489107431467725871003289085860801 as NUMERIC(100, 20)) as a,
@@ -101,6 +127,34 @@ pub fn roundtrip<C>(
     similar_asserts::assert_eq!(batches_coerced, batches_query);
 }
 
+/// Same as [roundtrip], but compares the data regardless of how it was split into batches.
+pub fn roundtrip_many_rows<C>(
+    conn: &mut C,
+    table_name: &str,
+    spec: ArrowGenSpec,
+    ident_quote_char: char,
+    nullable_results: bool,
+) where
+    C: Connector + SchemaEdit,
+{
+    let mut rng = rand_chacha::ChaCha8Rng::from_seed([0; 32]);
+    let (schema, batches) = generate_batch(spec, &mut rng);
+
+    load_into_table(conn, schema.clone(), &batches, table_name).unwrap();
+
+    let override_nullable = if !nullable_results { Some(true) } else { None };
+    let (schema_coerced, batches_coerced) =
+        coerce::coerce_batches(schema, &batches, coerce_type::<C>, override_nullable).unwrap();
+
+    let (schema_query, batches_query) = query_table(conn, table_name, ident_quote_char).unwrap();
+
+    similar_asserts::assert_eq!(schema_coerced, schema_query);
+    similar_asserts::assert_eq!(
+        concat_batches(&schema_coerced, &batches_coerced).unwrap(),
+        concat_batches(&schema_query, &batches_query).unwrap()
+    );
+}
+
 pub fn schema_get<C>(conn: &mut C, table_name: &str, spec: ArrowGenSpec)
 where
     C: Connector + SchemaEdit + SchemaGet,
@@ -140,6 +194,110 @@ where
     ));
 }
 
+pub fn schema_create_unsupported<C>(conn: &mut C, table_name: &str, ty: DataType)
+where
+    C: Connector + SchemaEdit,
+{
+    let field = Field::new("interval_col", ty, true);
+    let schema = Arc::new(Schema::new(vec![field]));
+
+    let err = conn.table_create(table_name, schema).unwrap_err();
+    match err {
+        TableCreateError::Connector(ConnectorError::IncompatibleSchema {
+            table_name: t,
+            message,
+            ..
+        }) => {
+            assert_eq!(t, table_name);
+            assert!(message.contains("interval_col"), "{}", message);
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+pub fn append_unsupported<C>(conn: &mut C, table_name: &str, ty: DataType)
+where
+    C: Connector + SchemaEdit,
+{
+    let field = Field::new("interval_col", DataType::Int64, true);
+    let schema = Arc::new(Schema::new(vec![field]));
+    load_into_table(conn, schema, &[], table_name).unwrap();
+
+    let array = arrow::array::new_null_array(&ty, 2);
+    let field = Field::new("interval_col", ty, true);
+    let schema = Arc::new(Schema::new(vec![field]));
+    let batch = RecordBatch::try_new(schema, vec![array]).unwrap();
+
+    let mut appender = conn.append(table_name).unwrap();
+    match appender.append(batch).unwrap_err() {
+        ConnectorError::NotSupported { feature, .. } => {
+            assert!(feature.contains("interval_col"), "{}", feature);
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+/// Returns details of a syntax error, a unique violation and a not null violation.
+pub fn db_errors<C: Connector>(conn: &mut C, table_name: &str) -> [DbErrorDetails; 3] {
+    let _ = connector_arrow::query(conn, &format!("DROP TABLE {table_name}"));
+    let ddl = format!("CREATE TABLE {table_name} (a INTEGER NOT NULL UNIQUE)");
+    connector_arrow::query(conn, &ddl).unwrap();
+    connector_arrow::query(conn, &format!("INSERT INTO {table_name} VALUES (1)")).unwrap();
+
+    let syntax = connector_arrow::query(conn, "SELEC 1").unwrap_err();
+    let unique = connector_arrow::query(conn, &format!("INSERT INTO {table_name} VALUES (1)"));
+    let not_null = connector_arrow::query(conn, &format!("INSERT INTO {table_name} VALUES (NULL)"));
+
+    [syntax, unique.unwrap_err(), not_null.unwrap_err()].map(|err| {
+        assert!(!err.is_retryable());
+        err.db_error().unwrap()
+    })
+}
+
+pub fn dyn_connector(conn: &mut dyn DynConnector, table_name: &str) {
+    let _ = conn.table_drop(table_name);
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+    conn.table_create(table_name, schema.clone()).unwrap();
+    assert!(conn.table_list().unwrap().contains(&table_name.to_string()));
+
+    let array = Arc::new(Int64Array::from(vec![Some(1), None])) as ArrayRef;
+    let batch = RecordBatch::try_new(schema, vec![array]).unwrap();
+    conn.append(table_name, vec![batch.clone()]).unwrap();
+
+    let batches = conn.query(&format!("SELECT a FROM {table_name}")).unwrap();
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&batches).unwrap().to_string(),
+        pretty_format_batches(std::slice::from_ref(&batch))
+            .unwrap()
+            .to_string()
+    );
+
+    let mut appender = conn.appender(table_name).unwrap();
+    appender.append(batch.clone()).unwrap();
+    appender.finish().unwrap();
+
+    let mut stmt = conn
+        .prepare(&format!("SELECT a FROM {table_name}"))
+        .unwrap();
+    let reader = stmt.start(&[]).unwrap();
+    let schema = reader.schema();
+    let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+    similar_asserts::assert_eq!(
+        pretty_format_batches(&batches).unwrap().to_string(),
+        pretty_format_batches(&[batch.clone(), batch])
+            .unwrap()
+            .to_string()
+    );
+    assert_eq!(schema.field(0).name(), "a");
+    drop(stmt);
+
+    let db_ty = conn.type_arrow_into_db(&DataType::Int64).unwrap();
+    assert!(conn.type_db_into_arrow(&db_ty).is_some());
+
+    conn.table_drop(table_name).unwrap();
+}
+
 pub fn ident_escaping<C>(conn: &mut C, table_name_prefix: &str)
 where
     C: Connector + SchemaEdit + SchemaGet,
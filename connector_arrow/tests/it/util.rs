@@ -195,3 +195,74 @@ impl<S1: ToString, S2: ToString, V: ArrowValue> From<(S1, S2, V)> for QueryOfSin
         }
     }
 }
+
+/// A batch with list, struct and map columns, including nulls and empty containers.
+#[cfg(any(feature = "src_duckdb", feature = "src_postgres"))]
+pub fn containers_batch() -> RecordBatch {
+    use arrow::array::{
+        Array, Int32Array, Int32Builder, Int64Array, ListArray, MapBuilder, MapFieldNames,
+        StringArray, StringBuilder, StructArray,
+    };
+    use arrow::buffer::{NullBuffer, OffsetBuffer};
+    use arrow::datatypes::Int32Type;
+
+    let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+        Some(vec![Some(1), None, Some(3)]),
+        None,
+        Some(vec![]),
+        Some(vec![Some(4)]),
+    ]);
+
+    let fields = vec![
+        Field::new("a", DataType::Int64, true),
+        Field::new("b", DataType::Utf8, true),
+    ];
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from(vec![Some(1), None, None, Some(4)])),
+        Arc::new(StringArray::from(vec![Some("a"), None, Some("c"), None])),
+    ];
+    let nulls = NullBuffer::from(vec![true, false, true, true]);
+    let structs = StructArray::new(fields.into(), columns, Some(nulls));
+
+    let item_fields = vec![Field::new("x", DataType::Int32, true)];
+    let item_values: Vec<ArrayRef> = vec![Arc::new(Int32Array::from(vec![Some(1), None, Some(3)]))];
+    let items = StructArray::new(item_fields.into(), item_values, None);
+    let item = Field::new("item", items.data_type().clone(), true);
+    let list_of_structs = ListArray::new(
+        Arc::new(item),
+        OffsetBuffer::from_lengths([2, 0, 1, 0]),
+        Arc::new(items),
+        Some(NullBuffer::from(vec![true, true, true, false])),
+    );
+
+    let names = MapFieldNames {
+        entry: "entries".into(),
+        key: "key".into(),
+        value: "value".into(),
+    };
+    let mut map = MapBuilder::new(Some(names), StringBuilder::new(), Int32Builder::new());
+    map.keys().append_value("a");
+    map.values().append_value(1);
+    map.keys().append_value("b");
+    map.values().append_null();
+    map.append(true).unwrap();
+    map.append(false).unwrap();
+    map.append(true).unwrap();
+    map.keys().append_value("c");
+    map.values().append_value(3);
+    map.append(true).unwrap();
+    let map = map.finish();
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(list),
+        Arc::new(structs),
+        Arc::new(list_of_structs),
+        Arc::new(map),
+    ];
+    let fields = ["list", "struct", "list_of_structs", "map"]
+        .iter()
+        .zip(&columns)
+        .map(|(name, array)| Field::new(*name, array.data_type().clone(), true))
+        .collect::<Vec<_>>();
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap()
+}